@@ -0,0 +1,101 @@
+//! IP-XACT components, IEEE 1685-2009 (`spirit:`) and 1685-2014 (`ipxact:`).
+
+use crate::xml::{
+    access, child, children, description, number, required_number, required_text, small,
+};
+use crate::{Access, Device, EnumeratedValue, Error, Field, Peripheral, Register};
+
+use roxmltree::{Document, Node};
+
+pub fn parse(doc: &Document) -> Result<Device, Error> {
+    let component = doc.root_element();
+    let mut peripherals = vec![];
+    for map in child(component, "memoryMaps")
+        .into_iter()
+        .flat_map(|list| children(list, "memoryMap"))
+    {
+        for block in children(map, "addressBlock") {
+            let width = number(block, "width")?;
+            let block_access = access(block)?;
+            peripherals.push(Peripheral {
+                name: required_text(block, "name")?.to_string(),
+                description: description(block),
+                base_address: required_number(block, "baseAddress")?,
+                registers: children(block, "register")
+                    .map(|register| read_register(register, width, block_access))
+                    .collect::<Result<_, _>>()?,
+            });
+        }
+    }
+    Ok(Device {
+        name: required_text(component, "name")?.to_string(),
+        description: description(component),
+        peripherals,
+    })
+}
+
+fn read_register(
+    node: Node,
+    width: Option<u64>,
+    block_access: Option<Access>,
+) -> Result<Register, Error> {
+    let size = number(node, "size")?.or(width).unwrap_or(32);
+    let access = access(node)?.or(block_access).unwrap_or_default();
+    let fields = children(node, "field")
+        .map(|field| read_field(field, access))
+        .collect::<Result<Vec<_>, _>>()?;
+    // 1685-2009 gives the reset value of the whole register, 1685-2014 one for each field.
+    let mut reset_value = reset(node)?.unwrap_or(0);
+    for field in children(node, "field") {
+        let offset = required_number(field, "bitOffset")?;
+        if let Some(value) = reset(field)?.filter(|_| offset < 64) {
+            let mask = u64::MAX >> (64 - required_number(field, "bitWidth")?.clamp(1, 64));
+            reset_value = reset_value & !(mask << offset) | (value & mask) << offset;
+        }
+    }
+    let dim = number(node, "dim")?.or(child(node, "array")
+        .map(|array| required_number(array, "dim"))
+        .transpose()?);
+
+    Ok(Register {
+        name: required_text(node, "name")?.to_string(),
+        description: description(node),
+        offset: required_number(node, "addressOffset")?,
+        size: small("size", size)?,
+        access,
+        reset_value,
+        dim: dim.map(|dim| small("dim", dim)).transpose()?,
+        fields,
+    })
+}
+
+fn read_field(node: Node, register_access: Access) -> Result<Field, Error> {
+    let enumerated_values = child(node, "enumeratedValues")
+        .into_iter()
+        .flat_map(|list| children(list, "enumeratedValue"))
+        .map(|value| {
+            Ok(EnumeratedValue {
+                name: required_text(value, "name")?.to_string(),
+                description: description(value),
+                value: required_number(value, "value")?,
+            })
+        })
+        .collect::<Result<_, Error>>()?;
+    Ok(Field {
+        name: required_text(node, "name")?.to_string(),
+        description: description(node),
+        bit_offset: small("bitOffset", required_number(node, "bitOffset")?)?,
+        bit_width: small("bitWidth", required_number(node, "bitWidth")?)?,
+        access: access(node)?.unwrap_or(register_access),
+        enumerated_values,
+    })
+}
+
+/// The reset value of a register or field, in `<reset>` or `<resets><reset>`.
+fn reset(node: Node) -> Result<Option<u64>, Error> {
+    child(node, "resets")
+        .and_then(|resets| child(resets, "reset"))
+        .or_else(|| child(node, "reset"))
+        .map(|reset| required_number(reset, "value"))
+        .transpose()
+}
@@ -0,0 +1,205 @@
+//! Generates `dials` specs from the register descriptions that vendors publish, in CMSIS-SVD or
+//! IP-XACT XML.
+//!
+//! Each peripheral becomes a module holding its base address, a `spec!` struct for every register
+//! with fields, with their access modes, reset values and enumerated values, and a
+//! `register_block!` laying the registers out. From a build script:
+//!
+//! ```no_run
+//! let out = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+//! dials_import::generate("device.svd", out.join("device.rs")).unwrap();
+//! ```
+//!
+//! and then `include!(concat!(env!("OUT_DIR"), "/device.rs"));` in the crate.
+
+mod ip_xact;
+mod rust;
+mod svd;
+mod xml;
+
+use std::fmt;
+use std::path::Path;
+
+/// A chip, or an IP block, described by an SVD or IP-XACT file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Device {
+    pub name: String,
+    pub description: Option<String>,
+    pub peripherals: Vec<Peripheral>,
+}
+
+/// A block of registers at a base address. IP-XACT address blocks are read as peripherals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Peripheral {
+    pub name: String,
+    pub description: Option<String>,
+    pub base_address: u64,
+    pub registers: Vec<Register>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Register {
+    pub name: String,
+    pub description: Option<String>,
+    /// The byte offset of the register from the base address of its peripheral.
+    pub offset: u64,
+    /// The width of the register in bits.
+    pub size: u32,
+    pub access: Access,
+    pub reset_value: u64,
+    /// The number of registers in an array of registers, declared with `dim` and a `[%s]` name.
+    pub dim: Option<u32>,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    pub description: Option<String>,
+    /// The lowest bit of the field.
+    pub bit_offset: u32,
+    pub bit_width: u32,
+    pub access: Access,
+    pub enumerated_values: Vec<EnumeratedValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumeratedValue {
+    pub name: String,
+    pub description: Option<String>,
+    pub value: u64,
+}
+
+/// How software may access a register or field, as spelled in both SVD and IP-XACT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Access {
+    ReadOnly,
+    WriteOnly,
+    #[default]
+    ReadWrite,
+    WriteOnce,
+    ReadWriteOnce,
+}
+
+impl Access {
+    /// Parses `read-only`, `write-only`, `read-write`, `writeOnce` or `read-writeOnce`.
+    pub fn parse(access: &str) -> Option<Self> {
+        match access {
+            "read-only" => Some(Access::ReadOnly),
+            "write-only" => Some(Access::WriteOnly),
+            "read-write" => Some(Access::ReadWrite),
+            "writeOnce" => Some(Access::WriteOnce),
+            "read-writeOnce" => Some(Access::ReadWriteOnce),
+            _ => None,
+        }
+    }
+
+    /// The mode as written in `#[access(..)]`.
+    pub fn attribute(self) -> &'static str {
+        match self {
+            Access::ReadOnly => "read_only",
+            Access::WriteOnly => "write_only",
+            Access::ReadWrite => "read_write",
+            Access::WriteOnce => "write_once",
+            Access::ReadWriteOnce => "read_write_once",
+        }
+    }
+}
+
+/// A description that could not be read or understood.
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Xml(roxmltree::Error),
+    /// The root element is neither an SVD `device` nor an IP-XACT `component`.
+    UnknownFormat(String),
+    /// A required element is missing.
+    Missing {
+        element: &'static str,
+        parent: String,
+    },
+    /// An element doesn't hold a value of the expected form.
+    Invalid {
+        element: &'static str,
+        value: String,
+    },
+    /// A `derivedFrom` names a peripheral or register that isn't declared before it.
+    UnknownBase(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{e}"),
+            Error::Xml(e) => write!(f, "{e}"),
+            Error::UnknownFormat(root) => write!(
+                f,
+                "expected an SVD `device` or IP-XACT `component`, found `{root}`"
+            ),
+            Error::Missing { element, parent } => {
+                write!(f, "`{parent}` has no `{element}`")
+            }
+            Error::Invalid { element, value } => {
+                write!(f, "`{value}` is not a valid `{element}`")
+            }
+            Error::UnknownBase(name) => {
+                write!(
+                    f,
+                    "`derivedFrom` names `{name}`, which is not declared before it"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Xml(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<roxmltree::Error> for Error {
+    fn from(e: roxmltree::Error) -> Self {
+        Error::Xml(e)
+    }
+}
+
+/// Reads a CMSIS-SVD device description.
+pub fn parse_svd(text: &str) -> Result<Device, Error> {
+    svd::parse(&roxmltree::Document::parse(text)?)
+}
+
+/// Reads an IP-XACT component, from either the 1685-2009 or 1685-2014 schema.
+pub fn parse_ip_xact(text: &str) -> Result<Device, Error> {
+    ip_xact::parse(&roxmltree::Document::parse(text)?)
+}
+
+/// Reads an SVD or IP-XACT description, telling them apart by their root element.
+pub fn parse(text: &str) -> Result<Device, Error> {
+    let doc = roxmltree::Document::parse(text)?;
+    match doc.root_element().tag_name().name() {
+        "device" => svd::parse(&doc),
+        "component" => ip_xact::parse(&doc),
+        root => Err(Error::UnknownFormat(root.to_string())),
+    }
+}
+
+/// Generates the specs for the description at `input` into `output`, for use from a build
+/// script. Cargo is told to run the build script again when `input` changes.
+pub fn generate(input: impl AsRef<Path>, output: impl AsRef<Path>) -> Result<(), Error> {
+    let input = input.as_ref();
+    println!("cargo:rerun-if-changed={}", input.display());
+    let device = parse(&std::fs::read_to_string(input)?)?;
+    std::fs::write(output, device.to_rust())?;
+    Ok(())
+}
@@ -0,0 +1,387 @@
+//! Writes a [`Device`] as Rust source that declares its registers with the `dials` macros.
+
+use crate::{Access, Device, Field, Peripheral, Register};
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+impl Device {
+    /// Rust source with a module for each peripheral, holding its base address, a `spec!` struct
+    /// for every register with fields and a `register_block!` laying its registers out.
+    pub fn to_rust(&self) -> String {
+        let mut out = format!(
+            "// Generated by dials-import from the description of `{}`. Do not edit.\n",
+            self.name
+        );
+        if let Some(description) = &self.description {
+            writeln!(out, "//\n// {description}").unwrap();
+        }
+        let mut modules = Names::default();
+        for peripheral in &self.peripherals {
+            out.push('\n');
+            peripheral.write_rust(&mut out, &modules.unique(snake_case(&peripheral.name)));
+        }
+        out
+    }
+}
+
+impl Peripheral {
+    fn write_rust(&self, out: &mut String, module: &str) {
+        let mut types = Names::default();
+        let mut members = Names::default();
+        // The registers, with the name of the spec each holds if it has fields.
+        let registers = self
+            .registers
+            .iter()
+            .map(|r| {
+                let ty = (!r.fields.is_empty()).then(|| {
+                    let ty = types.unique(camel_case(&r.name));
+                    types.0.insert(format!("{ty}Fields"));
+                    ty
+                });
+                (r, members.unique(snake_case(&r.name)), ty)
+            })
+            .collect::<Vec<_>>();
+        let mut block = camel_case(&self.name);
+        if types.0.contains(&block) {
+            block.push_str("Block");
+        }
+        let block = types.unique(block);
+
+        doc(out, "", self.description.as_deref());
+        writeln!(out, "pub mod {module} {{").unwrap();
+        writeln!(
+            out,
+            "    /// The address of the `{}` registers.\n    pub const BASE_ADDRESS: usize = {:#x};",
+            self.name, self.base_address
+        )
+        .unwrap();
+
+        let specs = registers
+            .iter()
+            .filter_map(|(r, _, ty)| Some((*r, ty.as_deref()?)))
+            .filter(|(r, _)| container(r.size).is_some())
+            .collect::<Vec<_>>();
+        if !specs.is_empty() {
+            writeln!(out, "\n    dials::spec! {{").unwrap();
+            for (idx, (register, ty)) in specs.into_iter().enumerate() {
+                if idx > 0 {
+                    out.push('\n');
+                }
+                register.write_spec(out, ty, &mut types);
+            }
+            writeln!(out, "    }}").unwrap();
+        }
+
+        let mut by_offset = registers.iter().collect::<Vec<_>>();
+        by_offset.sort_by_key(|(r, _, _)| r.offset);
+
+        let mut layout = String::new();
+        let mut end = 0;
+        for (register, member, ty) in by_offset {
+            let Some(int) = container(register.size) else {
+                writeln!(
+                    layout,
+                    "            // `{}` is left out, as it is {} bits wide.",
+                    register.name, register.size
+                )
+                .unwrap();
+                continue;
+            };
+            if register.offset < end {
+                writeln!(
+                    layout,
+                    "            // `{}` at {:#x} is left out, as it overlaps the register before it.",
+                    register.name, register.offset
+                )
+                .unwrap();
+                continue;
+            }
+            let ty = ty.clone().unwrap_or_else(|| int.to_string());
+            let bytes = u64::from(register.size / 8);
+            doc(&mut layout, "            ", register.description.as_deref());
+            match register.dim {
+                Some(dim) => {
+                    end = register.offset + bytes * u64::from(dim);
+                    writeln!(
+                        layout,
+                        "            {:#x}..{end:#x} => {member}: [{ty}; {dim}],",
+                        register.offset
+                    )
+                    .unwrap();
+                }
+                None => {
+                    end = register.offset + bytes;
+                    writeln!(
+                        layout,
+                        "            {:#x} => {member}: {ty},",
+                        register.offset
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        if layout.contains("=>") {
+            writeln!(out, "\n    dials::register_block! {{").unwrap();
+            doc(out, "        ", self.description.as_deref());
+            writeln!(
+                out,
+                "        pub struct {block} {{\n{layout}        }}\n    }}"
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "
+    /// The `{name}` registers at [`BASE_ADDRESS`].
+    ///
+    /// # Safety
+    ///
+    /// The registers must be mapped at [`BASE_ADDRESS`], see [`{block}Regs::from_ptr`].
+    pub unsafe fn regs() -> {block}Regs {{
+        unsafe {{ {block}Regs::from_ptr(BASE_ADDRESS as *mut {block}) }}
+    }}",
+                name = self.name,
+            )
+            .unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+    }
+}
+
+impl Register {
+    /// Writes the register as a `spec!` struct called `ty`, preceded by the enums of its fields.
+    fn write_spec(&self, out: &mut String, ty: &str, types: &mut Names) {
+        let mut fields = self.fields.iter().collect::<Vec<_>>();
+        fields.sort_by_key(|f| f.bit_offset);
+
+        let mut body = String::new();
+        let mut members = Names::default();
+        let mut bit = 0;
+        for field in fields {
+            let end = field.bit_offset + field.bit_width;
+            if field.bit_width == 0 || field.bit_offset < bit || end > self.size {
+                writeln!(
+                    body,
+                    "            // `{}` is left out, as it overlaps another field or lies outside the register.",
+                    field.name
+                )
+                .unwrap();
+                continue;
+            }
+            if field.bit_offset > bit {
+                reserved(&mut body, bit, field.bit_offset, &mut members);
+            }
+            bit = end;
+
+            let name = members.unique(snake_case(&field.name));
+            let values = field.variants();
+            let mut description = field.description.clone();
+            let decl = match field.bit_width {
+                1 => {
+                    // Single bits can't hold an enum, so their values are only documented.
+                    for (variant, value) in &values {
+                        let line = format!("`{value}`: {}", variant.name);
+                        description = Some(match description {
+                            Some(d) => format!("{d}\n\n{line}"),
+                            None => line,
+                        });
+                    }
+                    format!("bool @ {}", field.bit_offset)
+                }
+                width if values.is_empty() => {
+                    format!("u{width} @ {}..{end}", field.bit_offset)
+                }
+                width => {
+                    let enum_ty = types.unique(format!("{ty}{}", camel_case(&field.name)));
+                    field.write_enum(out, &enum_ty, &values);
+                    format!("u{width} as {enum_ty} @ {}..{end}", field.bit_offset)
+                }
+            };
+            for line in description.iter().flat_map(|d| d.split('\n')) {
+                doc(
+                    &mut body,
+                    "            ",
+                    Some(line).filter(|l| !l.is_empty()),
+                );
+                if line.is_empty() {
+                    writeln!(body, "            ///").unwrap();
+                }
+            }
+            if field.access != Access::ReadWrite {
+                writeln!(body, "            #[access({})]", field.access.attribute()).unwrap();
+            }
+            writeln!(body, "            {name}: {decl},").unwrap();
+        }
+        if bit < self.size {
+            reserved(&mut body, bit, self.size, &mut members);
+        }
+
+        doc(out, "        ", self.description.as_deref());
+        let reset = self.reset_value & (u64::MAX >> (64 - self.size));
+        if reset != 0 {
+            writeln!(out, "        #[reset({reset:#x})]").unwrap();
+        }
+        write!(out, "        struct {ty} {{\n{body}        }}\n").unwrap();
+    }
+}
+
+impl Field {
+    /// The enumerated values that fit the field, named as enum variants, without repeats.
+    fn variants(&self) -> Vec<(Variant, u64)> {
+        let mut names = Names::default();
+        let mut seen = HashSet::new();
+        self.enumerated_values
+            .iter()
+            .filter(|v| self.bit_width >= 64 || v.value >> self.bit_width == 0)
+            .filter(|v| seen.insert(v.value))
+            .map(|v| {
+                let name = names.unique(camel_case(&v.name));
+                let variant = Variant {
+                    name,
+                    description: v.description.clone(),
+                };
+                (variant, v.value)
+            })
+            .collect()
+    }
+
+    fn write_enum(&self, out: &mut String, name: &str, variants: &[(Variant, u64)]) {
+        let summary = match &self.description {
+            Some(description) => description.clone(),
+            None => format!("The values of `{}`.", self.name),
+        };
+        doc(out, "        ", Some(&summary));
+        writeln!(out, "        enum {name} {{").unwrap();
+        for (variant, value) in variants {
+            doc(out, "            ", variant.description.as_deref());
+            writeln!(out, "            {} = {value},", variant.name).unwrap();
+        }
+        writeln!(out, "        }}\n").unwrap();
+    }
+}
+
+struct Variant {
+    name: String,
+    description: Option<String>,
+}
+
+/// Names already taken in a scope, handing out numbered names for clashes.
+#[derive(Default)]
+struct Names(HashSet<String>);
+
+impl Names {
+    fn unique(&mut self, name: String) -> String {
+        let mut unique = name.clone();
+        let mut n = 2;
+        while !self.0.insert(unique.clone()) {
+            unique = format!("{name}{n}");
+            n += 1;
+        }
+        unique
+    }
+}
+
+/// Fills the bits `start..end`, which no field covers, with a reserved field.
+fn reserved(out: &mut String, start: u32, end: u32, members: &mut Names) {
+    let name = members.unique(format!("reserved{start}"));
+    let decl = match end - start {
+        1 => format!("bool @ {start}"),
+        width => format!("u{width} @ {start}..{end}"),
+    };
+    writeln!(out, "            #[reserved]\n            {name}: {decl},").unwrap();
+}
+
+fn doc(out: &mut String, indent: &str, doc: Option<&str>) {
+    if let Some(doc) = doc {
+        writeln!(out, "{indent}/// {doc}").unwrap();
+    }
+}
+
+/// The integer type of a register `size` bits wide, if there is one.
+fn container(size: u32) -> Option<&'static str> {
+    match size {
+        8 => Some("u8"),
+        16 => Some("u16"),
+        32 => Some("u32"),
+        64 => Some("u64"),
+        _ => None,
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+    "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true", "try", "type",
+    "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield",
+];
+
+/// The methods of a spec struct that a field of the same name would clash with.
+const SPEC_METHODS: &[&str] = &["new", "matches", "decode", "diff", "validate", "raw"];
+
+/// `TX_DATA`, `TxData` or `txData` as `tx_data`.
+fn snake_case(name: &str) -> String {
+    let mut snake = words(name)
+        .iter()
+        .map(|w| w.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    if !snake.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        snake.insert(0, 'n');
+    }
+    if KEYWORDS.contains(&&*snake) || SPEC_METHODS.contains(&&*snake) {
+        snake.push('_');
+    }
+    snake
+}
+
+/// `TX_DATA`, `TXDATA` or `txData` as `TxData`, `Txdata` and `TxData`.
+fn camel_case(name: &str) -> String {
+    let mut camel = words(name)
+        .iter()
+        .map(|w| {
+            let rest = match w.chars().any(|c| c.is_ascii_lowercase()) {
+                true => w[1..].to_string(),
+                false => w[1..].to_ascii_lowercase(),
+            };
+            format!("{}{rest}", w[..1].to_ascii_uppercase())
+        })
+        .collect::<String>();
+    if !camel.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        camel.insert(0, 'V');
+    }
+    if camel == "Self" {
+        camel.push('_');
+    }
+    camel
+}
+
+/// Splits a name at underscores and other punctuation, and where `txData` or `TXData` change
+/// case.
+fn words(name: &str) -> Vec<String> {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut words = vec![];
+    let mut word = String::new();
+    for (idx, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        let prev = idx.checked_sub(1).map(|idx| chars[idx]);
+        let next = chars.get(idx + 1);
+        let boundary = c.is_ascii_uppercase()
+            && (prev.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit())
+                || prev.is_some_and(|p| p.is_ascii_uppercase())
+                    && next.is_some_and(|n| n.is_ascii_lowercase()));
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
@@ -0,0 +1,248 @@
+//! CMSIS-SVD, <https://open-cmsis-pack.github.io/svd-spec/main/>.
+
+use crate::xml::{
+    access, child, children, description, number, parse_number, required_number, required_text,
+    small, text,
+};
+use crate::{Access, Device, EnumeratedValue, Error, Field, Peripheral, Register};
+
+use roxmltree::{Document, Node};
+
+/// The register properties that a device, peripheral or cluster passes down to its registers.
+#[derive(Debug, Clone, Copy, Default)]
+struct Properties {
+    size: Option<u32>,
+    access: Option<Access>,
+    reset_value: Option<u64>,
+}
+
+impl Properties {
+    /// These properties, overridden by those declared on `node`.
+    fn read(self, node: Node) -> Result<Self, Error> {
+        Ok(Properties {
+            size: match number(node, "size")? {
+                Some(size) => Some(small("size", size)?),
+                None => self.size,
+            },
+            access: access(node)?.or(self.access),
+            reset_value: number(node, "resetValue")?.or(self.reset_value),
+        })
+    }
+}
+
+pub fn parse(doc: &Document) -> Result<Device, Error> {
+    let device = doc.root_element();
+    let properties = Properties::default().read(device)?;
+    let mut peripherals: Vec<Peripheral> = vec![];
+    for node in child(device, "peripherals")
+        .into_iter()
+        .flat_map(|list| children(list, "peripheral"))
+    {
+        let base = match node.attribute("derivedFrom") {
+            Some(base) => Some(
+                peripherals
+                    .iter()
+                    .find(|p| p.name == base)
+                    .cloned()
+                    .ok_or_else(|| Error::UnknownBase(base.to_string()))?,
+            ),
+            None => None,
+        };
+        let properties = properties.read(node)?;
+        let mut registers = base
+            .as_ref()
+            .map(|base| base.registers.clone())
+            .unwrap_or_default();
+        if let Some(list) = child(node, "registers") {
+            read_registers(list, properties, 0, "", &mut registers)?;
+        }
+        peripherals.push(Peripheral {
+            name: required_text(node, "name")?.to_string(),
+            description: description(node).or(base.and_then(|base| base.description)),
+            base_address: required_number(node, "baseAddress")?,
+            registers,
+        });
+    }
+    Ok(Device {
+        name: required_text(device, "name")?.to_string(),
+        description: description(device),
+        peripherals,
+    })
+}
+
+/// Reads the registers of `list` into `registers`, flattening clusters into registers named
+/// `{cluster}_{register}`.
+fn read_registers(
+    list: Node,
+    properties: Properties,
+    offset: u64,
+    prefix: &str,
+    registers: &mut Vec<Register>,
+) -> Result<(), Error> {
+    for node in list.children().filter(|n| n.is_element()) {
+        match node.tag_name().name() {
+            "register" => read_register(node, properties, offset, prefix, registers)?,
+            "cluster" => {
+                let properties = properties.read(node)?;
+                let cluster_offset = offset + required_number(node, "addressOffset")?;
+                for (name, at) in instances(node, required_text(node, "name")?)? {
+                    let prefix = format!("{prefix}{name}_");
+                    read_registers(node, properties, cluster_offset + at, &prefix, registers)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn read_register(
+    node: Node,
+    properties: Properties,
+    offset: u64,
+    prefix: &str,
+    registers: &mut Vec<Register>,
+) -> Result<(), Error> {
+    let base = match node.attribute("derivedFrom") {
+        Some(base) => Some(
+            registers
+                .iter()
+                .find(|r| r.name == base || r.name == format!("{prefix}{base}"))
+                .cloned()
+                .ok_or_else(|| Error::UnknownBase(base.to_string()))?,
+        ),
+        None => None,
+    };
+    let properties = properties.read(node)?;
+    let size = properties
+        .size
+        .or(base.as_ref().map(|base| base.size))
+        .unwrap_or(32);
+    let access = properties
+        .access
+        .or(base.as_ref().map(|base| base.access))
+        .unwrap_or_default();
+    let reset_value = properties
+        .reset_value
+        .or(base.as_ref().map(|base| base.reset_value))
+        .unwrap_or(0);
+    let fields = match child(node, "fields") {
+        Some(list) => children(list, "field")
+            .map(|field| read_field(field, access))
+            .collect::<Result<Vec<_>, _>>()?,
+        None => base
+            .as_ref()
+            .map(|base| base.fields.clone())
+            .unwrap_or_default(),
+    };
+    let description = description(node).or(base.and_then(|base| base.description));
+    let name = required_text(node, "name")?;
+    let offset = offset + required_number(node, "addressOffset")?;
+
+    let register = |name: String, offset, dim| Register {
+        name: format!("{prefix}{name}"),
+        description: description.clone(),
+        offset,
+        size,
+        access,
+        reset_value,
+        dim,
+        fields: fields.clone(),
+    };
+    // `name[%s]` with registers packed back to back is an array, anything else is expanded.
+    match (name.strip_suffix("[%s]"), number(node, "dim")?) {
+        (Some(array), Some(dim))
+            if text(node, "dimIndex").is_none()
+                && required_number(node, "dimIncrement")? * 8 == u64::from(size) =>
+        {
+            registers.push(register(
+                array.to_string(),
+                offset,
+                Some(small("dim", dim)?),
+            ));
+        }
+        _ => {
+            for (name, at) in instances(node, name)? {
+                registers.push(register(name, offset + at, None));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_field(node: Node, register_access: Access) -> Result<Field, Error> {
+    let (bit_offset, bit_width) = if let Some(offset) = number(node, "bitOffset")? {
+        (offset, number(node, "bitWidth")?.unwrap_or(1))
+    } else if let Some(lsb) = number(node, "lsb")? {
+        let msb = required_number(node, "msb")?;
+        (lsb, msb.saturating_sub(lsb) + 1)
+    } else {
+        let range = required_text(node, "bitRange")?;
+        let invalid = || Error::Invalid {
+            element: "bitRange",
+            value: range.to_string(),
+        };
+        let (msb, lsb) = range
+            .strip_prefix('[')
+            .and_then(|r| r.strip_suffix(']'))
+            .and_then(|r| r.split_once(':'))
+            .ok_or_else(invalid)?;
+        let msb = parse_number("bitRange", msb)?;
+        let lsb = parse_number("bitRange", lsb)?;
+        (lsb, msb.checked_sub(lsb).ok_or_else(invalid)? + 1)
+    };
+
+    let mut enumerated_values = vec![];
+    if let Some(list) = child(node, "enumeratedValues") {
+        for value in children(list, "enumeratedValue") {
+            // Defaults cover every other value, and `#1x0` values have don't care bits, neither
+            // of which an enum variant can stand for.
+            match text(value, "value") {
+                Some(v) if !v.contains(['x', 'X']) || v.starts_with("0x") => enumerated_values
+                    .push(EnumeratedValue {
+                        name: required_text(value, "name")?.to_string(),
+                        description: description(value),
+                        value: parse_number("value", v)?,
+                    }),
+                _ => {}
+            }
+        }
+    }
+
+    Ok(Field {
+        name: required_text(node, "name")?.to_string(),
+        description: description(node),
+        bit_offset: small("bitOffset", bit_offset)?,
+        bit_width: small("bitWidth", bit_width)?,
+        access: access(node)?.unwrap_or(register_access),
+        enumerated_values,
+    })
+}
+
+/// The names and offsets of the copies of an element declared with `dim`, or just the element
+/// itself.
+fn instances(node: Node, name: &str) -> Result<Vec<(String, u64)>, Error> {
+    let Some(dim) = number(node, "dim")? else {
+        return Ok(vec![(name.to_string(), 0)]);
+    };
+    let increment = required_number(node, "dimIncrement")?;
+    let indices = match text(node, "dimIndex") {
+        Some(index) => match index.split_once('-') {
+            Some((first, last)) if !index.contains(',') => {
+                let first = parse_number("dimIndex", first)?;
+                let last = parse_number("dimIndex", last)?;
+                (first..=last).map(|i| i.to_string()).collect()
+            }
+            _ => index.split(',').map(|i| i.trim().to_string()).collect(),
+        },
+        None => (0..dim).map(|i| i.to_string()).collect::<Vec<_>>(),
+    };
+    Ok(indices
+        .into_iter()
+        .zip(0..)
+        .map(|(index, idx)| {
+            let name = name.replace("[%s]", &index).replace("%s", &index);
+            (name, idx * increment)
+        })
+        .collect())
+}
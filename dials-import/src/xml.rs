@@ -0,0 +1,106 @@
+//! Reading elements shared by SVD and IP-XACT, which differ mostly in their namespaces.
+
+use crate::{Access, Error};
+
+use roxmltree::Node;
+
+/// The first child element called `name`, in any namespace.
+pub fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|n| n.is_element() && n.tag_name().name() == name)
+}
+
+/// The child elements called `name`, in any namespace.
+pub fn children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children()
+        .filter(move |n| n.is_element() && n.tag_name().name() == name)
+}
+
+/// The trimmed text of the child element called `name`.
+pub fn text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name).and_then(|n| n.text()).map(str::trim)
+}
+
+pub fn required_text<'a>(node: Node<'a, '_>, name: &'static str) -> Result<&'a str, Error> {
+    text(node, name).ok_or_else(|| Error::Missing {
+        element: name,
+        parent: text(node, "name")
+            .unwrap_or(node.tag_name().name())
+            .to_string(),
+    })
+}
+
+/// A description with its line breaks and indentation collapsed into single spaces.
+pub fn description(node: Node) -> Option<String> {
+    let text = text(node, "description")?;
+    Some(text.split_whitespace().collect::<Vec<_>>().join(" ")).filter(|d| !d.is_empty())
+}
+
+/// The number in the child element called `name`.
+pub fn number(node: Node, name: &'static str) -> Result<Option<u64>, Error> {
+    text(node, name).map(|n| parse_number(name, n)).transpose()
+}
+
+pub fn required_number(node: Node, name: &'static str) -> Result<u64, Error> {
+    parse_number(name, required_text(node, name)?)
+}
+
+/// A number that must fit in a `u32`, such as a width or bit offset.
+pub fn small(element: &'static str, value: u64) -> Result<u32, Error> {
+    u32::try_from(value).map_err(|_| Error::Invalid {
+        element,
+        value: value.to_string(),
+    })
+}
+
+pub fn access(node: Node) -> Result<Option<Access>, Error> {
+    text(node, "access")
+        .map(|a| {
+            Access::parse(a).ok_or_else(|| Error::Invalid {
+                element: "access",
+                value: a.to_string(),
+            })
+        })
+        .transpose()
+}
+
+/// Parses the forms numbers take in SVD and IP-XACT: decimal, `0x1F`, `#0101` binary, Verilog
+/// style `'h1F` or `8'b0101`, and decimal with a `k`, `M` or `G` suffix.
+pub fn parse_number(element: &'static str, value: &str) -> Result<u64, Error> {
+    let invalid = || Error::Invalid {
+        element,
+        value: value.to_string(),
+    };
+    let digits = value.replace('_', "");
+    let (radix, digits) = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        (16, hex.to_string())
+    } else if let Some(bin) = digits.strip_prefix('#') {
+        (2, bin.to_string())
+    } else if let Some((_, verilog)) = digits.split_once('\'') {
+        let mut chars = verilog.chars();
+        let radix = match chars.next().map(|c| c.to_ascii_lowercase()) {
+            Some('h') => 16,
+            Some('b') => 2,
+            Some('o') => 8,
+            Some('d') => 10,
+            _ => return Err(invalid()),
+        };
+        (radix, chars.as_str().to_string())
+    } else {
+        let (digits, scale) = match digits.char_indices().last() {
+            Some((idx, 'k' | 'K')) => (&digits[..idx], 1 << 10),
+            Some((idx, 'm' | 'M')) => (&digits[..idx], 1 << 20),
+            Some((idx, 'g' | 'G')) => (&digits[..idx], 1 << 30),
+            _ => (&digits[..], 1),
+        };
+        let value = digits.parse::<u64>().map_err(|_| invalid())?;
+        return value.checked_mul(scale).ok_or_else(invalid);
+    };
+    u64::from_str_radix(&digits, radix).map_err(|_| invalid())
+}
@@ -0,0 +1,280 @@
+// Generated by dials-import from the description of `ACME1`. Do not edit.
+//
+// A microcontroller with two UARTs and a timer.
+
+/// Universal asynchronous receiver and transmitter
+pub mod uart0 {
+    /// The address of the `UART0` registers.
+    pub const BASE_ADDRESS: usize = 0x40001000;
+
+    dials::spec! {
+        /// Operating mode
+        enum CtrlMode {
+            /// Normal operation
+            Normal = 0,
+            Loopback = 1,
+            IrDa = 2,
+        }
+
+        /// Control register
+        #[reset(0x10)]
+        struct Ctrl {
+            /// Enables the UART
+            en: bool @ 0,
+            /// Operating mode
+            mode: u2 as CtrlMode @ 1..3,
+            #[reserved]
+            reserved3: bool @ 3,
+            /// Number of stop bits
+            stop: u2 @ 4..6,
+            #[reserved]
+            reserved6: bool @ 6,
+            /// Transmit interrupt enable
+            ///
+            /// `0`: Disabled
+            ///
+            /// `1`: Enabled
+            txie: bool @ 7,
+            #[reserved]
+            reserved8: u24 @ 8..32,
+        }
+
+        /// Status register
+        #[reset(0x1)]
+        struct Status {
+            /// Transmit buffer empty
+            #[access(read_only)]
+            txe: bool @ 0,
+            /// Receive buffer not empty
+            #[access(read_only)]
+            rxne: bool @ 1,
+            /// Kind of the last error
+            #[access(read_only)]
+            type_: u2 @ 2..4,
+            #[reserved]
+            reserved4: u4 @ 4..8,
+            /// Errors since the last read
+            #[access(read_write_once)]
+            err_count: u8 @ 8..16,
+            #[reserved]
+            reserved16: u16 @ 16..32,
+        }
+
+        /// Receive FIFO level
+        struct FifoLevel {
+            #[access(read_only)]
+            count: u6 @ 0..6,
+            #[reserved]
+            reserved6: u10 @ 6..16,
+        }
+
+        /// Receive FIFO interrupt threshold
+        #[reset(0x8)]
+        struct FifoThresh {
+            #[access(write_once)]
+            value: u6 @ 0..6,
+            #[reserved]
+            reserved6: u10 @ 6..16,
+        }
+    }
+
+    dials::register_block! {
+        /// Universal asynchronous receiver and transmitter
+        pub struct Uart0 {
+            /// Control register
+            0x0 => ctrl: Ctrl,
+            /// Status register
+            0x4 => status: Status,
+            /// Transmit FIFO
+            0x10..0x20 => data: [u32; 4],
+            /// Receive FIFO level
+            0x20 => fifo_level: FifoLevel,
+            /// Receive FIFO interrupt threshold
+            0x22 => fifo_thresh: FifoThresh,
+        }
+    }
+
+    /// The `UART0` registers at [`BASE_ADDRESS`].
+    ///
+    /// # Safety
+    ///
+    /// The registers must be mapped at [`BASE_ADDRESS`], see [`Uart0Regs::from_ptr`].
+    pub unsafe fn regs() -> Uart0Regs {
+        unsafe { Uart0Regs::from_ptr(BASE_ADDRESS as *mut Uart0) }
+    }
+}
+
+/// Universal asynchronous receiver and transmitter
+pub mod uart1 {
+    /// The address of the `UART1` registers.
+    pub const BASE_ADDRESS: usize = 0x40002000;
+
+    dials::spec! {
+        /// Operating mode
+        enum CtrlMode {
+            /// Normal operation
+            Normal = 0,
+            Loopback = 1,
+            IrDa = 2,
+        }
+
+        /// Control register
+        #[reset(0x10)]
+        struct Ctrl {
+            /// Enables the UART
+            en: bool @ 0,
+            /// Operating mode
+            mode: u2 as CtrlMode @ 1..3,
+            #[reserved]
+            reserved3: bool @ 3,
+            /// Number of stop bits
+            stop: u2 @ 4..6,
+            #[reserved]
+            reserved6: bool @ 6,
+            /// Transmit interrupt enable
+            ///
+            /// `0`: Disabled
+            ///
+            /// `1`: Enabled
+            txie: bool @ 7,
+            #[reserved]
+            reserved8: u24 @ 8..32,
+        }
+
+        /// Status register
+        #[reset(0x1)]
+        struct Status {
+            /// Transmit buffer empty
+            #[access(read_only)]
+            txe: bool @ 0,
+            /// Receive buffer not empty
+            #[access(read_only)]
+            rxne: bool @ 1,
+            /// Kind of the last error
+            #[access(read_only)]
+            type_: u2 @ 2..4,
+            #[reserved]
+            reserved4: u4 @ 4..8,
+            /// Errors since the last read
+            #[access(read_write_once)]
+            err_count: u8 @ 8..16,
+            #[reserved]
+            reserved16: u16 @ 16..32,
+        }
+
+        /// Receive FIFO level
+        struct FifoLevel {
+            #[access(read_only)]
+            count: u6 @ 0..6,
+            #[reserved]
+            reserved6: u10 @ 6..16,
+        }
+
+        /// Receive FIFO interrupt threshold
+        #[reset(0x8)]
+        struct FifoThresh {
+            #[access(write_once)]
+            value: u6 @ 0..6,
+            #[reserved]
+            reserved6: u10 @ 6..16,
+        }
+    }
+
+    dials::register_block! {
+        /// Universal asynchronous receiver and transmitter
+        pub struct Uart1 {
+            /// Control register
+            0x0 => ctrl: Ctrl,
+            /// Status register
+            0x4 => status: Status,
+            /// Transmit FIFO
+            0x10..0x20 => data: [u32; 4],
+            /// Receive FIFO level
+            0x20 => fifo_level: FifoLevel,
+            /// Receive FIFO interrupt threshold
+            0x22 => fifo_thresh: FifoThresh,
+        }
+    }
+
+    /// The `UART1` registers at [`BASE_ADDRESS`].
+    ///
+    /// # Safety
+    ///
+    /// The registers must be mapped at [`BASE_ADDRESS`], see [`Uart1Regs::from_ptr`].
+    pub unsafe fn regs() -> Uart1Regs {
+        unsafe { Uart1Regs::from_ptr(BASE_ADDRESS as *mut Uart1) }
+    }
+}
+
+/// Free running timer
+pub mod timer {
+    /// The address of the `TIMER` registers.
+    pub const BASE_ADDRESS: usize = 0x40003000;
+
+    dials::spec! {
+        /// Timer control
+        struct Timer {
+            en: bool @ 0,
+            #[reserved]
+            reserved1: u3 @ 1..4,
+            prescale: u4 @ 4..8,
+            #[reserved]
+            reserved8: u24 @ 8..32,
+        }
+    }
+
+    dials::register_block! {
+        /// Free running timer
+        pub struct TimerBlock {
+            /// Timer control
+            0x0 => timer: Timer,
+            /// Counter value
+            0x8 => cnt0: u32,
+            /// Counter value
+            0x10 => cnt1: u32,
+        }
+    }
+
+    /// The `TIMER` registers at [`BASE_ADDRESS`].
+    ///
+    /// # Safety
+    ///
+    /// The registers must be mapped at [`BASE_ADDRESS`], see [`TimerBlockRegs::from_ptr`].
+    pub unsafe fn regs() -> TimerBlockRegs {
+        unsafe { TimerBlockRegs::from_ptr(BASE_ADDRESS as *mut TimerBlock) }
+    }
+}
+
+/// Watchdog
+pub mod wdog {
+    /// The address of the `WDOG` registers.
+    pub const BASE_ADDRESS: usize = 0x40004000;
+
+    dials::spec! {
+        /// Watchdog control
+        struct Ctrl {
+            en: bool @ 0,
+            #[reserved]
+            reserved1: u31 @ 1..32,
+        }
+    }
+
+    dials::register_block! {
+        /// Watchdog
+        pub struct Wdog {
+            /// Watchdog control
+            0x0 => ctrl: Ctrl,
+            /// Reload value
+            0x4 => load: u32,
+        }
+    }
+
+    /// The `WDOG` registers at [`BASE_ADDRESS`].
+    ///
+    /// # Safety
+    ///
+    /// The registers must be mapped at [`BASE_ADDRESS`], see [`WdogRegs::from_ptr`].
+    pub unsafe fn regs() -> WdogRegs {
+        unsafe { WdogRegs::from_ptr(BASE_ADDRESS as *mut Wdog) }
+    }
+}
@@ -0,0 +1,52 @@
+// Generated by dials-import from the description of `timer`. Do not edit.
+
+/// Timer registers
+pub mod timer_regs {
+    /// The address of the `timer_regs` registers.
+    pub const BASE_ADDRESS: usize = 0x1000;
+
+    dials::spec! {
+        /// Divides the clock
+        enum CtrlPrescale {
+            Div1 = 0,
+            Div8 = 3,
+        }
+
+        /// Timer control
+        #[reset(0x31)]
+        struct Ctrl {
+            enable: bool @ 0,
+            #[reserved]
+            reserved1: u3 @ 1..4,
+            /// Divides the clock
+            prescale: u4 as CtrlPrescale @ 4..8,
+            #[reserved]
+            reserved8: u24 @ 8..32,
+        }
+
+        struct Count {
+            #[access(read_only)]
+            value: u16 @ 0..16,
+            #[reserved]
+            reserved16: u16 @ 16..32,
+        }
+    }
+
+    dials::register_block! {
+        /// Timer registers
+        pub struct TimerRegs {
+            /// Timer control
+            0x0 => ctrl: Ctrl,
+            0x4 => count: Count,
+        }
+    }
+
+    /// The `timer_regs` registers at [`BASE_ADDRESS`].
+    ///
+    /// # Safety
+    ///
+    /// The registers must be mapped at [`BASE_ADDRESS`], see [`TimerRegsRegs::from_ptr`].
+    pub unsafe fn regs() -> TimerRegsRegs {
+        unsafe { TimerRegsRegs::from_ptr(BASE_ADDRESS as *mut TimerRegs) }
+    }
+}
@@ -0,0 +1,64 @@
+use dials_import::{parse, parse_ip_xact, Access};
+
+#[allow(dead_code)]
+mod timer {
+    include!("fixtures/timer.rs");
+}
+
+use timer::timer_regs::{Ctrl, CtrlPrescale};
+
+const IP_XACT: &str = include_str!("fixtures/timer.xml");
+
+#[test]
+fn address_blocks_and_field_resets() {
+    let device = parse_ip_xact(IP_XACT).unwrap();
+    assert_eq!(device.name, "timer");
+    let block = &device.peripherals[0];
+    assert_eq!((&*block.name, block.base_address), ("timer_regs", 0x1000));
+
+    let ctrl = &block.registers[0];
+    assert_eq!(ctrl.reset_value, 0x31);
+    let values = ctrl.fields[1].enumerated_values.iter();
+    assert_eq!(
+        values.map(|v| (&*v.name, v.value)).collect::<Vec<_>>(),
+        [("div1", 0), ("div8", 3)]
+    );
+    let count = &block.registers[1];
+    assert_eq!((count.offset, count.access), (4, Access::ReadOnly));
+    assert_eq!(count.fields[0].access, Access::ReadOnly);
+}
+
+#[test]
+fn register_resets_from_2009() {
+    let component = r#"<spirit:component xmlns:spirit="http://www.spiritconsortium.org/XMLSchema/SPIRIT/1.5">
+        <spirit:name>gpio</spirit:name>
+        <spirit:memoryMaps><spirit:memoryMap><spirit:name>map</spirit:name>
+            <spirit:addressBlock>
+                <spirit:name>gpio</spirit:name>
+                <spirit:baseAddress>0x2000</spirit:baseAddress>
+                <spirit:width>16</spirit:width>
+                <spirit:register>
+                    <spirit:name>pins</spirit:name>
+                    <spirit:addressOffset>0x0</spirit:addressOffset>
+                    <spirit:dim>4</spirit:dim>
+                    <spirit:reset><spirit:value>0x00ff</spirit:value></spirit:reset>
+                </spirit:register>
+            </spirit:addressBlock>
+        </spirit:memoryMap></spirit:memoryMaps>
+    </spirit:component>"#;
+    let device = parse(component).unwrap();
+    let pins = &device.peripherals[0].registers[0];
+    assert_eq!((pins.size, pins.dim, pins.reset_value), (16, Some(4), 0xff));
+}
+
+#[test]
+fn generated_source_is_up_to_date() {
+    assert_eq!(
+        parse(IP_XACT).unwrap().to_rust(),
+        include_str!("fixtures/timer.rs")
+    );
+    let mut ctrl = Ctrl::new();
+    assert_eq!(ctrl.prescale(), Some(CtrlPrescale::Div8));
+    ctrl.set_prescale(CtrlPrescale::Div1);
+    assert_eq!(ctrl.0, 0x01);
+}
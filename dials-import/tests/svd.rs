@@ -0,0 +1,133 @@
+use dials::mock::MockBus;
+use dials_import::{parse, parse_svd, Access, Error};
+
+#[allow(dead_code)]
+mod acme {
+    include!("fixtures/acme.rs");
+}
+
+use acme::uart0::{Ctrl, CtrlMode, FifoThresh, Status, Uart0Regs};
+
+const SVD: &str = include_str!("fixtures/acme.svd");
+
+#[test]
+fn registers_and_fields() {
+    let device = parse_svd(SVD).unwrap();
+    assert_eq!(device.name, "ACME1");
+    let names = device.peripherals.iter().map(|p| &*p.name);
+    assert_eq!(
+        names.collect::<Vec<_>>(),
+        ["UART0", "UART1", "TIMER", "WDOG"]
+    );
+
+    let uart = &device.peripherals[0];
+    assert_eq!(uart.base_address, 0x4000_1000);
+    assert_eq!(
+        uart.description.as_deref(),
+        Some("Universal asynchronous receiver and transmitter")
+    );
+    let ctrl = &uart.registers[0];
+    assert_eq!((ctrl.size, ctrl.reset_value), (32, 0x10));
+    let fields = ctrl
+        .fields
+        .iter()
+        .map(|f| (&*f.name, f.bit_offset, f.bit_width));
+    assert_eq!(
+        fields.collect::<Vec<_>>(),
+        [("EN", 0, 1), ("MODE", 1, 2), ("STOP", 4, 2), ("TXIE", 7, 1)]
+    );
+    // The default value has no variant of its own.
+    let values = ctrl.fields[1].enumerated_values.iter();
+    assert_eq!(
+        values.map(|v| (&*v.name, v.value)).collect::<Vec<_>>(),
+        [("NORMAL", 0), ("LOOPBACK", 1), ("IrDA", 2)]
+    );
+
+    let status = &uart.registers[1];
+    assert_eq!(status.access, Access::ReadOnly);
+    assert_eq!(status.fields[0].access, Access::ReadOnly);
+    assert_eq!(status.fields[3].access, Access::ReadWriteOnce);
+
+    let data = &uart.registers[2];
+    assert_eq!(
+        (&*data.name, data.dim, data.offset),
+        ("DATA", Some(4), 0x10)
+    );
+    assert_eq!(data.access, Access::WriteOnly);
+    let fifo = uart.registers[3..]
+        .iter()
+        .map(|r| (&*r.name, r.offset, r.size));
+    assert_eq!(
+        fifo.collect::<Vec<_>>(),
+        [("FIFO_LEVEL", 0x20, 16), ("FIFO_THRESH", 0x22, 16)]
+    );
+
+    let derived = &device.peripherals[1];
+    assert_eq!(derived.base_address, 0x4000_2000);
+    assert_eq!(derived.registers, uart.registers);
+
+    let counters = device.peripherals[2].registers[1..].iter();
+    assert_eq!(
+        counters.map(|r| (&*r.name, r.offset)).collect::<Vec<_>>(),
+        [("CNT0", 0x8), ("CNT1", 0x10)]
+    );
+}
+
+#[test]
+fn generated_source_is_up_to_date() {
+    assert_eq!(
+        parse_svd(SVD).unwrap().to_rust(),
+        include_str!("fixtures/acme.rs")
+    );
+}
+
+#[test]
+fn generated_specs() {
+    let mut ctrl = Ctrl::new();
+    assert_eq!(ctrl.0, 0x10);
+    assert_eq!(ctrl.stop(), 1);
+    ctrl.set_mode(CtrlMode::IrDa).set_en();
+    assert_eq!(ctrl.0, 0x15);
+    assert!(Status::new().txe());
+
+    let bus = MockBus::new();
+    let uart = Uart0Regs(&bus);
+    uart.set_data(2, 0xab);
+    uart.set_fifo_thresh(FifoThresh::new());
+    assert_eq!(bus.memory().read(0x18, 4), 0xab);
+    assert_eq!(bus.memory().read(0x22, 2), 0x8);
+    assert_eq!(acme::timer::TimerBlock::CNT1_OFFSET, 0x10);
+    // Registers are laid out by offset, whatever order they are declared in.
+    assert_eq!(acme::wdog::Wdog::CTRL_OFFSET, 0x0);
+    assert_eq!(acme::wdog::Wdog::LOAD_OFFSET, 0x4);
+}
+
+#[test]
+fn build_script_helper() {
+    let dir = std::env::temp_dir().join(format!("dials-import-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("acme.rs");
+    dials_import::generate("tests/fixtures/acme.svd", &output).unwrap();
+    let generated = std::fs::read_to_string(&output).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(generated, include_str!("fixtures/acme.rs"));
+}
+
+#[test]
+fn invalid_descriptions() {
+    let error = parse("<memory/>").unwrap_err();
+    assert!(matches!(&error, Error::UnknownFormat(root) if root == "memory"));
+
+    let missing = "<device><name>D</name><peripherals><peripheral><name>P</name></peripheral></peripherals></device>";
+    let error = parse(missing).unwrap_err();
+    assert_eq!(error.to_string(), "`P` has no `baseAddress`");
+
+    let invalid = "<device><name>D</name><size>wide</size></device>";
+    let error = parse(invalid).unwrap_err();
+    assert_eq!(error.to_string(), "`wide` is not a valid `size`");
+
+    let derived = r#"<device><name>D</name><peripherals>
+        <peripheral derivedFrom="UART9"><name>P</name><baseAddress>0</baseAddress></peripheral>
+    </peripherals></device>"#;
+    assert!(matches!(parse(derived), Err(Error::UnknownBase(base)) if base == "UART9"));
+}
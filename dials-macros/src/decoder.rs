@@ -0,0 +1,189 @@
+use crate::spec::parse::SpecParser;
+
+use proc_macro2::{Delimiter, Span, TokenTree};
+use quote::quote;
+
+/// An enum over specs with fixed fields, `enum Name: u32 { Add, Addi(AddImmediate), .. }`.
+struct Decoder {
+    attrs: Vec<proc_macro2::TokenStream>,
+    vis: proc_macro2::TokenStream,
+    name: proc_macro2::Ident,
+    word_ty: proc_macro2::Ident,
+    entries: Vec<(proc_macro2::Ident, proc_macro2::TokenStream)>,
+}
+
+pub fn generate_decoder(input: proc_macro2::TokenStream) -> syn::Result<proc_macro2::TokenStream> {
+    Ok(parse(&mut SpecParser::new(input))?.generate())
+}
+
+fn parse(p: &mut SpecParser) -> syn::Result<Decoder> {
+    let mut attrs = vec![];
+    while let Some(attr) = p.parse_attribute()? {
+        attrs.push(attr);
+    }
+
+    let mut vis = proc_macro2::TokenStream::new();
+    let name = loop {
+        match p.advance1() {
+            Some(TokenTree::Ident(kw)) if kw == "enum" => match p.advance1() {
+                Some(TokenTree::Ident(name)) => break name,
+                t => {
+                    return Err(syn::Error::new(
+                        t.map(|t| t.span()).unwrap_or(kw.span()),
+                        "expected the name of the decoder enum",
+                    ))
+                }
+            },
+            Some(t) => vis.extend([t]),
+            None => {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    "expected a decoder declaration of the form `enum Name: u32 { .. }`",
+                ))
+            }
+        }
+    };
+
+    let word_ty = match p.advance2() {
+        (Some(TokenTree::Punct(colon)), Some(TokenTree::Ident(ty))) if colon.as_char() == ':' => ty,
+        _ => {
+            return Err(syn::Error::new(
+                name.span(),
+                "expected the type of the decoded words, e.g. `: u32`",
+            ))
+        }
+    };
+
+    let mut body = match p.advance1() {
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => {
+            SpecParser::new(g.stream())
+        }
+        _ => {
+            return Err(syn::Error::new(
+                word_ty.span(),
+                "expected a braced list of decoder entries",
+            ))
+        }
+    };
+    let mut entries = vec![];
+    loop {
+        let variant = match body.advance1() {
+            Some(TokenTree::Ident(variant)) => variant,
+            Some(t) => return Err(syn::Error::new(t.span(), "expected a decoder entry")),
+            None => break,
+        };
+        let spec = match body.peek1() {
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis => {
+                body.advance1();
+                g.stream()
+            }
+            _ => quote! { #variant },
+        };
+        if let Some((other, _)) = entries.iter().find(|(v, _)| *v == variant) {
+            return Err(syn::Error::new(
+                variant.span(),
+                format!("decoder entry `{other}` is declared more than once"),
+            ));
+        }
+        entries.push((variant, spec));
+        match body.advance1() {
+            Some(TokenTree::Punct(p)) if p.as_char() == ',' => {}
+            None => break,
+            Some(t) => return Err(syn::Error::new(t.span(), "expected ',' or end of entries")),
+        }
+    }
+    if entries.is_empty() {
+        return Err(syn::Error::new(
+            name.span(),
+            "a decoder needs at least one entry",
+        ));
+    }
+
+    Ok(Decoder {
+        attrs,
+        vis,
+        name,
+        word_ty,
+        entries,
+    })
+}
+
+impl Decoder {
+    fn generate(&self) -> proc_macro2::TokenStream {
+        let Self {
+            attrs,
+            vis,
+            name,
+            word_ty,
+            entries,
+        } = self;
+        let count = entries.len();
+        let variants = entries.iter().map(|(v, _)| v).collect::<Vec<_>>();
+        let specs = entries.iter().map(|(_, s)| s).collect::<Vec<_>>();
+        let indices = 0..count;
+
+        let patterns = quote! {
+            [#((#specs::MATCH_MASK as u128, #specs::MATCH_VALUE as u128)),*]
+        };
+
+        // Every entry is checked against those before it, naming the pair in the error.
+        let checks = (1..count).map(|entry| {
+            let arms = (0..entry).map(|earlier| {
+                let shadowed = format!(
+                    "decoder `{name}`: `{}` can never be decoded, `{}` is listed first and matches every word it does",
+                    variants[entry], variants[earlier]
+                );
+                let ambiguous = format!(
+                    "decoder `{name}`: `{}` and `{}` both match some words, fix more bits of one of them",
+                    variants[earlier], variants[entry]
+                );
+                quote! {
+                    Some((#earlier, true)) => panic!(#shadowed),
+                    Some((#earlier, false)) => panic!(#ambiguous),
+                }
+            });
+            quote! {
+                match ::dials::decode::conflict(&PATTERNS, #entry) {
+                    #(#arms)*
+                    _ => {}
+                }
+            }
+        });
+
+        quote! {
+            #(#[#attrs])*
+            #vis enum #name {
+                #(#variants(#specs),)*
+            }
+
+            const _: () = {
+                const PATTERNS: [(u128, u128); #count] = #patterns;
+                #(#checks)*
+            };
+
+            impl #name {
+                /// Decodes `raw` as the first entry whose fixed fields it holds.
+                pub fn decode(raw: #word_ty) -> ::core::option::Option<Self> {
+                    const PATTERNS: [(u128, u128); #count] = #patterns;
+                    const TABLE: ::dials::decode::Table<#count> =
+                        ::dials::decode::Table::new(&PATTERNS);
+
+                    for entry in TABLE.candidates(raw as u128) {
+                        match entry {
+                            #(#indices if #specs::matches(raw) => return Some(Self::#variants(#specs(raw))),)*
+                            _ => {}
+                        }
+                    }
+                    None
+                }
+
+                /// The word this was decoded from.
+                pub fn raw(&self) -> #word_ty {
+                    match self {
+                        #(Self::#variants(spec) => spec.0,)*
+                    }
+                }
+            }
+        }
+    }
+}
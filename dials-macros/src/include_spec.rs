@@ -0,0 +1,362 @@
+use crate::spec::generate_dials;
+
+use quote::quote;
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// The items of a `spec!` read from a layout file, `include_spec!("regs/ctrl.toml")`. The path
+/// is relative to the manifest of the crate, and the file is TOML or JSON of the form
+///
+/// ```toml
+/// [[const]]
+/// name = "VERSION"
+/// type = "u8"
+/// value = 2
+///
+/// [[enum]]
+/// name = "Speed"
+/// variants = [{ name = "Off" }, { name = "Slow", value = 2 }, { name = "Fast" }]
+///
+/// [[struct]]
+/// name = "Ctrl"
+/// doc = "The control register."
+/// reset = 0x10
+/// fields = [
+///     { name = "enable", type = "bool" },
+///     { name = "speed", type = "u2", enum = "Speed", access = "read_write" },
+///     { name = "version", type = "u3", at = 4, fixed = "VERSION" },
+///     { name = "rsvd", type = "u1", reserved = "one" },
+/// ]
+/// ```
+///
+/// which is turned into the `spec!` it stands for, so the generated code is the same. Scaled
+/// fields give their `scale` and `offset` as numbers, `{ name = "temp", type = "u8", scale = 0.5,
+/// offset = -40 }`. Split fields, encodings, parity and CRC fields, and `match` variants cannot be
+/// written in layout files; specs using them are declared with `spec!`.
+pub fn generate_include_spec(
+    input: proc_macro2::TokenStream,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let file = syn::parse2::<syn::LitStr>(input)?;
+    let error =
+        |message: String| syn::Error::new(file.span(), format!("in `{}`: {message}", file.value()));
+
+    let dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| error("`CARGO_MANIFEST_DIR` is not set".to_string()))?;
+    let path = Path::new(&dir).join(file.value());
+    let toml = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => true,
+        Some("json") => false,
+        _ => return Err(error("expected a `.toml` or `.json` file".to_string())),
+    };
+    let text = std::fs::read_to_string(&path).map_err(|e| error(e.to_string()))?;
+    let layout = match toml {
+        true => toml::from_str::<toml::Value>(&text)
+            .map_err(|e| e.to_string())
+            .and_then(|toml| serde_json::to_value(toml).map_err(|e| e.to_string())),
+        false => serde_json::from_str::<Value>(&text).map_err(|e| e.to_string()),
+    }
+    .map_err(error)?;
+
+    let source = spec_source(&layout).map_err(error)?;
+    let tokens = source
+        .parse::<proc_macro2::TokenStream>()
+        .map_err(|e| error(e.to_string()))?;
+    let mut output = generate_dials(tokens).map_err(|e| error(e.to_string()))?;
+    // Rebuild when the file changes.
+    let path = path.to_string_lossy();
+    output.extend(quote! {
+        const _: &[u8] = include_bytes!(#path);
+    });
+    Ok(output)
+}
+
+/// The `spec!` input that `layout` describes.
+fn spec_source(layout: &Value) -> Result<String, String> {
+    let root = layout
+        .as_object()
+        .ok_or("expected a table of `const`, `enum` and `struct` entries")?;
+    check_keys(root, "the file", &["const", "enum", "struct"])?;
+    let mut out = String::new();
+
+    for entry in entries(root, "const", "the file")? {
+        let name = ident(entry, "a `const`")?;
+        let what = format!("const `{name}`");
+        check_keys(entry, &what, &["name", "doc", "type", "value"])?;
+        let ty = parsed::<syn::Type>(entry, "type", &what, "a type")?
+            .ok_or(format!("{what} has no `type`"))?;
+        let value = int(entry, "value", &what)?.ok_or(format!("{what} has no `value`"))?;
+        docs(&mut out, entry, &what)?;
+        out.push_str(&format!("const {name}: {ty} = {value};\n"));
+    }
+
+    for entry in entries(root, "enum", "the file")? {
+        let name = ident(entry, "an `enum`")?;
+        let what = format!("enum `{name}`");
+        check_keys(entry, &what, &["name", "doc", "variants"])?;
+        docs(&mut out, entry, &what)?;
+        out.push_str(&format!("enum {name} {{\n"));
+        for variant in entries(entry, "variants", &what)? {
+            let variant_name = ident(variant, &format!("a variant of `{name}`"))?;
+            let what = format!("variant `{variant_name}` of `{name}`");
+            check_keys(variant, &what, &["name", "doc", "value"])?;
+            docs(&mut out, variant, &what)?;
+            match int(variant, "value", &what)? {
+                Some(value) => out.push_str(&format!("{variant_name} = {value},\n")),
+                None => out.push_str(&format!("{variant_name},\n")),
+            }
+        }
+        out.push_str("}\n");
+    }
+
+    for entry in entries(root, "struct", "the file")? {
+        let name = ident(entry, "a `struct`")?;
+        let what = format!("struct `{name}`");
+        check_keys(entry, &what, &["name", "doc", "reset", "fields"])?;
+        docs(&mut out, entry, &what)?;
+        if let Some(reset) = int(entry, "reset", &what)? {
+            out.push_str(&format!("#[reset({reset})]\n"));
+        }
+        out.push_str(&format!("struct {name} {{\n"));
+        for field in entries(entry, "fields", &what)? {
+            let field_name = ident(field, &format!("a field of `{name}`"))?;
+            let what = format!("field `{field_name}` of `{name}`");
+            check_keys(
+                field,
+                &what,
+                &[
+                    "name", "doc", "type", "scale", "offset", "enum", "at", "fixed", "reserved",
+                    "alias", "access",
+                ],
+            )?;
+            docs(&mut out, field, &what)?;
+            match field.get("reserved") {
+                None | Some(Value::Bool(false)) => {}
+                Some(Value::Bool(true)) => out.push_str("#[reserved]\n"),
+                Some(Value::String(bits)) if bits == "zero" || bits == "one" => {
+                    out.push_str(&format!("#[reserved({bits})]\n"))
+                }
+                Some(_) => {
+                    return Err(format!(
+                        "{what} must be `reserved = true`, `\"zero\"` or `\"one\"`"
+                    ))
+                }
+            }
+            match field.get("alias") {
+                None | Some(Value::Bool(false)) => {}
+                Some(Value::Bool(true)) => out.push_str("#[alias]\n"),
+                Some(_) => return Err(format!("{what} must be `alias = true` or `false`")),
+            }
+            if let Some(access) = parsed::<syn::Ident>(field, "access", &what, "an identifier")? {
+                out.push_str(&format!("#[access({access})]\n"));
+            }
+            let ty = parsed::<syn::Ident>(field, "type", &what, "an identifier")?
+                .ok_or(format!("{what} has no `type`"))?;
+            out.push_str(&format!("{field_name}: {ty}"));
+            match (
+                number(field, "scale", &what)?,
+                number(field, "offset", &what)?,
+            ) {
+                (None, None) => {}
+                (None, Some(_)) => return Err(format!("{what} has an `offset` but no `scale`")),
+                (Some(scale), offset) => {
+                    out.push_str(&format!(" * {scale:?}"));
+                    match offset.unwrap_or(0.0) {
+                        offset if offset < 0.0 => out.push_str(&format!(" - {:?}", -offset)),
+                        offset => out.push_str(&format!(" + {offset:?}")),
+                    }
+                }
+            }
+            if let Some(enum_ty) = parsed::<syn::Path>(field, "enum", &what, "the path of an enum")?
+            {
+                out.push_str(&format!(" as {enum_ty}"));
+            }
+            if let Some(at) = int(field, "at", &what)? {
+                out.push_str(&format!(" @ {at}"));
+            }
+            if let Some(fixed) = int(field, "fixed", &what)? {
+                out.push_str(&format!(" == {fixed}"));
+            }
+            out.push_str(",\n");
+        }
+        out.push_str("}\n");
+    }
+
+    Ok(out)
+}
+
+/// The tables in the array `key` of `table`, none if it is missing.
+fn entries<'a>(
+    table: &'a Map<String, Value>,
+    key: &str,
+    what: &str,
+) -> Result<Vec<&'a Map<String, Value>>, String> {
+    match table.get(key) {
+        None => Ok(vec![]),
+        Some(Value::Array(entries)) => entries
+            .iter()
+            .map(|entry| {
+                entry
+                    .as_object()
+                    .ok_or(format!("the `{key}` of {what} must all be tables"))
+            })
+            .collect(),
+        Some(_) => Err(format!("the `{key}` of {what} must be an array of tables")),
+    }
+}
+
+fn check_keys(table: &Map<String, Value>, what: &str, known: &[&str]) -> Result<(), String> {
+    match table.keys().find(|key| !known.contains(&key.as_str())) {
+        Some(key) => Err(format!(
+            "{what} has an unknown key `{key}`, expected one of {}",
+            known
+                .iter()
+                .map(|key| format!("`{key}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+fn string<'a>(
+    table: &'a Map<String, Value>,
+    key: &str,
+    what: &str,
+) -> Result<Option<&'a str>, String> {
+    match table.get(key) {
+        None => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s)),
+        Some(_) => Err(format!("the `{key}` of {what} must be a string")),
+    }
+}
+
+/// The string `key` of `table`, which must parse as a single `T`, such as a type or an
+/// identifier, so that it cannot stand for more than that in the `spec!`.
+fn parsed<T: syn::parse::Parse>(
+    table: &Map<String, Value>,
+    key: &str,
+    what: &str,
+    expected: &str,
+) -> Result<Option<String>, String> {
+    match string(table, key, what)? {
+        Some(s) => match syn::parse_str::<T>(s) {
+            Ok(_) => Ok(Some(s.to_string())),
+            Err(_) => Err(format!(
+                "the `{key}` of {what} must be {expected}, not `{s}`"
+            )),
+        },
+        None => Ok(None),
+    }
+}
+
+fn number(table: &Map<String, Value>, key: &str, what: &str) -> Result<Option<f64>, String> {
+    match table.get(key) {
+        None => Ok(None),
+        Some(Value::Number(n)) => Ok(n.as_f64()),
+        Some(_) => Err(format!("the `{key}` of {what} must be a number")),
+    }
+}
+
+/// The `name` of an entry, which must be an identifier.
+fn ident(table: &Map<String, Value>, what: &str) -> Result<String, String> {
+    let name = string(table, "name", what)?.ok_or(format!("{what} has no `name`"))?;
+    syn::parse_str::<syn::Ident>(name)
+        .map(|_| name.to_string())
+        .map_err(|_| format!("`{name}`, the name of {what}, is not an identifier"))
+}
+
+/// An integer written as a number, or as a string holding a Rust integer literal or the name of a
+/// constant.
+fn int(table: &Map<String, Value>, key: &str, what: &str) -> Result<Option<String>, String> {
+    match table.get(key) {
+        None => Ok(None),
+        Some(Value::Number(n)) if n.is_u64() => Ok(Some(n.to_string())),
+        Some(Value::String(s))
+            if syn::parse_str::<syn::LitInt>(s).is_ok()
+                || syn::parse_str::<syn::Ident>(s).is_ok() =>
+        {
+            Ok(Some(s.clone()))
+        }
+        Some(_) => Err(format!(
+            "the `{key}` of {what} must be a non-negative integer or the name of a constant"
+        )),
+    }
+}
+
+/// Writes the `doc` of an entry as doc attributes, one per line.
+fn docs(out: &mut String, table: &Map<String, Value>, what: &str) -> Result<(), String> {
+    for line in string(table, "doc", what)?
+        .iter()
+        .flat_map(|doc| doc.lines())
+    {
+        out.push_str(&format!("#[doc = {:?}]\n", format!(" {line}")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::spec_source;
+    use serde_json::json;
+
+    fn field(field: serde_json::Value) -> Result<String, String> {
+        spec_source(&json!({ "struct": [{ "name": "Ctrl", "fields": [field] }] }))
+    }
+
+    #[test]
+    fn fields() {
+        assert_eq!(
+            field(json!({ "name": "temp", "type": "u8", "scale": 0.5, "offset": -40 })),
+            Ok("struct Ctrl {\ntemp: u8 * 0.5 - 40.0,\n}\n".to_string())
+        );
+        assert_eq!(
+            field(json!({ "name": "speed", "type": "u2", "enum": "regs::Speed", "at": 4 })),
+            Ok("struct Ctrl {\nspeed: u2 as regs::Speed @ 4,\n}\n".to_string())
+        );
+    }
+
+    #[test]
+    fn strings_cannot_splice_in_other_syntax() {
+        assert_eq!(
+            field(json!({ "name": "a", "type": "u2, evil: u3" })),
+            Err(
+                "the `type` of field `a` of `Ctrl` must be an identifier, not `u2, evil: u3`"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            field(json!({ "name": "a", "type": "u8 * 0.5 - 40" })),
+            Err(
+                "the `type` of field `a` of `Ctrl` must be an identifier, not `u8 * 0.5 - 40`"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            field(json!({ "name": "a", "type": "u2", "enum": "Speed = [1]" })),
+            Err(
+                "the `enum` of field `a` of `Ctrl` must be the path of an enum, not `Speed = [1]`"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            field(json!({ "name": "a", "type": "u2", "access": "read_only)] #[alias" })),
+            Err("the `access` of field `a` of `Ctrl` must be an identifier, not `read_only)] #[alias`"
+                .to_string())
+        );
+        let source = spec_source(&json!({
+            "const": [{ "name": "A", "type": "u8; const B: u8", "value": 1 }]
+        }));
+        assert_eq!(
+            source,
+            Err("the `type` of const `A` must be a type, not `u8; const B: u8`".to_string())
+        );
+    }
+
+    #[test]
+    fn offsets_need_a_scale() {
+        assert_eq!(
+            field(json!({ "name": "a", "type": "u8", "offset": 3 })),
+            Err("field `a` of `Ctrl` has an `offset` but no `scale`".to_string())
+        );
+    }
+}
@@ -1,5 +1,9 @@
 extern crate proc_macro;
 
+mod decoder;
+mod include_spec;
+mod pattern;
+mod register_block;
 mod spec;
 
 #[proc_macro]
@@ -8,3 +12,31 @@ pub fn spec(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         .unwrap_or_else(syn::Error::into_compile_error)
         .into()
 }
+
+#[proc_macro]
+pub fn decoder(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    decoder::generate_decoder(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro]
+pub fn pat(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    pattern::generate_pattern(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro]
+pub fn register_block(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    register_block::generate_register_block(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro]
+pub fn include_spec(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    include_spec::generate_include_spec(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
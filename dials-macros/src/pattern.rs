@@ -0,0 +1,147 @@
+use crate::spec::parse::SpecParser;
+
+use proc_macro2::{Delimiter, Spacing, Span, TokenTree};
+use quote::quote;
+
+/// A pattern over the fields of a spec, `Rbga { red: 0xff, alpha: 0, .. }`, optionally preceded
+/// by a raw value to test against it.
+struct Pattern {
+    raw: Option<proc_macro2::TokenStream>,
+    spec: proc_macro2::TokenStream,
+    fields: Vec<(proc_macro2::Ident, proc_macro2::TokenStream)>,
+}
+
+pub fn generate_pattern(input: proc_macro2::TokenStream) -> syn::Result<proc_macro2::TokenStream> {
+    Ok(parse(input)?.generate())
+}
+
+fn parse(input: proc_macro2::TokenStream) -> syn::Result<Pattern> {
+    let mut tokens = input.into_iter().collect::<Vec<_>>();
+
+    let body = match tokens.pop() {
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => g,
+        t => {
+            return Err(syn::Error::new(
+                t.map(|t| t.span()).unwrap_or(Span::call_site()),
+                "expected a pattern of the form `Spec { field: value, .. }`",
+            ))
+        }
+    };
+    let raw = match tokens
+        .iter()
+        .position(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == ','))
+    {
+        Some(comma) => {
+            let spec = tokens.split_off(comma + 1);
+            tokens.pop();
+            Some(std::mem::replace(&mut tokens, spec).into_iter().collect())
+        }
+        None => None,
+    };
+    if tokens.is_empty() {
+        return Err(syn::Error::new(
+            body.span(),
+            "expected the name of a spec before the pattern",
+        ));
+    }
+
+    let mut body = SpecParser::new(body.stream());
+    let mut fields: Vec<(proc_macro2::Ident, proc_macro2::TokenStream)> = vec![];
+    loop {
+        let name = match body.advance1() {
+            Some(TokenTree::Ident(name)) => name,
+            Some(TokenTree::Punct(dot))
+                if dot.as_char() == '.' && dot.spacing() == Spacing::Joint =>
+            {
+                match body.advance2() {
+                    (Some(TokenTree::Punct(p)), None) if p.as_char() == '.' => break,
+                    _ => {
+                        return Err(syn::Error::new(
+                            dot.span(),
+                            "expected `..` at the end of the pattern",
+                        ))
+                    }
+                }
+            }
+            Some(t) => return Err(syn::Error::new(t.span(), "expected a field name")),
+            None => break,
+        };
+        match body.advance1() {
+            Some(TokenTree::Punct(colon)) if colon.as_char() == ':' => {}
+            _ => {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("expected `{name}: value`"),
+                ))
+            }
+        }
+        let mut value = proc_macro2::TokenStream::new();
+        while let Some(t) = body.peek1() {
+            if matches!(&t, TokenTree::Punct(p) if p.as_char() == ',') {
+                break;
+            }
+            body.advance1();
+            value.extend([t]);
+        }
+        if value.is_empty() {
+            return Err(syn::Error::new(
+                name.span(),
+                format!("expected a value for `{name}`"),
+            ));
+        }
+        if fields.iter().any(|(other, _)| *other == name) {
+            return Err(syn::Error::new(
+                name.span(),
+                format!("field `{name}` appears more than once in the pattern"),
+            ));
+        }
+        fields.push((name, value));
+        match body.advance1() {
+            Some(TokenTree::Punct(p)) if p.as_char() == ',' => {}
+            None => break,
+            Some(t) => return Err(syn::Error::new(t.span(), "expected ',' or end of pattern")),
+        }
+    }
+
+    Ok(Pattern {
+        raw,
+        spec: tokens.into_iter().collect(),
+        fields,
+    })
+}
+
+impl Pattern {
+    fn generate(&self) -> proc_macro2::TokenStream {
+        let spec = &self.spec;
+        let steps = self.fields.iter().map(|(name, value)| {
+            let pat_fld = proc_macro2::Ident::new(&format!("__pat_{name}"), name.span());
+            let contradiction =
+                format!("pattern can never match, `{name}` contradicts the bits fixed before it");
+            quote! {
+                let (field_mask, field_value) = #spec::#pat_fld(#value);
+                if (value ^ field_value) & mask & field_mask != 0 {
+                    panic!(#contradiction);
+                }
+                let (mask, value) = (mask | field_mask, value | field_value);
+            }
+        });
+
+        // Starting from the spec's own fixed fields keeps patterns from matching other encodings.
+        let pattern = quote! {
+            const {
+                let (mask, value) = (#spec::MATCH_MASK, #spec::MATCH_VALUE);
+                #(#steps)*
+                (mask, value)
+            }
+        };
+        match &self.raw {
+            Some(raw) => quote! {
+                {
+                    let (mask, value) = #pattern;
+                    (#raw) & mask == value
+                }
+            },
+            None => pattern,
+        }
+    }
+}
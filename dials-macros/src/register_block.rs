@@ -0,0 +1,394 @@
+use crate::spec::parse::SpecParser;
+
+use proc_macro2::{Delimiter, Span, TokenTree};
+use quote::{format_ident, quote};
+
+/// Registers laid out at byte offsets, `struct Uart { 0x00 => ctrl: Ctrl, .. }`.
+struct RegisterBlock {
+    attrs: Vec<proc_macro2::TokenStream>,
+    vis: proc_macro2::TokenStream,
+    name: proc_macro2::Ident,
+    registers: Vec<Register>,
+}
+
+struct Register {
+    attrs: Vec<proc_macro2::TokenStream>,
+    offset: usize,
+    /// The end of the bytes given to the register, declared as `0x08..0x18 => fifo: [u32; 4]`.
+    end: Option<usize>,
+    name: proc_macro2::Ident,
+    ty: proc_macro2::TokenStream,
+    /// The element type of an array of registers, `[u32; 4]`.
+    elem: Option<proc_macro2::TokenStream>,
+}
+
+pub fn generate_register_block(
+    input: proc_macro2::TokenStream,
+) -> syn::Result<proc_macro2::TokenStream> {
+    Ok(parse(&mut SpecParser::new(input))?.generate())
+}
+
+fn parse(p: &mut SpecParser) -> syn::Result<RegisterBlock> {
+    let mut attrs = vec![];
+    while let Some(attr) = p.parse_attribute()? {
+        attrs.push(attr);
+    }
+
+    let mut vis = proc_macro2::TokenStream::new();
+    let name =
+        loop {
+            match p.advance1() {
+                Some(TokenTree::Ident(kw)) if kw == "struct" => match p.advance1() {
+                    Some(TokenTree::Ident(name)) => break name,
+                    t => {
+                        return Err(syn::Error::new(
+                            t.map(|t| t.span()).unwrap_or(kw.span()),
+                            "expected the name of the register block",
+                        ))
+                    }
+                },
+                Some(t) => vis.extend([t]),
+                None => return Err(syn::Error::new(
+                    Span::call_site(),
+                    "expected a register block of the form `struct Name { 0x00 => reg: Spec, .. }`",
+                )),
+            }
+        };
+
+    let mut body = match p.advance1() {
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => {
+            SpecParser::new(g.stream())
+        }
+        _ => {
+            return Err(syn::Error::new(
+                name.span(),
+                "expected a braced list of registers",
+            ))
+        }
+    };
+    if let Some(t) = p.peek1() {
+        return Err(syn::Error::new(
+            t.span(),
+            "expected end of input after the register block",
+        ));
+    }
+
+    let mut registers: Vec<Register> = vec![];
+    loop {
+        let mut attrs = vec![];
+        while let Some(attr) = body.parse_attribute()? {
+            attrs.push(attr);
+        }
+        let Some(first) = body.peek1() else {
+            break;
+        };
+        let offset = body.parse_int::<usize>()?;
+        let end = match body.peek2() {
+            (Some(TokenTree::Punct(p0)), Some(TokenTree::Punct(p1)))
+                if p0.as_char() == '.' && p1.as_char() == '.' =>
+            {
+                body.advance2();
+                Some(body.parse_int::<usize>()?)
+            }
+            _ => None,
+        };
+        let name = match body.advance2() {
+            (Some(TokenTree::Punct(p0)), Some(TokenTree::Punct(p1)))
+                if p0.as_char() == '=' && p1.as_char() == '>' =>
+            {
+                match body.advance2() {
+                    (Some(TokenTree::Ident(name)), Some(TokenTree::Punct(colon)))
+                        if colon.as_char() == ':' =>
+                    {
+                        name
+                    }
+                    _ => {
+                        return Err(syn::Error::new(
+                            first.span(),
+                            "expected `name: Type` after `=>`",
+                        ))
+                    }
+                }
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    first.span(),
+                    "expected a register of the form `0x00 => name: Type`",
+                ))
+            }
+        };
+
+        // The type runs up to the next comma outside of angle brackets.
+        let mut ty = vec![];
+        let mut depth = 0usize;
+        while let Some(t) = body.peek1() {
+            match &t {
+                TokenTree::Punct(p) if p.as_char() == ',' && depth == 0 => break,
+                TokenTree::Punct(p) if p.as_char() == '<' => depth += 1,
+                TokenTree::Punct(p) if p.as_char() == '>' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            body.advance1();
+            ty.push(t);
+        }
+        let elem = match &ty[..] {
+            [TokenTree::Group(g)] if g.delimiter() == Delimiter::Bracket => {
+                let inner = g.stream().into_iter().collect::<Vec<_>>();
+                let semi = inner
+                    .iter()
+                    .position(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == ';'));
+                semi.map(|semi| inner[..semi].iter().cloned().collect())
+            }
+            _ => None,
+        };
+        if ty.is_empty() {
+            return Err(syn::Error::new(
+                name.span(),
+                format!("expected the type of register `{name}`"),
+            ));
+        }
+
+        if let Some(end) = end.filter(|end| *end <= offset) {
+            return Err(syn::Error::new(
+                first.span(),
+                format!("register `{name}` ends at {end:#x}, before it starts at {offset:#x}"),
+            ));
+        }
+        if let Some(prev) = registers.last() {
+            if offset <= prev.offset {
+                return Err(syn::Error::new(
+                    first.span(),
+                    format!(
+                        "register `{name}` at {offset:#x} must come after `{}` at {:#x}",
+                        prev.name, prev.offset
+                    ),
+                ));
+            }
+            if let Some(prev_end) = prev.end.filter(|prev_end| offset < *prev_end) {
+                return Err(syn::Error::new(
+                    first.span(),
+                    format!(
+                        "register `{name}` at {offset:#x} overlaps `{}`, which ends at {prev_end:#x}",
+                        prev.name
+                    ),
+                ));
+            }
+        }
+        if let Some(other) = registers.iter().find(|r| r.name == name) {
+            return Err(syn::Error::new(
+                name.span(),
+                format!("register `{}` is declared more than once", other.name),
+            ));
+        }
+        registers.push(Register {
+            attrs,
+            offset,
+            end,
+            name,
+            ty: ty.into_iter().collect(),
+            elem,
+        });
+
+        match body.advance1() {
+            Some(TokenTree::Punct(p)) if p.as_char() == ',' => {}
+            None => break,
+            Some(t) => {
+                return Err(syn::Error::new(
+                    t.span(),
+                    "expected ',' or end of registers",
+                ))
+            }
+        }
+    }
+    if registers.is_empty() {
+        return Err(syn::Error::new(
+            name.span(),
+            "a register block needs at least one register",
+        ));
+    }
+
+    Ok(RegisterBlock {
+        attrs,
+        vis,
+        name,
+        registers,
+    })
+}
+
+impl RegisterBlock {
+    fn generate(&self) -> proc_macro2::TokenStream {
+        let Self {
+            attrs,
+            vis,
+            name,
+            registers,
+        } = self;
+        let regs = format_ident!("{}Regs", name);
+        let name_str = name.to_string();
+
+        let mut members = vec![];
+        let mut checks = vec![];
+        let mut consts = vec![];
+        let mut accessors = vec![];
+        // The end of the previous register, as an expression.
+        let mut prev_end = quote! { 0 };
+        for (idx, r) in registers.iter().enumerate() {
+            let Register {
+                attrs,
+                offset,
+                end,
+                name: reg,
+                ty,
+                elem,
+            } = r;
+            let lit_offset = proc_macro2::Literal::usize_suffixed(*offset);
+            // Saturating, so that overlapping registers fail the checks below with a message.
+            let pad = format_ident!("_reserved{}", idx);
+            let padding = (idx > 0 || *offset > 0).then(|| {
+                quote! { #pad: [u8; #lit_offset.saturating_sub(#prev_end)], }
+            });
+            members.push(quote! {
+                #padding
+                #(#[#attrs])*
+                pub #reg: #ty,
+            });
+
+            let misaligned = format!(
+                "register `{reg}` of `{name_str}` at {offset:#x} is not aligned for its type"
+            );
+            let overlapping = format!(
+                "register `{reg}` of `{name_str}` at {offset:#x} overlaps the register before it"
+            );
+            checks.push(quote! {
+                assert!(#lit_offset % ::core::mem::align_of::<#ty>() == 0, #misaligned);
+                assert!(#lit_offset >= #prev_end, #overlapping);
+                assert!(::core::mem::offset_of!(#name, #reg) == #lit_offset);
+            });
+            if let Some(end) = end {
+                let lit_end = proc_macro2::Literal::usize_suffixed(*end);
+                let mis_sized = format!(
+                    "register `{reg}` of `{name_str}` does not fill {offset:#x}..{end:#x} exactly"
+                );
+                checks.push(quote! {
+                    assert!(::core::mem::size_of::<#ty>() == #lit_end - #lit_offset, #mis_sized);
+                });
+            }
+            prev_end = quote! { #lit_offset + ::core::mem::size_of::<#ty>() };
+
+            let offset_const = format_ident!("{}_OFFSET", reg.to_string().to_uppercase());
+            let offset_doc = format!(" The byte offset of `{reg}` within the block.");
+            consts.push(quote! {
+                #[doc = #offset_doc]
+                pub const #offset_const: usize = #lit_offset;
+            });
+
+            let set_reg = format_ident!("set_{}", reg);
+            let modify_reg = format_ident!("modify_{}", reg);
+            let reg_str = reg.to_string();
+            accessors.push(match elem {
+                Some(elem) => {
+                    let out_of_bounds = format!(
+                        "index {{index}} is out of bounds for register array `{reg}` of length {{len}}"
+                    );
+                    let access = quote! {
+                        {
+                            let len = ::core::mem::size_of::<#ty>() / ::core::mem::size_of::<#elem>();
+                            assert!(index < len, #out_of_bounds);
+                            ::dials::bus::Access {
+                                register: #reg_str,
+                                index: Some(index),
+                                offset: #name::#offset_const + index * ::core::mem::size_of::<#elem>(),
+                                bytes: ::core::mem::size_of::<#elem>(),
+                                fields: <#elem as ::dials::bus::Register>::fields,
+                            }
+                        }
+                    };
+                    quote! {
+                        /// Reads element `index` of the register array, panicking if it is out of
+                        /// bounds.
+                        pub fn #reg(&self, index: usize) -> #elem {
+                            let access = #access;
+                            <#elem as ::dials::bus::Register>::from_raw(self.0.read(access))
+                        }
+
+                        /// Writes element `index` of the register array, panicking if it is out of
+                        /// bounds.
+                        pub fn #set_reg(&self, index: usize, value: #elem) {
+                            let access = #access;
+                            self.0.write(access, ::dials::bus::Register::to_raw(&value))
+                        }
+                    }
+                }
+                None => {
+                    let access = quote! {
+                        ::dials::bus::Access {
+                            register: #reg_str,
+                            index: None,
+                            offset: #name::#offset_const,
+                            bytes: ::core::mem::size_of::<#ty>(),
+                            fields: <#ty as ::dials::bus::Register>::fields,
+                        }
+                    };
+                    quote! {
+                        /// Reads the register.
+                        pub fn #reg(&self) -> #ty {
+                            <#ty as ::dials::bus::Register>::from_raw(self.0.read(#access))
+                        }
+
+                        /// Writes the register.
+                        pub fn #set_reg(&self, value: #ty) {
+                            self.0.write(#access, ::dials::bus::Register::to_raw(&value))
+                        }
+
+                        /// Reads the register, lets `f` change it and writes it back.
+                        pub fn #modify_reg(&self, f: impl ::core::ops::FnOnce(&mut #ty)) {
+                            let mut value = self.#reg();
+                            f(&mut value);
+                            self.#set_reg(value);
+                        }
+                    }
+                }
+            });
+        }
+
+        let regs_doc = format!(
+            " Access to the registers of a [`{name_str}`] through a `dials::bus::Bus`, by default the memory-mapped block itself."
+        );
+
+        quote! {
+            #(#[#attrs])*
+            #[repr(C)]
+            #vis struct #name {
+                #(#members)*
+            }
+
+            const _: () = {
+                #(#checks)*
+            };
+
+            impl #name {
+                #(#consts)*
+            }
+
+            #[doc = #regs_doc]
+            #[derive(Debug, Clone, Copy)]
+            #vis struct #regs<B = ::dials::bus::Mmio>(pub B);
+
+            impl #regs {
+                /// Volatile access to the block at `base`.
+                ///
+                /// # Safety
+                ///
+                /// `base` must be aligned and valid for volatile reads and writes of the whole
+                /// block for as long as the accessor, or any copy of it, is used.
+                pub const unsafe fn from_ptr(base: *mut #name) -> Self {
+                    Self(::dials::bus::Mmio::new(base.cast()))
+                }
+            }
+
+            impl<B: ::dials::bus::Bus> #regs<B> {
+                #(#accessors)*
+            }
+        }
+    }
+}
@@ -1,5 +1,5 @@
 mod generate;
-mod parse;
+pub(crate) mod parse;
 mod syntax;
 
 use crate::spec::parse::SpecParser;
@@ -7,9 +7,16 @@ use crate::spec::parse::SpecParser;
 pub fn generate_dials(input: proc_macro2::TokenStream) -> syn::Result<proc_macro2::TokenStream> {
     let mut output = proc_macro2::TokenStream::new();
 
-    SpecParser::new(input)
-        .parse()?
-        .generate_dials_impl(&mut output)?;
+    let items = SpecParser::new(input).parse_items()?;
+    for e in &items.enums {
+        e.generate(&mut output);
+    }
+    for c in &items.consts {
+        c.generate(&mut output);
+    }
+    for spec in items.specs {
+        spec.generate_dials_impl(&mut output)?;
+    }
 
     Ok(output)
 }
@@ -1,4 +1,19 @@
-use crate::spec::syntax::{Spec, SpecField};
+mod arith;
+mod check;
+mod diff;
+mod docs;
+mod encoding;
+mod fields;
+mod layout;
+mod pattern;
+mod register;
+mod serde;
+mod shared;
+mod text;
+mod validate;
+mod variants;
+
+use crate::spec::syntax::{Scale, Spec, SpecField};
 
 use std::str::FromStr;
 
@@ -6,11 +21,22 @@ use quote::{quote, ToTokens};
 
 impl Spec {
     pub fn generate_dials_impl(self, output: &mut proc_macro2::TokenStream) -> syn::Result<()> {
-        let Self { name, fields } = self;
+        let Self { name, fields, .. } = &self;
 
         let (struct_total_bits, struct_container_ty) = {
-            let total_bits = fields.iter().map(|f| f.size).sum::<usize>();
-            let struct_total_bits = container_size_bits(name.span(), total_bits)?;
+            let total_bits = fields
+                .iter()
+                .chain(
+                    self.variants
+                        .iter()
+                        .flat_map(|v| &v.variants)
+                        .flat_map(|v| &v.fields),
+                )
+                .map(|f| f.start + f.size)
+                .max()
+                .unwrap_or(0);
+            let struct_total_bits =
+                container_size_bits(name.span(), self.bits.unwrap_or(total_bits))?;
 
             (
                 struct_total_bits,
@@ -23,25 +49,90 @@ impl Spec {
             (0..struct_total_bits).map(|_| "1").collect::<String>()
         ))?;
 
+        if let Some(reset) = self
+            .reset
+            .filter(|reset| struct_total_bits < 128 && *reset >> struct_total_bits != 0)
+        {
+            return Err(syn::Error::new(
+                name.span(),
+                format!(
+                    "reset value {reset:#x} does not fit in the {struct_total_bits}-bit `{name}`"
+                ),
+            ));
+        }
+
+        // The value `new()` and `Default` start from, for documenting reset values.
+        let initial = self.with_checks(self.initial_bits())?;
+
         let consts: proc_macro2::TokenStream = fields
             .iter()
-            .map(|f| f.const_bitmask_declaration(struct_total_bits, &struct_container_ty))
+            .map(|f| f.const_bitmask_declaration(struct_total_bits, &struct_container_ty, initial))
             .collect::<syn::Result<_>>()?;
 
         let impls: proc_macro2::TokenStream = fields
             .iter()
-            .map(|f| f.getter_setter_declaration(struct_total_bits, &struct_container_ty))
+            .map(|f| {
+                f.getter_setter_declaration(
+                    struct_total_bits,
+                    &struct_container_ty,
+                    self.has_checks(),
+                    initial,
+                )
+            })
             .collect::<syn::Result<_>>()?;
 
+        let arithmetic: proc_macro2::TokenStream = fields
+            .iter()
+            .map(|f| f.arithmetic_declaration(struct_total_bits, &struct_container_ty))
+            .collect();
+
+        let matches = self.match_declaration(&struct_container_ty)?;
+        let checks = self.check_declaration(struct_total_bits, &struct_container_ty)?;
+        let diff = self.diff_declaration();
+        let patterns = self.pattern_fns(struct_total_bits, &struct_container_ty);
+        let default = self.has_required_bits().then(|| {
+            quote! {
+                impl ::core::default::Default for #name {
+                    fn default() -> Self {
+                        Self::new()
+                    }
+                }
+            }
+        });
+        let fields_struct = self.fields_struct(&struct_container_ty)?;
+        let validate = self.validate_impls(struct_total_bits, &struct_container_ty)?;
+        let text = self.text_impls(&struct_container_ty);
+        let serde = self.serde_impls(&struct_container_ty);
+        let register = self.register_impl(&struct_container_ty);
+        let variants = self.variant_impls(struct_total_bits)?;
+        let struct_docs = self.struct_docs(struct_total_bits, initial);
+        let layout = self.layout_const(struct_total_bits, initial);
+
         quote! {
+            #struct_docs
             #[repr(transparent)]
             pub struct #name (pub #struct_container_ty);
             impl #name {
                 const ALL_ONES: #struct_container_ty = #all_ones_bitmask;
                 #consts
+                #matches
 
                 #impls
+                #checks
+                #arithmetic
+                #diff
+                #patterns
+                #layout
             }
+
+            #default
+
+            #fields_struct
+            #validate
+            #text
+            #serde
+            #register
+            #variants
         }
         .to_tokens(output);
 
@@ -49,21 +140,172 @@ impl Spec {
     }
 }
 
+impl Spec {
+    /// The fields that make up a value, leaving out aliases of their bits and fixed or reserved
+    /// fields.
+    fn value_fields(&self) -> impl Iterator<Item = &SpecField> {
+        self.fields.iter().filter(|f| !f.alias && f.is_settable())
+    }
+
+    /// A new value to set fields of, `new()` if there are fixed, reserved or check fields.
+    fn initial_value(&self) -> proc_macro2::TokenStream {
+        let name = &self.name;
+        if self.has_checks() {
+            quote! { #name::new() }
+        } else {
+            quote! { #name(#name::INITIAL) }
+        }
+    }
+
+    /// The bits of the fixed and reserved fields, with every other bit zero or as in the reset
+    /// value of the spec.
+    fn initial_bits(&self) -> u128 {
+        let required = self
+            .fields
+            .iter()
+            .filter(|f| f.fixed.is_some() || f.reserved.is_some());
+        let mask = required
+            .clone()
+            .fold(0, |mask, f| mask | f.place(u128::MAX >> (128 - f.size)));
+        required
+            .filter_map(|f| Some(f.place(f.fixed.or(f.reserved)?)))
+            .fold(self.reset.unwrap_or(0) & !mask, |placed, value| {
+                placed | value
+            })
+    }
+
+    /// Whether new values start from anything but zero, because a field must hold a particular
+    /// value or the spec has a reset value.
+    fn has_required_bits(&self) -> bool {
+        self.reset.is_some() || self.fields.iter().any(|f| !f.is_settable())
+    }
+
+    /// The union of the bitmasks of every field, including those of every variant of a tagged
+    /// spec, as an expression of the container type.
+    fn field_mask(&self) -> proc_macro2::TokenStream {
+        let masks = std::iter::once(self)
+            .chain(self.variant_views(None).iter().map(|(_, view)| view))
+            .flat_map(|spec| {
+                let name = &spec.name;
+                spec.fields.iter().filter(|f| !f.alias).map(move |f| {
+                    let mask = f.const_ident("");
+                    quote! { #name::#mask }
+                })
+            })
+            .collect::<Vec<_>>();
+        quote! { (0 #(| #masks)*) }
+    }
+
+    /// The field whose value selects the variant of a tagged spec.
+    fn tag_field(&self) -> Option<&SpecField> {
+        let tag = &self.variants.as_ref()?.tag;
+        self.fields.iter().find(|f| f.name == *tag)
+    }
+
+    /// `MATCH_MASK` and `MATCH_VALUE` for the fixed fields, `matches` to test a raw value against
+    /// them, and `new` to create a value holding them.
+    fn match_declaration(
+        &self,
+        struct_container_ty: &proc_macro2::Ident,
+    ) -> syn::Result<proc_macro2::TokenStream> {
+        let mask = self
+            .fields
+            .iter()
+            .filter(|f| f.fixed.is_some())
+            .fold(0, |mask, f| mask | f.place(u128::MAX >> (128 - f.size)));
+        let value = self
+            .fields
+            .iter()
+            .filter_map(|f| Some(f.place(f.fixed?)))
+            .fold(0, |placed, value| placed | value);
+        let lit_mask = proc_macro2::Literal::from_str(&format!("{mask:#x}"))?;
+        let lit_value = proc_macro2::Literal::from_str(&format!("{value:#x}"))?;
+        let lit_initial = proc_macro2::Literal::from_str(&format!("{:#x}", self.initial_bits()))?;
+
+        let new_value = if self.has_checks() {
+            quote! {
+                let mut out = Self(Self::INITIAL);
+                out.__update_checks();
+                out
+            }
+        } else {
+            quote! { Self(Self::INITIAL) }
+        };
+        let new = self.has_required_bits().then(|| {
+            quote! {
+                /// A value holding the fixed and reserved fields and up to date checks, with every
+                /// other field zero, or as in the reset value given with `#[reset(..)]`.
+                pub fn new() -> Self {
+                    #new_value
+                }
+            }
+        });
+
+        Ok(quote! {
+            /// The bits of the fixed fields.
+            pub const MATCH_MASK: #struct_container_ty = #lit_mask;
+            /// The values of the fixed fields, placed within `MATCH_MASK`.
+            pub const MATCH_VALUE: #struct_container_ty = #lit_value;
+
+            /// The fixed and reserved fields and the reset value, from which new values are built
+            /// up.
+            const INITIAL: #struct_container_ty = #lit_initial;
+
+            /// Whether `raw` holds the values of the fixed fields.
+            pub const fn matches(raw: #struct_container_ty) -> bool {
+                raw & Self::MATCH_MASK == Self::MATCH_VALUE
+            }
+
+            #new
+        })
+    }
+}
+
 impl SpecField {
+    pub(super) fn prefixed_ident(&self, prefix: &str) -> proc_macro2::Ident {
+        proc_macro2::Ident::new(&format!("{}_{}", prefix, self.name), self.name.span())
+    }
+
+    pub(super) fn const_ident(&self, suffix: &str) -> proc_macro2::Ident {
+        let name = self.name.to_string().to_uppercase();
+        if suffix.is_empty() {
+            proc_macro2::Ident::new(&name, self.name.span())
+        } else {
+            proc_macro2::Ident::new(&format!("{}_{}", name, suffix), self.name.span())
+        }
+    }
+
+    /// The type a multi-bit field is read and written as.
+    pub(super) fn value_ty(&self, struct_container_ty: &proc_macro2::Ident) -> proc_macro2::Ident {
+        if self.signed {
+            proc_macro2::Ident::new(
+                &struct_container_ty.to_string().replacen('u', "i", 1),
+                struct_container_ty.span(),
+            )
+        } else {
+            struct_container_ty.clone()
+        }
+    }
+
     fn getter_setter_declaration(
         &self,
         struct_total_bits: usize,
         struct_container_ty: &proc_macro2::Ident,
+        update_checks: bool,
+        initial: u128,
     ) -> syn::Result<proc_macro2::TokenStream> {
         let mut output = proc_macro2::TokenStream::new();
-        let fld = &self.name;
+        let name = &self.name;
+        let getter_docs = if self.has_raw_accessors() {
+            self.method_docs(&format!("The raw bits of [`Self::{name}`]."), initial)
+        } else {
+            self.field_docs(&format!("The value of `{name}`."), initial)
+        };
+        let update = update_checks.then(|| quote! { self.__update_checks(); });
+        let fld = &self.getter_ident();
         let fld_const_id =
             proc_macro2::Ident::new(&self.name.to_string().to_uppercase(), self.name.span());
-        let fld_const_id_st = proc_macro2::Ident::new(
-            &format!("{}_START", self.name.to_string().to_uppercase()),
-            self.name.span(),
-        );
-        let set_fld = proc_macro2::Ident::new(&format!("set_{}", self.name), self.name.span());
+        let set_fld = self.setter_ident();
 
         if self.size == 1 {
             let unset_fld =
@@ -71,22 +313,39 @@ impl SpecField {
             let toggle_fld =
                 proc_macro2::Ident::new(&format!("toggle_{}", self.name), self.name.span());
 
-            quote! {
-                pub fn #fld(&self) -> bool {
-                    (self.0 >> Self::#fld_const_id_st) & 1 == 1
-                }
+            let set_docs = self.method_docs(&format!("Sets `{name}` to `true`."), initial);
+            let unset_docs = self.method_docs(&format!("Sets `{name}` to `false`."), initial);
+            let toggle_docs = self.method_docs(&format!("Flips `{name}`."), initial);
+            let setters = self.is_settable().then(|| {
+                quote! {
+                #set_docs
                 pub fn #set_fld(&mut self) -> &mut Self {
                     self.0 |= Self::#fld_const_id;
+                    #update
                     self
                 }
+                #unset_docs
                 pub fn #unset_fld(&mut self) -> &mut Self {
-                    self.0 &= Self::#fld_const_id;
+                    self.0 &= !Self::#fld_const_id;
+                    #update
                     self
                 }
+                #toggle_docs
                 pub fn #toggle_fld(&mut self) -> &mut Self {
                     self.0 ^= Self::#fld_const_id;
+                    #update
                     self
                 }
+                }
+            });
+
+            let raw_value = self.raw_bits_tokens(struct_total_bits);
+            quote! {
+                #getter_docs
+                pub fn #fld(&self) -> bool {
+                    #raw_value == 1
+                }
+                #setters
             }
             .to_tokens(&mut output)
         } else {
@@ -96,31 +355,265 @@ impl SpecField {
             );
             let lit_struct_total_bits =
                 proc_macro2::Literal::from_str(&struct_total_bits.to_string())?;
-            let lit_2_as_struct_container_type =
-                proc_macro2::Literal::from_str(&format!("2_{struct_container_ty}"))?;
+            let value_ty = self.value_ty(struct_container_ty);
+            let setter_docs = if self.has_raw_accessors() {
+                format!(
+                    "Sets the raw bits of [`Self::{name}`] to `value`, wrapped to {} bits.",
+                    self.size
+                )
+            } else {
+                format!("Sets `{name}` to `value`, wrapped to {} bits.", self.size)
+            };
+            let setter_docs = self.method_docs(&setter_docs, initial);
+
+            let raw_value = self.raw_bits_tokens(struct_total_bits);
+            let placed = self.place_tokens();
+            let store = quote! {
+                self.0 = (self.0 & !Self::#fld_const_id) | #placed;
+                #update
+            };
+
+            let (getter, setter) = if self.signed {
+                (
+                    quote! {
+                        let value = #raw_value;
+                        let unused = #lit_struct_total_bits - Self::#fld_const_id_sz;
+                        ((value << unused) as #value_ty) >> unused
+                    },
+                    quote! {
+                        #setter_docs
+                        pub fn #set_fld(&mut self, value: #value_ty) -> &mut Self {
+                            let mut value = value as #struct_container_ty;
+                            value &= Self::ALL_ONES >> (#lit_struct_total_bits - Self::#fld_const_id_sz);
+                            #store
+                            self
+                        }
+                    },
+                )
+            } else {
+                (
+                    raw_value,
+                    quote! {
+                        #setter_docs
+                        pub fn #set_fld(&mut self, mut value: #struct_container_ty) -> &mut Self {
+                            // Wrap modulo 2^SIZE, also for fields as wide as the container.
+                            value &= Self::ALL_ONES >> (#lit_struct_total_bits - Self::#fld_const_id_sz);
+                            #store
+                            self
+                        }
+                    },
+                )
+            };
+
+            let setter = self.is_settable().then_some(setter);
+
             quote! {
-                pub fn #fld(&self) -> #struct_container_ty {
-                    (self.0 >> Self::#fld_const_id_st) & (Self::ALL_ONES >> (#lit_struct_total_bits - Self::#fld_const_id_sz))
+                #getter_docs
+                pub fn #fld(&self) -> #value_ty {
+                    #getter
                 }
-                pub fn #set_fld(&mut self, mut value: #struct_container_ty) -> &mut Self {
-                    if value >= #lit_2_as_struct_container_type.pow(Self::#fld_const_id_sz as u32) {
-                        value = value % #lit_2_as_struct_container_type.pow(Self::#fld_const_id_sz as u32);
-                    }
-                    let mask = Self::ALL_ONES ^ Self::#fld_const_id;
+                #setter
+            }
+            .to_tokens(&mut output);
 
-                    (*self).0 = (self.0 & mask) | (value << Self::#fld_const_id_st);
-                    self
+            if let Some(enum_ty) = &self.enum_ty {
+                let set_name = self.prefixed_ident("set");
+                let getter_docs = self.field_docs(
+                    "The variant held by the field, or `None` if its bits are not a valid discriminant.",
+                    initial,
+                );
+                let setter_docs =
+                    self.method_docs(&format!("Sets `{name}` to the variant `value`."), initial);
+                let bits = if struct_total_bits == 128 {
+                    quote! { ::dials::FieldEnum::to_bits(&value) }
+                } else {
+                    quote! { ::dials::FieldEnum::to_bits(&value) as #struct_container_ty }
+                };
+                quote! {
+                    #getter_docs
+                    pub fn #name(&self) -> ::core::option::Option<#enum_ty> {
+                        <#enum_ty as ::dials::FieldEnum>::from_bits(u128::from(self.#fld()))
+                    }
+                    #setter_docs
+                    pub fn #set_name(&mut self, value: #enum_ty) -> &mut Self {
+                        self.#set_fld(#bits)
+                    }
                 }
+                .to_tokens(&mut output);
+            }
+
+            if let Some(encoding) = self.encoding {
+                self.encoded_accessors(encoding, struct_container_ty, initial)?
+                    .to_tokens(&mut output);
+            }
+
+            if let Some(scale) = &self.scale {
+                self.scaled_accessors(scale, struct_total_bits, initial)
+                    .to_tokens(&mut output);
             }
-            .to_tokens(&mut output)
         };
         Ok(output)
     }
 
+    /// `SCALE` and `OFFSET` consts, and accessors of the real value `bits * SCALE + OFFSET`.
+    fn scaled_accessors(
+        &self,
+        scale: &Scale,
+        struct_total_bits: usize,
+        initial: u128,
+    ) -> proc_macro2::TokenStream {
+        let name = &self.name;
+        let name_str = proc_macro2::Literal::string(&self.name.to_string());
+        let set_name = self.prefixed_ident("set");
+        let fld = self.getter_ident();
+        let set_fld = self.setter_ident();
+        let fld_const_id_sz = self.const_ident("SIZE");
+        let fld_const_id_scale = self.const_ident("SCALE");
+        let fld_const_id_offset = self.const_ident("OFFSET");
+        let factor = proc_macro2::Literal::f64_unsuffixed(scale.factor);
+        let offset = proc_macro2::Literal::f64_unsuffixed(scale.offset.abs());
+        let offset = if scale.offset.is_sign_negative() {
+            quote! { -#offset }
+        } else {
+            quote! { #offset }
+        };
+        let (min, max) = if self.signed {
+            let half = 1u128 << (self.size - 1);
+            (-(half as f64), (half - 1) as f64)
+        } else {
+            (0.0, (u128::MAX >> (128 - self.size)) as f64)
+        };
+        let (min, max) = (
+            proc_macro2::Literal::f64_unsuffixed(min.abs()),
+            proc_macro2::Literal::f64_unsuffixed(max),
+        );
+        let min = if self.signed {
+            quote! { -#min }
+        } else {
+            quote! { #min }
+        };
+        let value_ty = self.value_ty(&proc_macro2::Ident::new(
+            &format!("u{}", struct_total_bits),
+            self.name.span(),
+        ));
+        let getter_docs = self.field_docs(
+            "The real value of the field, `bits * SCALE + OFFSET`.",
+            initial,
+        );
+        let setter_docs = self.method_docs(
+            "Sets the field to the nearest value it can hold, failing if `value` is out of its range.",
+            initial,
+        );
+        let setter = self.is_settable().then(|| {
+            quote! {
+                #setter_docs
+                pub fn #set_name(&mut self, value: f64) -> ::core::result::Result<&mut Self, ::dials::RangeError> {
+                    let bits = ((value - Self::#fld_const_id_offset) / Self::#fld_const_id_scale).round();
+                    if !(#min..=#max).contains(&bits) {
+                        return Err(::dials::RangeError {
+                            field: #name_str,
+                            value: value.to_string(),
+                            bits: Self::#fld_const_id_sz,
+                        });
+                    }
+                    Ok(self.#set_fld(bits as #value_ty))
+                }
+            }
+        });
+
+        let scale_doc = format!(" The step between successive values of `{name}`.");
+        let offset_doc = format!(" The value of `{name}` when its raw bits are zero.");
+
+        quote! {
+            #[doc = #scale_doc]
+            pub const #fld_const_id_scale: f64 = #factor;
+            #[doc = #offset_doc]
+            pub const #fld_const_id_offset: f64 = #offset;
+
+            #getter_docs
+            pub fn #name(&self) -> f64 {
+                self.#fld() as f64 * Self::#fld_const_id_scale + Self::#fld_const_id_offset
+            }
+            #setter
+        }
+    }
+
+    /// The name of the getter of the field's raw value, which enum and scaled fields suffix with
+    /// `_bits`.
+    pub(super) fn getter_ident(&self) -> proc_macro2::Ident {
+        if self.has_raw_accessors() {
+            proc_macro2::Ident::new(&format!("{}_bits", self.name), self.name.span())
+        } else {
+            self.name.clone()
+        }
+    }
+
+    /// The name of the setter of the field's raw value, see `getter_ident`.
+    pub(super) fn setter_ident(&self) -> proc_macro2::Ident {
+        if self.has_raw_accessors() {
+            proc_macro2::Ident::new(&format!("set_{}_bits", self.name), self.name.span())
+        } else {
+            self.prefixed_ident("set")
+        }
+    }
+
+    /// An expression reading the bits of the field as an unsigned value of the container type.
+    pub(super) fn raw_bits_tokens(&self, struct_total_bits: usize) -> proc_macro2::TokenStream {
+        let fld_const_id_st = self.const_ident("START");
+        let fld_const_id_sz = self.const_ident("SIZE");
+        let lit_struct_total_bits = proc_macro2::Literal::usize_unsuffixed(struct_total_bits);
+        match &self.split {
+            None if self.size == 1 => quote! { ((self.0 >> Self::#fld_const_id_st) & 1) },
+            None => quote! {
+                ((self.0 >> Self::#fld_const_id_st) & (Self::ALL_ONES >> (#lit_struct_total_bits - Self::#fld_const_id_sz)))
+            },
+            Some(split) => {
+                // Segments are listed most significant first, so the last one sits just above
+                // the implied zero bits.
+                let mut offset = split.shift;
+                let mut gather = vec![];
+                for r in split.segments.iter().rev() {
+                    let seg_mask =
+                        proc_macro2::Literal::u128_unsuffixed(u128::MAX >> (128 - r.size));
+                    let from_raw = shift_tokens(quote! { self.0 }, ">>", r.start);
+                    gather.push(shift_tokens(
+                        quote! { (#from_raw & #seg_mask) },
+                        "<<",
+                        offset,
+                    ));
+                    offset += r.size;
+                }
+                quote! { (#(#gather)|*) }
+            }
+        }
+    }
+
+    /// An expression moving `value`, already wrapped to the field's size, into the field's bits.
+    fn place_tokens(&self) -> proc_macro2::TokenStream {
+        let Some(split) = &self.split else {
+            let fld_const_id_st = self.const_ident("START");
+            return quote! { (value << Self::#fld_const_id_st) };
+        };
+        let mut offset = split.shift;
+        let mut scatter = vec![];
+        for r in split.segments.iter().rev() {
+            let seg_mask = proc_macro2::Literal::u128_unsuffixed(u128::MAX >> (128 - r.size));
+            let from_value = shift_tokens(quote! { value }, ">>", offset);
+            scatter.push(shift_tokens(
+                quote! { (#from_value & #seg_mask) },
+                "<<",
+                r.start,
+            ));
+            offset += r.size;
+        }
+        quote! { (#(#scatter)|*) }
+    }
+
     fn const_bitmask_declaration(
         &self,
         struct_total_bits: usize,
         struct_container_ty: &proc_macro2::Ident,
+        initial: u128,
     ) -> syn::Result<proc_macro2::TokenStream> {
         let mut output = proc_macro2::TokenStream::new();
         let lit_bitmask = proc_macro2::Literal::from_str(&format!(
@@ -128,7 +621,11 @@ impl SpecField {
             (0..struct_total_bits)
                 .rev()
                 .map(|idx| {
-                    if idx >= self.start && idx < self.start + self.size {
+                    if self
+                        .bit_ranges()
+                        .iter()
+                        .any(|r| idx >= r.start && idx < r.start + r.size)
+                    {
                         "1"
                     } else {
                         "0"
@@ -139,13 +636,18 @@ impl SpecField {
         let const_name =
             proc_macro2::Ident::new(&self.name.to_string().to_uppercase(), self.name.span());
 
+        let docs = self.field_docs(&format!("The mask of `{}`.", self.name), initial);
+
         quote! {
+            #docs
             pub const #const_name: #struct_container_ty = #lit_bitmask;
         }
         .to_tokens(&mut output);
 
-        self.lit_to_associated_const("start")?
-            .to_tokens(&mut output);
+        if self.split.is_none() {
+            self.lit_to_associated_const("start")?
+                .to_tokens(&mut output);
+        }
         self.lit_to_associated_const("size")?.to_tokens(&mut output);
 
         Ok(output)
@@ -160,22 +662,19 @@ impl SpecField {
             ),
             self.name.span(),
         );
-        let lit = proc_macro2::Literal::from_str(
-            &{
-                if modifier == "start" {
-                    self.start
-                } else if modifier == "size" {
-                    self.size
-                } else {
-                    return Err(syn::Error::new(
-                        self.name.span(),
-                        &format!("not implemented for {}", modifier),
-                    ));
-                }
-            }
-            .to_string(),
-        )?;
+        let (value, doc) = if modifier == "start" {
+            (self.start, format!(" The lowest bit of `{}`.", self.name))
+        } else if modifier == "size" {
+            (self.size, format!(" The width of `{}` in bits.", self.name))
+        } else {
+            return Err(syn::Error::new(
+                self.name.span(),
+                format!("not implemented for {}", modifier),
+            ));
+        };
+        let lit = proc_macro2::Literal::from_str(&value.to_string())?;
         Ok(quote! {
+            #[doc = #doc]
             pub const #const_mod: usize = #lit;
         })
     }
@@ -196,3 +695,25 @@ fn container_size_bits(span: proc_macro2::Span, total_bits: usize) -> syn::Resul
         }
     })
 }
+
+/// `expr << n` or `expr >> n`, leaving out shifts by zero.
+fn shift_tokens(expr: proc_macro2::TokenStream, op: &str, n: usize) -> proc_macro2::TokenStream {
+    if n == 0 {
+        return expr;
+    }
+    let n = proc_macro2::Literal::usize_unsuffixed(n);
+    match op {
+        "<<" => quote! { (#expr << #n) },
+        _ => quote! { (#expr >> #n) },
+    }
+}
+
+/// `expr as to`, leaving out the cast when `expr` already has that type.
+fn cast(expr: proc_macro2::TokenStream, from: &str, to: &str) -> proc_macro2::TokenStream {
+    if from == to {
+        expr
+    } else {
+        let to = proc_macro2::Ident::new(to, proc_macro2::Span::call_site());
+        quote! { #expr as #to }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::spec::syntax::SpecField;
+
+use quote::quote;
+
+impl SpecField {
+    /// In-place arithmetic on a multi-bit field, working modulo `2^SIZE` through its getter and
+    /// setter so neighbouring bits are left alone.
+    pub(super) fn arithmetic_declaration(
+        &self,
+        struct_total_bits: usize,
+        struct_container_ty: &proc_macro2::Ident,
+    ) -> proc_macro2::TokenStream {
+        if self.size == 1 || !self.is_settable() || self.has_raw_accessors() {
+            return quote! {};
+        }
+        let fld = &self.name;
+        let set_fld = self.prefixed_ident("set");
+        let wrapping_add_fld = self.prefixed_ident("wrapping_add");
+        let checked_add_fld = self.prefixed_ident("checked_add");
+        let saturating_sub_fld = self.prefixed_ident("saturating_sub");
+        let increment_fld = self.prefixed_ident("increment");
+        let fld_const_id_sz = self.const_ident("SIZE");
+        let value_ty = self.value_ty(struct_container_ty);
+        let lit_struct_total_bits = proc_macro2::Literal::usize_unsuffixed(struct_total_bits);
+
+        let out_of_range = if self.signed {
+            Some(quote! { !matches!(value >> (Self::#fld_const_id_sz - 1), 0 | -1) })
+        } else {
+            // `checked_add` already catches overflow of fields as wide as the container.
+            (self.size < struct_total_bits).then(|| quote! { value >> Self::#fld_const_id_sz != 0 })
+        }
+        .map(|out_of_range| {
+            quote! {
+                if #out_of_range {
+                    return None;
+                }
+            }
+        });
+        // Unsigned differences can only shrink, so they stay within the field once clamped at 0.
+        let clamp = self.signed.then(|| {
+            quote! {
+                let unused = #lit_struct_total_bits - Self::#fld_const_id_sz;
+                let value = value.clamp(#value_ty::MIN >> unused, #value_ty::MAX >> unused);
+            }
+        });
+        let wraps_to = if self.signed {
+            "its smallest value"
+        } else {
+            "zero"
+        };
+        let increment_doc = format!(
+            " Adds one to the field, wrapping around to {wraps_to} after its largest value."
+        );
+
+        quote! {
+            /// Adds `rhs` to the field, wrapping around at its bounds.
+            pub fn #wrapping_add_fld(&mut self, rhs: #value_ty) -> &mut Self {
+                let value = self.#fld().wrapping_add(rhs);
+                self.#set_fld(value)
+            }
+
+            /// Adds `rhs` to the field, or leaves it unchanged and returns `None` if the sum does
+            /// not fit.
+            pub fn #checked_add_fld(&mut self, rhs: #value_ty) -> ::core::option::Option<&mut Self> {
+                let value = self.#fld().checked_add(rhs)?;
+                #out_of_range
+                Some(self.#set_fld(value))
+            }
+
+            /// Subtracts `rhs` from the field, stopping at its bounds.
+            pub fn #saturating_sub_fld(&mut self, rhs: #value_ty) -> &mut Self {
+                let value = self.#fld().saturating_sub(rhs);
+                #clamp
+                self.#set_fld(value)
+            }
+
+            #[doc = #increment_doc]
+            pub fn #increment_fld(&mut self) -> &mut Self {
+                self.#wrapping_add_fld(1)
+            }
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use crate::spec::generate::cast;
+use crate::spec::syntax::{Check, Spec, SpecField};
+
+use quote::quote;
+
+impl Spec {
+    fn check_fields(&self) -> impl Iterator<Item = (&SpecField, &Check)> {
+        self.fields
+            .iter()
+            .filter_map(|f| Some((f, f.check.as_ref()?)))
+    }
+
+    /// Whether setters need to recompute parity or checksum fields.
+    pub(super) fn has_checks(&self) -> bool {
+        self.check_fields().next().is_some()
+    }
+
+    /// The fields a check is computed over, which can't be checks themselves.
+    fn covered_fields<'a>(&'a self, check: &Check) -> syn::Result<Vec<&'a SpecField>> {
+        check
+            .over()
+            .iter()
+            .map(|name| match self.fields.iter().find(|f| f.name == *name) {
+                Some(f) if f.check.is_none() => Ok(f),
+                Some(_) => Err(syn::Error::new(
+                    name.span(),
+                    format!("`{name}` is itself a check field and can't be covered by another"),
+                )),
+                None => Err(syn::Error::new(
+                    name.span(),
+                    format!("`{name}` is not a field of `{}`", self.name),
+                )),
+            })
+            .collect()
+    }
+
+    /// An expression computing the bits check field `f` should hold, as a `u128`.
+    pub(super) fn check_value(
+        &self,
+        f: &SpecField,
+        check: &Check,
+        struct_total_bits: usize,
+    ) -> syn::Result<proc_macro2::TokenStream> {
+        let covered = self.covered_fields(check)?;
+        Ok(match check {
+            Check::Parity { odd, .. } => {
+                let masks = covered.iter().map(|c| c.const_ident(""));
+                let flip = odd.then(|| quote! { ^ 1 });
+                quote! {
+                    (u128::from((self.0 & (#(Self::#masks)|*)).count_ones() & 1) #flip)
+                }
+            }
+            Check::Crc { poly, init, .. } => {
+                let message = covered.iter().map(|c| {
+                    let raw_value = c.raw_bits_tokens(struct_total_bits);
+                    let size = proc_macro2::Literal::usize_unsuffixed(c.size);
+                    quote! { (u128::from(#raw_value), #size) }
+                });
+                let width = proc_macro2::Literal::usize_unsuffixed(f.size);
+                let poly = proc_macro2::Literal::u128_unsuffixed(*poly);
+                let init = proc_macro2::Literal::u128_unsuffixed(*init);
+                quote! {
+                    ::dials::check::crc(&[#(#message),*], #width, #poly, #init)
+                }
+            }
+        })
+    }
+
+    /// `initial` with every check field holding the value computed over it, as in `new()`.
+    pub(super) fn with_checks(&self, initial: u128) -> syn::Result<u128> {
+        let mut out = initial;
+        for (f, check) in self.check_fields() {
+            let covered = self.covered_fields(check)?;
+            let value = match check {
+                Check::Parity { odd, .. } => {
+                    let ones: u32 = covered
+                        .iter()
+                        .map(|c| c.extract(initial).count_ones())
+                        .sum();
+                    u128::from(ones & 1 == 1) ^ u128::from(*odd)
+                }
+                Check::Crc { poly, init, .. } => {
+                    let message = covered.iter().map(|c| (c.extract(initial), c.size));
+                    crc(message, f.size, *poly, *init)
+                }
+            };
+            out = (out & !f.place(u128::MAX >> (128 - f.size))) | f.place(value);
+        }
+        Ok(out)
+    }
+
+    /// `__update_checks`, called by every setter to recompute the parity and checksum fields.
+    pub(super) fn check_declaration(
+        &self,
+        struct_total_bits: usize,
+        struct_container_ty: &proc_macro2::Ident,
+    ) -> syn::Result<proc_macro2::TokenStream> {
+        if !self.has_checks() {
+            return Ok(quote! {});
+        }
+        let updates = self
+            .check_fields()
+            .map(|(f, check)| {
+                let value = cast(
+                    self.check_value(f, check, struct_total_bits)?,
+                    "u128",
+                    &struct_container_ty.to_string(),
+                );
+                let fld_const_id = f.const_ident("");
+                let placed = f.place_tokens();
+                Ok(quote! {
+                    let value = #value;
+                    self.0 = (self.0 & !Self::#fld_const_id) | #placed;
+                })
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        Ok(quote! {
+            fn __update_checks(&mut self) {
+                #(#updates)*
+            }
+        })
+    }
+}
+
+/// The CRC computed by `::dials::check::crc`, for documenting the reset value of CRC fields.
+fn crc(message: impl Iterator<Item = (u128, usize)>, width: usize, poly: u128, init: u128) -> u128 {
+    let mask = u128::MAX >> (128 - width);
+    let top = 1 << (width - 1);
+    let mut crc = init;
+    for (value, bits) in message {
+        for bit in (0..bits).rev() {
+            let feedback = (crc & top != 0) != ((value >> bit) & 1 == 1);
+            crc = (crc << 1) & mask;
+            if feedback {
+                crc ^= poly;
+            }
+        }
+    }
+    crc
+}
@@ -0,0 +1,71 @@
+use crate::spec::syntax::Spec;
+
+use quote::quote;
+
+impl Spec {
+    /// `diff`, yielding the fields that differ between two values. Aliases are left out, as the
+    /// fields they alias already report their bits.
+    pub(super) fn diff_declaration(&self) -> proc_macro2::TokenStream {
+        let name_str = proc_macro2::Literal::string(&self.name.to_string());
+        let fields = self.fields.iter().filter(|f| !f.alias);
+        let field_count = fields.clone().count();
+        let changes = fields.map(|f| {
+            let fld = f.getter_ident();
+            let fld_str = proc_macro2::Literal::string(&f.name.to_string());
+            quote! {
+                ::dials::FieldChange {
+                    field: #fld_str,
+                    old: ::dials::FieldValue::from(self.#fld()),
+                    new: ::dials::FieldValue::from(other.#fld()),
+                }
+            }
+        });
+
+        // The fields of tagged values depend on their variants, so they are compared by name,
+        // as listed by `Register::fields`.
+        if self.variants.is_some() {
+            return quote! {
+                /// The fields whose values differ between `self` and `other`, out of those of the
+                /// variant of `self` that the variant of `other` also has. Formatting the result
+                /// with `{:?}` lists them as `Name { field: old -> new, .. }`.
+                pub fn diff(
+                    &self,
+                    other: &Self,
+                ) -> impl ::core::iter::Iterator<Item = ::dials::FieldChange>
+                       + ::core::clone::Clone
+                       + ::core::fmt::Debug {
+                    let old = <Self as ::dials::bus::Register>::fields(u128::from(self.0));
+                    let new = <Self as ::dials::bus::Register>::fields(u128::from(other.0));
+                    let changes = old
+                        .into_iter()
+                        .filter_map(|(field, old)| {
+                            let (_, new) = new.iter().find(|(name, _)| *name == field)?;
+                            Some(::dials::FieldChange { field, old, new: *new })
+                        })
+                        .collect::<::std::vec::Vec<_>>();
+                    ::dials::diff::Diff::new(
+                        #name_str,
+                        changes.into_iter().filter(|change| change.old != change.new),
+                    )
+                }
+            };
+        }
+
+        quote! {
+            /// The fields whose values differ between `self` and `other`. Formatting the result
+            /// with `{:?}` lists them as `Name { field: old -> new, .. }`.
+            pub fn diff(
+                &self,
+                other: &Self,
+            ) -> impl ::core::iter::Iterator<Item = ::dials::FieldChange>
+                   + ::core::clone::Clone
+                   + ::core::fmt::Debug {
+                let changes: [::dials::FieldChange; #field_count] = [#(#changes),*];
+                ::dials::diff::Diff::new(
+                    #name_str,
+                    changes.into_iter().filter(|change| change.old != change.new),
+                )
+            }
+        }
+    }
+}
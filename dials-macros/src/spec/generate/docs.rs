@@ -0,0 +1,238 @@
+use crate::spec::syntax::{Access, BitRange, Spec, SpecField};
+
+use quote::quote;
+
+impl Spec {
+    /// The doc comments of the struct followed by a table of its bits, most significant first.
+    pub(super) fn struct_docs(
+        &self,
+        struct_total_bits: usize,
+        initial: u128,
+    ) -> proc_macro2::TokenStream {
+        let mut rows = self
+            .fields
+            .iter()
+            .filter(|f| !f.alias)
+            .flat_map(|f| f.bit_ranges().into_iter().map(move |r| (r, Some(f))))
+            .collect::<Vec<_>>();
+        let used = rows.iter().fold(0u128, |used, (r, _)| {
+            used | (u128::MAX >> (128 - r.size)) << r.start
+        });
+        let mut bit = 0;
+        while bit < struct_total_bits {
+            let size = (bit..struct_total_bits)
+                .take_while(|b| used >> b & 1 == 0)
+                .count();
+            if size == 0 {
+                bit += 1;
+            } else {
+                rows.push((BitRange { start: bit, size }, None));
+                bit += size;
+            }
+        }
+        rows.sort_by_key(|(r, _)| std::cmp::Reverse(r.start));
+        rows.extend(
+            self.fields
+                .iter()
+                .filter(|f| f.alias)
+                .flat_map(|f| f.bit_ranges().into_iter().map(move |r| (r, Some(f)))),
+        );
+
+        let mut lines = self.doc.clone();
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(format!(
+            " Layout of the `u{struct_total_bits}`, most significant bits first:"
+        ));
+        lines.push(String::new());
+        lines.push(" | Bits | Field | Access | Reset | Description |".to_string());
+        lines.push(" |------|-------|--------|-------|-------------|".to_string());
+        for (r, f) in rows {
+            lines.push(match f {
+                Some(f) => format!(
+                    " | {} | `{}` | {} | {} | {} |",
+                    range_doc(&r),
+                    f.name,
+                    f.access_doc(),
+                    f.reset_doc(initial),
+                    f.doc
+                        .first()
+                        .map(|line| line.trim().replace('|', "\\|"))
+                        .unwrap_or_default(),
+                ),
+                None => format!(" | {} | | unused | | |", range_doc(&r)),
+            });
+        }
+        quote! { #(#[doc = #lines])* }
+    }
+}
+
+impl SpecField {
+    /// The doc comments of the field, then a summary of its bits, reset value and access.
+    pub(super) fn field_docs(&self, summary: &str, initial: u128) -> proc_macro2::TokenStream {
+        let mut lines = self.doc.clone();
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(format!(" {summary}"));
+        lines.push(String::new());
+        lines.push(format!(" {}", self.layout_doc(initial)));
+        quote! { #(#[doc = #lines])* }
+    }
+
+    /// A one-line doc comment followed by a summary of the field's bits, reset value and access.
+    pub(super) fn method_docs(&self, summary: &str, initial: u128) -> proc_macro2::TokenStream {
+        let summary = format!(" {summary}");
+        let layout = format!(" {}", self.layout_doc(initial));
+        quote! {
+            #[doc = #summary]
+            #[doc = ""]
+            #[doc = #layout]
+        }
+    }
+
+    /// `Bits [11:8], 4 bits wide, reset value 0x3, read-write.`
+    fn layout_doc(&self, initial: u128) -> String {
+        let mut bits = self
+            .bit_ranges()
+            .iter()
+            .map(range_doc)
+            .collect::<Vec<_>>()
+            .join(", ");
+        if let Some(split) = self.split.as_ref().filter(|s| s.shift > 0) {
+            bits.push_str(&format!(" shifted left by {}", split.shift));
+        }
+        let width = match self.size {
+            1 => String::new(),
+            size => format!(", {size} bits wide"),
+        };
+        format!(
+            "{} {bits}{width}, reset value {}, {}.",
+            if self.size == 1 { "Bit" } else { "Bits" },
+            self.reset_doc(initial),
+            self.access_doc(),
+        )
+    }
+
+    fn reset_doc(&self, initial: u128) -> String {
+        match self.extract(initial) {
+            value if self.size == 1 => format!("`{}`", value == 1),
+            value => format!("`{value:#x}`"),
+        }
+    }
+
+    fn access_doc(&self) -> &'static str {
+        if self.fixed.is_some() {
+            "read-only, fixed"
+        } else if self.reserved.is_some() {
+            "reserved"
+        } else if self.check.is_some() {
+            "read-only, maintained by setters"
+        } else {
+            match (self.access, self.alias) {
+                (Some(Access::ReadOnly), _) => "read-only",
+                (Some(Access::WriteOnly), _) => "write-only",
+                (Some(Access::WriteOnce), _) => "write-once",
+                (Some(Access::ReadWriteOnce), _) => "read, write-once",
+                (_, true) => "read-write, alias",
+                _ => "read-write",
+            }
+        }
+    }
+}
+
+/// `[11:8]`, or `[3]` for a single bit.
+fn range_doc(r: &BitRange) -> String {
+    match r.size {
+        1 => format!("`[{}]`", r.start),
+        _ => format!("`[{}:{}]`", r.start + r.size - 1, r.start),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proc_macro2::TokenTree;
+    use quote::quote;
+
+    /// The doc strings generated for `input`, one per `#[doc = ".."]` attribute.
+    fn docs(input: proc_macro2::TokenStream) -> Vec<String> {
+        let mut docs = vec![];
+        collect_docs(crate::spec::generate_dials(input).unwrap(), &mut docs);
+        docs
+    }
+
+    fn collect_docs(tokens: proc_macro2::TokenStream, docs: &mut Vec<String>) {
+        for token in tokens {
+            let TokenTree::Group(group) = token else {
+                continue;
+            };
+            let attr = group.stream().into_iter().collect::<Vec<_>>();
+            match &attr[..] {
+                [TokenTree::Ident(id), TokenTree::Punct(eq), TokenTree::Literal(lit)]
+                    if id == "doc" && eq.as_char() == '=' =>
+                {
+                    if let syn::Lit::Str(s) = syn::Lit::new(lit.clone()) {
+                        docs.push(s.value());
+                    }
+                }
+                _ => collect_docs(group.stream(), docs),
+            }
+        }
+    }
+
+    #[test]
+    fn layout_table_and_accessor_docs() {
+        let docs = docs(quote! {
+            /// A status register.
+            #[reset(0x8001)]
+            struct Status {
+                /// Set once the device is ready.
+                #[access(read_only)]
+                ready: bool,
+                #[reserved]
+                rsvd: u3,
+                mode: u4 == 5,
+                #[access(write_once)]
+                lock: u8,
+            }
+        });
+        let has = |line: &str| docs.iter().any(|doc| doc == line);
+
+        assert!(has(" A status register."));
+        assert!(has(" Layout of the `u16`, most significant bits first:"));
+        assert!(has(" | Bits | Field | Access | Reset | Description |"));
+        assert!(has(" | `[15:8]` | `lock` | write-once | `0x80` |  |"));
+        assert!(has(" | `[7:4]` | `mode` | read-only, fixed | `0x5` |  |"));
+        assert!(has(" | `[3:1]` | `rsvd` | reserved | `0x0` |  |"));
+        assert!(has(
+            " | `[0]` | `ready` | read-only | `true` | Set once the device is ready. |"
+        ));
+
+        assert!(has(" Bit `[0]`, reset value `true`, read-only."));
+        assert!(has(
+            " Bits `[15:8]`, 8 bits wide, reset value `0x80`, write-once."
+        ));
+        assert!(has(
+            " Bits `[7:4]`, 4 bits wide, reset value `0x5`, read-only, fixed."
+        ));
+    }
+
+    #[test]
+    fn unused_bits_and_split_fields() {
+        let docs = docs(quote! {
+            struct Insn {
+                op: u4,
+                imm: u7 = [15..12, 7..6] << 1,
+            }
+        });
+        let has = |line: &str| docs.iter().any(|doc| doc == line);
+
+        assert!(has(" | `[11:8]` | | unused | | |"));
+        assert!(has(" | `[15:12]` | `imm` | read-write | `0x0` |  |"));
+        assert!(has(" | `[7:6]` | `imm` | read-write | `0x0` |  |"));
+        assert!(has(
+            " Bits `[15:12]`, `[7:6]` shifted left by 1, 7 bits wide, reset value `0x0`, read-write."
+        ));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::spec::generate::cast;
+use crate::spec::syntax::{Encoding, SpecField};
+
+use quote::quote;
+
+impl SpecField {
+    /// Accessors of the value held by an `#[encoding(..)]` field, converting to and from its raw
+    /// bits. The setters fail for values the field can't encode.
+    pub(super) fn encoded_accessors(
+        &self,
+        encoding: Encoding,
+        struct_container_ty: &proc_macro2::Ident,
+        initial: u128,
+    ) -> syn::Result<proc_macro2::TokenStream> {
+        let name = &self.name;
+        let name_str = proc_macro2::Literal::string(&self.name.to_string());
+        let set_name = self.prefixed_ident("set");
+        let fld = self.getter_ident();
+        let set_fld = self.setter_ident();
+        let fld_const_id_sz = self.const_ident("SIZE");
+        let container = struct_container_ty.to_string();
+        let max = u128::MAX >> (128 - self.size);
+
+        let (value_ty, getter, in_range, encode) = match encoding {
+            Encoding::Bcd => {
+                let max =
+                    proc_macro2::Literal::u128_unsuffixed(10u128.pow(self.size as u32 / 4) - 1);
+                let decoded = cast(
+                    quote! { ::dials::encoding::bcd_decode(u128::from(self.#fld())) },
+                    "u128",
+                    &container,
+                );
+                let encoded = cast(
+                    quote! { ::dials::encoding::bcd_encode(u128::from(value)) },
+                    "u128",
+                    &container,
+                );
+                (
+                    quote! { #struct_container_ty },
+                    decoded,
+                    quote! { value <= #max },
+                    encoded,
+                )
+            }
+            Encoding::Gray => {
+                let max = proc_macro2::Literal::u128_unsuffixed(max);
+                let decoded = cast(
+                    quote! { ::dials::encoding::gray_decode(u128::from(self.#fld())) },
+                    "u128",
+                    &container,
+                );
+                let encoded = cast(
+                    quote! { ::dials::encoding::gray_encode(u128::from(value)) },
+                    "u128",
+                    &container,
+                );
+                (
+                    quote! { #struct_container_ty },
+                    decoded,
+                    quote! { value <= #max },
+                    encoded,
+                )
+            }
+            Encoding::OneHot => (
+                quote! { u32 },
+                quote! { self.#fld().trailing_zeros() },
+                quote! { (value as usize) < Self::#fld_const_id_sz },
+                quote! { 1 << value },
+            ),
+            Encoding::Offset(offset) => {
+                let Some(hi) = offset.checked_add(max as i128) else {
+                    return Err(syn::Error::new(
+                        self.name.span(),
+                        format!("the values of field `{}` do not fit in an i128", self.name),
+                    ));
+                };
+                let value_ty = [8, 16, 32, 64, 128]
+                    .into_iter()
+                    .find(|bits| {
+                        *bits == 128 || (offset >= -(1 << (bits - 1)) && hi < 1 << (bits - 1))
+                    })
+                    .map(|bits| format!("i{bits}"))
+                    .unwrap_or_default();
+                let value_ty_ident = proc_macro2::Ident::new(&value_ty, self.name.span());
+                let lit_offset = proc_macro2::Literal::i128_unsuffixed(offset.abs());
+                let lit_hi = proc_macro2::Literal::i128_unsuffixed(hi.abs());
+                let signed = |lit: proc_macro2::Literal, negative: bool| {
+                    if negative {
+                        quote! { -#lit }
+                    } else {
+                        quote! { #lit }
+                    }
+                };
+                let (lo, hi) = (
+                    signed(lit_offset.clone(), offset < 0),
+                    signed(lit_hi, hi < 0),
+                );
+                let (add, sub) = if offset < 0 {
+                    (quote! { - }, quote! { + })
+                } else {
+                    (quote! { + }, quote! { - })
+                };
+                let decoded = cast(
+                    quote! { (self.#fld() as i128 #add #lit_offset) },
+                    "i128",
+                    &value_ty,
+                );
+                let encoded = cast(
+                    quote! { (i128::from(value) #sub #lit_offset) },
+                    "i128",
+                    &container,
+                );
+                (
+                    quote! { #value_ty_ident },
+                    decoded,
+                    quote! { (#lo..=#hi).contains(&value) },
+                    encoded,
+                )
+            }
+        };
+
+        let getter_docs = self.field_docs(
+            "The value encoded by the field. Raw values rejected by `validate` decode to meaningless values.",
+            initial,
+        );
+        let setter_docs = self.method_docs(
+            "Sets the field to `value`, failing if the field can't encode it.",
+            initial,
+        );
+        let setter = self.is_settable().then(|| {
+            quote! {
+                #setter_docs
+                pub fn #set_name(&mut self, value: #value_ty) -> ::core::result::Result<&mut Self, ::dials::RangeError> {
+                    if !(#in_range) {
+                        return Err(::dials::RangeError {
+                            field: #name_str,
+                            value: value.to_string(),
+                            bits: Self::#fld_const_id_sz,
+                        });
+                    }
+                    Ok(self.#set_fld(#encode))
+                }
+            }
+        });
+
+        Ok(quote! {
+            #getter_docs
+            pub fn #name(&self) -> #value_ty {
+                #getter
+            }
+            #setter
+        })
+    }
+}
@@ -0,0 +1,124 @@
+use crate::spec::generate::container_size_bits;
+use crate::spec::syntax::{Spec, SpecField};
+
+use quote::quote;
+
+impl Spec {
+    /// A plain `{Name}Fields` struct with one public member per field, convertible to and from
+    /// the packed spec.
+    pub(super) fn fields_struct(
+        &self,
+        struct_container_ty: &proc_macro2::Ident,
+    ) -> syn::Result<proc_macro2::TokenStream> {
+        let name = &self.name;
+        let initial = self.initial_value();
+        let fields_name = proc_macro2::Ident::new(&format!("{}Fields", name), name.span());
+        let fld = self.value_fields().map(|f| &f.name).collect::<Vec<_>>();
+        let docs = self.value_fields().map(|f| {
+            let doc = &f.doc;
+            quote! { #(#[doc = #doc])* }
+        });
+        let struct_doc = format!(" The fields of [`{name}`], unpacked.");
+        let tys = self
+            .value_fields()
+            .map(SpecField::unpacked_ty)
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        let unpack = self
+            .value_fields()
+            .zip(&tys)
+            .map(|(f, ty)| {
+                let fld = &f.name;
+                let get_fld = f.getter_ident();
+                if f.size == 1 || *ty == f.value_ty(struct_container_ty) {
+                    quote! { #fld: value.#get_fld() }
+                } else {
+                    quote! { #fld: value.#get_fld() as #ty }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let pack = self.value_fields().zip(&tys).map(|(f, ty)| {
+            let fld = &f.name;
+            let set_fld = f.setter_ident();
+            if f.size == 1 {
+                return quote! {
+                    if fields.#fld {
+                        out.#set_fld();
+                    }
+                };
+            }
+            let fld_str = proc_macro2::Literal::string(&f.name.to_string());
+            let fld_const_id_sz = f.const_ident("SIZE");
+            // Signed values fit when every bit above the field's sign bit is a copy of it.
+            let out_of_range = if f.signed {
+                quote! { !matches!(fields.#fld >> (Self::#fld_const_id_sz - 1), 0 | -1) }
+            } else {
+                quote! { fields.#fld >> Self::#fld_const_id_sz != 0 }
+            };
+            // Fields that exactly fill their primitive can't be out of range.
+            let check = (!matches!(f.size, 8 | 16 | 32 | 64 | 128)).then(|| {
+                quote! {
+                    if #out_of_range {
+                        return Err(::dials::FieldsError::OutOfRange(::dials::RangeError {
+                            field: #fld_str,
+                            value: fields.#fld.to_string(),
+                            bits: Self::#fld_const_id_sz,
+                        }));
+                    }
+                }
+            });
+            let value_ty = f.value_ty(struct_container_ty);
+            let value = if *ty == value_ty {
+                quote! { fields.#fld }
+            } else {
+                quote! { fields.#fld as #value_ty }
+            };
+            quote! {
+                #check
+                out.#set_fld(#value);
+            }
+        });
+
+        Ok(quote! {
+            #[doc = #struct_doc]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+            pub struct #fields_name {
+                #(#docs pub #fld: #tys,)*
+            }
+
+            impl ::core::convert::From<#name> for #fields_name {
+                fn from(value: #name) -> Self {
+                    Self {
+                        #(#unpack,)*
+                    }
+                }
+            }
+
+            impl ::core::convert::TryFrom<#fields_name> for #name {
+                type Error = ::dials::FieldsError;
+
+                fn try_from(fields: #fields_name) -> ::core::result::Result<Self, Self::Error> {
+                    let mut out = #initial;
+                    #(#pack)*
+                    out.validate()?;
+                    Ok(out)
+                }
+            }
+        })
+    }
+}
+
+impl SpecField {
+    /// The smallest primitive that holds the field, `bool` for single bits.
+    fn unpacked_ty(&self) -> syn::Result<proc_macro2::Ident> {
+        if self.size == 1 {
+            return Ok(proc_macro2::Ident::new("bool", self.name.span()));
+        }
+        let bits = container_size_bits(self.name.span(), self.size)?;
+        Ok(proc_macro2::Ident::new(
+            &format!("{}{}", if self.signed { 'i' } else { 'u' }, bits),
+            self.name.span(),
+        ))
+    }
+}
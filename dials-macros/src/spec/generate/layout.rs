@@ -0,0 +1,139 @@
+use crate::spec::syntax::{Access, Encoding, Spec, SpecField};
+
+use quote::quote;
+
+impl Spec {
+    /// `LAYOUT`, the description of the spec that `dials::export` writes out for other languages.
+    pub(super) fn layout_const(
+        &self,
+        struct_total_bits: usize,
+        initial: u128,
+    ) -> proc_macro2::TokenStream {
+        let name = self.name.to_string();
+        let doc = doc_string(&self.doc);
+        let reset = proc_macro2::Literal::u128_unsuffixed(initial);
+        let fields = self.fields.iter().map(SpecField::layout);
+        let summary = format!(" The layout of [`{name}`], for exporting it with `dials::export`.");
+        let variants = match &self.variants {
+            Some(variants) => {
+                let tag = variants.tag.to_string();
+                let variants = variants.variants.iter().map(|v| {
+                    let view =
+                        proc_macro2::Ident::new(&format!("{}{}", self.name, v.name), v.name.span());
+                    let variant_name = v.name.to_string();
+                    let tag_value = proc_macro2::Literal::u128_unsuffixed(v.tag_value);
+                    quote! {
+                        ::dials::layout::Variant {
+                            name: #variant_name,
+                            tag_value: #tag_value,
+                            layout: &#view::LAYOUT,
+                        }
+                    }
+                });
+                quote! {
+                    ::core::option::Option::Some(::dials::layout::Variants {
+                        tag: #tag,
+                        variants: &[#(#variants),*],
+                    })
+                }
+            }
+            None => quote! { ::core::option::Option::None },
+        };
+        quote! {
+            #[doc = #summary]
+            pub const LAYOUT: ::dials::layout::Layout = ::dials::layout::Layout {
+                name: #name,
+                doc: #doc,
+                bits: #struct_total_bits,
+                reset: #reset,
+                fields: &[#(#fields),*],
+                variants: #variants,
+            };
+        }
+    }
+}
+
+impl SpecField {
+    fn layout(&self) -> proc_macro2::TokenStream {
+        let name = self.name.to_string();
+        let doc = doc_string(&self.doc);
+        let start = self.start;
+        let size = self.size;
+        let bits = self.bit_ranges().into_iter().map(|r| {
+            let (start, size) = (r.start, r.size);
+            quote! { ::dials::layout::BitRange { start: #start, size: #size } }
+        });
+        let shift = self.split.as_ref().map_or(0, |split| split.shift);
+        let signed = self.signed;
+        let alias = self.alias;
+
+        let kind = if let Some(fixed) = self.fixed {
+            let fixed = proc_macro2::Literal::u128_unsuffixed(fixed);
+            quote! { Fixed(#fixed) }
+        } else if let Some(reserved) = self.reserved {
+            let reserved = proc_macro2::Literal::u128_unsuffixed(reserved);
+            quote! { Reserved(#reserved) }
+        } else if let Some(check) = &self.check {
+            let over = check.over().iter().map(|f| f.to_string());
+            quote! { Check { over: &[#(#over),*] } }
+        } else if let Some(enum_ty) = &self.enum_ty {
+            let enum_name = enum_ty.to_string().replace(' ', "");
+            quote! {
+                Enum {
+                    name: #enum_name,
+                    variants: <#enum_ty as ::dials::FieldEnum>::VARIANTS,
+                }
+            }
+        } else if let Some(scale) = self.scale {
+            let factor = proc_macro2::Literal::f64_suffixed(scale.factor);
+            let offset = proc_macro2::Literal::f64_suffixed(scale.offset);
+            quote! { Scaled { factor: #factor, offset: #offset } }
+        } else if let Some(encoding) = self.encoding {
+            let encoding = match encoding {
+                Encoding::Bcd => quote! { Bcd },
+                Encoding::Gray => quote! { Gray },
+                Encoding::OneHot => quote! { OneHot },
+                Encoding::Offset(offset) => {
+                    let offset = proc_macro2::Literal::i128_unsuffixed(offset);
+                    quote! { Offset(#offset) }
+                }
+            };
+            quote! { Encoded(::dials::layout::Encoding::#encoding) }
+        } else {
+            quote! { Int }
+        };
+
+        let access = match self.access {
+            Some(Access::ReadOnly) => quote! { ReadOnly },
+            Some(Access::WriteOnly) => quote! { WriteOnly },
+            Some(Access::ReadWrite) => quote! { ReadWrite },
+            Some(Access::WriteOnce) => quote! { WriteOnce },
+            Some(Access::ReadWriteOnce) => quote! { ReadWriteOnce },
+            None if self.fixed.is_some() || self.check.is_some() => quote! { ReadOnly },
+            None => quote! { ReadWrite },
+        };
+
+        quote! {
+            ::dials::layout::FieldLayout {
+                name: #name,
+                doc: #doc,
+                start: #start,
+                size: #size,
+                bits: &[#(#bits),*],
+                shift: #shift,
+                signed: #signed,
+                alias: #alias,
+                kind: ::dials::layout::FieldKind::#kind,
+                access: ::dials::layout::Access::#access,
+            }
+        }
+    }
+}
+
+/// Doc comment lines as a single string, without the space rustdoc strips from each.
+fn doc_string(doc: &[String]) -> String {
+    doc.iter()
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,78 @@
+use crate::spec::syntax::{Spec, SpecField};
+
+use quote::quote;
+
+impl Spec {
+    /// One hidden `const fn` per field giving the `(mask, value)` pair for a value of it, which
+    /// `pat!` combines into a pattern for the whole spec.
+    pub(super) fn pattern_fns(
+        &self,
+        struct_total_bits: usize,
+        struct_container_ty: &proc_macro2::Ident,
+    ) -> proc_macro2::TokenStream {
+        let fns = self
+            .fields
+            .iter()
+            .map(|f| f.pattern_fn(struct_total_bits, struct_container_ty));
+        quote! {
+            #(#fns)*
+        }
+    }
+}
+
+impl SpecField {
+    fn pattern_fn(
+        &self,
+        struct_total_bits: usize,
+        struct_container_ty: &proc_macro2::Ident,
+    ) -> proc_macro2::TokenStream {
+        let pat_fld = self.prefixed_ident("__pat");
+        let fld_const_id = self.const_ident("");
+
+        if self.size == 1 {
+            return quote! {
+                #[doc(hidden)]
+                pub const fn #pat_fld(value: bool) -> (#struct_container_ty, #struct_container_ty) {
+                    (Self::#fld_const_id, if value { Self::#fld_const_id } else { 0 })
+                }
+            };
+        }
+
+        let fld_const_id_sz = self.const_ident("SIZE");
+        let value_ty = self.value_ty(struct_container_ty);
+        let lit_struct_total_bits = proc_macro2::Literal::usize_unsuffixed(struct_total_bits);
+        let out_of_range = format!(
+            "pattern value does not fit in {}-bit field `{}`",
+            self.size, self.name
+        );
+        let check = if self.signed {
+            Some(quote! { !matches!(value >> (Self::#fld_const_id_sz - 1), 0 | -1) })
+        } else {
+            // Fields as wide as the container can't be out of range.
+            (self.size < struct_total_bits).then(|| quote! { value >> Self::#fld_const_id_sz != 0 })
+        }
+        .map(|out_of_range_check| {
+            quote! {
+                if #out_of_range_check {
+                    panic!(#out_of_range);
+                }
+            }
+        });
+        let wrap = self.signed.then(|| {
+            quote! {
+                let value = value as #struct_container_ty
+                    & (Self::ALL_ONES >> (#lit_struct_total_bits - Self::#fld_const_id_sz));
+            }
+        });
+        let placed = self.place_tokens();
+
+        quote! {
+            #[doc(hidden)]
+            pub const fn #pat_fld(value: #value_ty) -> (#struct_container_ty, #struct_container_ty) {
+                #check
+                #wrap
+                (Self::#fld_const_id, #placed)
+            }
+        }
+    }
+}
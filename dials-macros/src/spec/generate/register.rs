@@ -0,0 +1,70 @@
+use crate::spec::generate::cast;
+use crate::spec::syntax::Spec;
+
+use quote::quote;
+
+impl Spec {
+    /// `Register`, so that the spec can be held by a register of a `register_block!`.
+    pub(super) fn register_impl(
+        &self,
+        struct_container_ty: &proc_macro2::Ident,
+    ) -> proc_macro2::TokenStream {
+        let name = &self.name;
+        let container = struct_container_ty.to_string();
+        let from_raw = cast(quote! { raw }, "u128", &container);
+        let fields = self
+            .fields
+            .iter()
+            .filter(|f| !f.alias)
+            .map(|f| {
+                let fld = f.getter_ident();
+                let fld_str = proc_macro2::Literal::string(&f.name.to_string());
+                quote! { (#fld_str, ::dials::FieldValue::from(value.#fld())) }
+            })
+            .collect::<Vec<_>>();
+        // A tagged value lists the fields of the variant its tag selects.
+        let variant_fields = self.variants.is_some().then(|| {
+            let enum_name = self.variant_enum_ident();
+            let arms = self.variant_views(None).into_iter().map(|(variant, view)| {
+                let variant_name = &variant.name;
+                let view_name = &view.name;
+                quote! {
+                    #enum_name::#variant_name(_) => {
+                        <#view_name as ::dials::bus::Register>::fields(raw)
+                    }
+                }
+            });
+            quote! {
+                if let Some(variant) = value.decode() {
+                    return match variant {
+                        #(#arms)*
+                    };
+                }
+            }
+        });
+        // Specs without fields keep the default, which has none.
+        let fields = (!fields.is_empty()).then(|| {
+            quote! {
+                fn fields(raw: u128) -> ::std::vec::Vec<(&'static str, ::dials::FieldValue)> {
+                    let value = Self(#from_raw);
+                    #variant_fields
+                    ::std::vec![#(#fields),*]
+                }
+            }
+        });
+
+        quote! {
+            impl ::dials::bus::Register for #name {
+                fn from_raw(raw: u128) -> Self {
+                    Self(#from_raw)
+                }
+
+                fn to_raw(&self) -> u128 {
+                    u128::from(self.0)
+                }
+
+                #fields
+            }
+        }
+    }
+}
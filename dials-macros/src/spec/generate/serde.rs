@@ -0,0 +1,258 @@
+use crate::spec::syntax::{SerdeRepr, Spec, SpecField};
+
+use quote::quote;
+
+impl Spec {
+    /// `Serialize` and `Deserialize` implementations, only emitted when `dials` is built with
+    /// its `serde` feature. See `dials::serde_support` for the representations.
+    pub(super) fn serde_impls(
+        &self,
+        struct_container_ty: &proc_macro2::Ident,
+    ) -> proc_macro2::TokenStream {
+        let name = &self.name;
+        let initial = self.initial_value();
+        let name_str = proc_macro2::Literal::string(&name.to_string());
+        let field_count = self.value_fields().count();
+        let field_strs = self
+            .value_fields()
+            .map(|f| proc_macro2::Literal::string(&f.name.to_string()))
+            .collect::<Vec<_>>();
+        let lib = quote! { ::dials::serde_support::lib };
+
+        // A tagged value is written as its tag followed by the fields of its variant.
+        let serialize_variant = self.tag_field().map(|tag| {
+            let enum_name = self.variant_enum_ident();
+            let tag_str = proc_macro2::Literal::string(&tag.name.to_string());
+            let tag_fld = tag.getter_ident();
+            let arms = self.variant_views(None).into_iter().map(|(variant, view)| {
+                let variant_name = &variant.name;
+                let field_count = 1 + view.value_fields().count();
+                let field_strs = view
+                    .value_fields()
+                    .map(|f| proc_macro2::Literal::string(&f.name.to_string()));
+                let getters = view.value_fields().map(SpecField::getter_ident);
+                quote! {
+                    #enum_name::#variant_name(view) => {
+                        let mut state = serializer.serialize_struct(#name_str, #field_count)?;
+                        state.serialize_field(#tag_str, &self.#tag_fld())?;
+                        #(state.serialize_field(#field_strs, &view.#getters())?;)*
+                        state.end()
+                    }
+                }
+            });
+            quote! {
+                if let Some(variant) = self.decode() {
+                    return match variant {
+                        #(#arms)*
+                    };
+                }
+            }
+        });
+        let serialize_fields = {
+            let getters = self.value_fields().map(SpecField::getter_ident);
+            quote! {
+                use #lib::ser::SerializeStruct;
+                #serialize_variant
+                let mut state = serializer.serialize_struct(#name_str, #field_count)?;
+                #(state.serialize_field(#field_strs, &self.#getters())?;)*
+                state.end()
+            }
+        };
+        let serialize_raw = quote! {
+            #lib::Serialize::serialize(&self.0, serializer)
+        };
+
+        // Reads the value of field `f` with `next`, range checks it and stores it in `out`.
+        let read_field =
+            |f: &SpecField, next: &dyn Fn(proc_macro2::TokenStream) -> proc_macro2::TokenStream| {
+                let fld_str = proc_macro2::Literal::string(&f.name.to_string());
+                let set_fld = f.setter_ident();
+                if f.size == 1 {
+                    let value = next(quote! { bool });
+                    quote! {
+                        if #value {
+                            out.#set_fld();
+                        }
+                    }
+                } else {
+                    let value_ty = f.value_ty(struct_container_ty);
+                    let value = next(quote! { #value_ty });
+                    let fld_const_id_sz = f.const_ident("SIZE");
+                    let (check_range, wide_ty) = if f.signed {
+                        (quote! { check_range_signed }, quote! { i128 })
+                    } else {
+                        (quote! { check_range }, quote! { u128 })
+                    };
+                    quote! {
+                        let value = #value;
+                        ::dials::serde_support::#check_range(
+                            #fld_str,
+                            value as #wide_ty,
+                            #name::#fld_const_id_sz,
+                        )?;
+                        out.#set_fld(value);
+                    }
+                }
+            };
+        let map_fields = self
+            .value_fields()
+            .map(|f| read_field(f, &|ty| quote! { map.next_value::<#ty>()? }))
+            .collect::<Vec<_>>();
+        let seq_fields = self.value_fields().enumerate().map(|(idx, f)| {
+            read_field(f, &|ty| {
+                quote! {
+                    seq.next_element::<#ty>()?
+                        .ok_or_else(|| A::Error::invalid_length(#idx, &self))?
+                }
+            })
+        });
+        let indices = 0..field_count;
+
+        let deserialize_fields = quote! {
+            struct FieldsVisitor;
+
+            impl<'de> #lib::de::Visitor<'de> for FieldsVisitor {
+                type Value = #name;
+
+                fn expecting(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    f.write_str(concat!("struct ", #name_str))
+                }
+
+                fn visit_map<A: #lib::de::MapAccess<'de>>(
+                    self,
+                    mut map: A,
+                ) -> ::core::result::Result<#name, A::Error> {
+                    use #lib::de::Error;
+                    let mut out = #initial;
+                    let mut seen = [false; #field_count];
+                    while let Some(key) = map.next_key::<::std::string::String>()? {
+                        match key.as_str() {
+                            #(
+                                #field_strs => {
+                                    if ::core::mem::replace(&mut seen[#indices], true) {
+                                        return Err(A::Error::duplicate_field(#field_strs));
+                                    }
+                                    #map_fields
+                                }
+                            )*
+                            _ => return Err(A::Error::unknown_field(&key, FIELDS)),
+                        }
+                    }
+                    for (seen, field) in seen.iter().zip(FIELDS) {
+                        if !seen {
+                            return Err(A::Error::missing_field(field));
+                        }
+                    }
+                    out.validate().map_err(A::Error::custom)?;
+                    Ok(out)
+                }
+
+                fn visit_seq<A: #lib::de::SeqAccess<'de>>(
+                    self,
+                    mut seq: A,
+                ) -> ::core::result::Result<#name, A::Error> {
+                    use #lib::de::Error;
+                    let mut out = #initial;
+                    #(#seq_fields)*
+                    out.validate().map_err(A::Error::custom)?;
+                    Ok(out)
+                }
+            }
+
+            const FIELDS: &[&str] = &[#(#field_strs),*];
+            #lib::Deserializer::deserialize_struct(deserializer, #name_str, FIELDS, FieldsVisitor)
+        };
+        // The fields of a tagged value are read ahead, and handed to the view of the variant
+        // that the tag selects. Unknown tags fall back to the common fields.
+        let deserialize_fields = match self.tag_field() {
+            Some(tag) => {
+                let enum_name = self.variant_enum_ident();
+                let tag_str = proc_macro2::Literal::string(&tag.name.to_string());
+                let read_tag = read_field(tag, &|ty| {
+                    quote! {
+                        <#ty as #lib::Deserialize>::deserialize(
+                            #lib::de::IntoDeserializer::<D::Error>::into_deserializer(value),
+                        )?
+                    }
+                });
+                let arms = self.variant_views(None).into_iter().map(|(variant, view)| {
+                    let variant_name = &variant.name;
+                    let view_name = &view.name;
+                    quote! {
+                        Some(#enum_name::#variant_name(_)) => {
+                            let view: #view_name = #lib::Deserialize::deserialize(
+                                fields.without(#tag_str).into_deserializer::<D::Error>(),
+                            )?;
+                            return Ok(#name::from(view));
+                        }
+                    }
+                });
+                quote! {
+                    let fields: ::dials::serde_support::TaggedFields =
+                        #lib::Deserialize::deserialize(deserializer)?;
+                    if let Some(value) = fields.tag(#tag_str) {
+                        let mut out = #initial;
+                        #read_tag
+                        match out.decode() {
+                            #(#arms)*
+                            None => {}
+                        }
+                    }
+                    let deserializer = fields.into_deserializer::<D::Error>();
+                    #deserialize_fields
+                }
+            }
+            None => deserialize_fields,
+        };
+        let field_mask = self.field_mask();
+        let deserialize_raw = quote! {
+            let raw: #struct_container_ty = #lib::Deserialize::deserialize(deserializer)?;
+            ::dials::serde_support::check_unused(#name_str, raw as u128, #field_mask as u128)?;
+            let value = #name(raw);
+            value.validate().map_err(#lib::de::Error::custom)?;
+            Ok(value)
+        };
+
+        let (serialize, deserialize) = match self.serde {
+            SerdeRepr::Map => (serialize_fields, deserialize_fields),
+            SerdeRepr::Raw => (serialize_raw, deserialize_raw),
+            SerdeRepr::Auto => (
+                quote! {
+                    if serializer.is_human_readable() {
+                        #serialize_fields
+                    } else {
+                        #serialize_raw
+                    }
+                },
+                quote! {
+                    if deserializer.is_human_readable() {
+                        #deserialize_fields
+                    } else {
+                        #deserialize_raw
+                    }
+                },
+            ),
+        };
+
+        quote! {
+            ::dials::__with_serde! {
+                impl #lib::Serialize for #name {
+                    fn serialize<S: #lib::Serializer>(
+                        &self,
+                        serializer: S,
+                    ) -> ::core::result::Result<S::Ok, S::Error> {
+                        #serialize
+                    }
+                }
+
+                impl<'de> #lib::Deserialize<'de> for #name {
+                    fn deserialize<D: #lib::Deserializer<'de>>(
+                        deserializer: D,
+                    ) -> ::core::result::Result<Self, D::Error> {
+                        #deserialize
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::spec::syntax::{SpecConst, SpecEnum};
+
+use quote::{quote, ToTokens};
+
+impl SpecEnum {
+    /// The enum itself, and the `FieldEnum` implementation that lets fields be declared `as` it.
+    pub fn generate(&self, output: &mut proc_macro2::TokenStream) {
+        let name = &self.name;
+        let attrs = &self.attrs;
+        let variants = self.variants.iter().map(|v| {
+            let attrs = &v.attrs;
+            let name = &v.name;
+            let discriminant = proc_macro2::Literal::u128_unsuffixed(v.discriminant);
+            quote! { #(#[#attrs])* #name = #discriminant }
+        });
+        let names = self.variants.iter().map(|v| &v.name).collect::<Vec<_>>();
+        let discriminants = self
+            .variants
+            .iter()
+            .map(|v| proc_macro2::Literal::u128_unsuffixed(v.discriminant))
+            .collect::<Vec<_>>();
+        let name_strs = names.iter().map(|n| n.to_string());
+        // Without a `repr` the discriminants would have to fit in an `isize`. `parse_enum` keeps
+        // them within `u64`.
+        let max = self
+            .variants
+            .iter()
+            .map(|v| v.discriminant)
+            .max()
+            .unwrap_or(0);
+        let repr = proc_macro2::Ident::new(
+            match max {
+                0..=0xff => "u8",
+                0x100..=0xffff => "u16",
+                0x1_0000..=0xffff_ffff => "u32",
+                _ => "u64",
+            },
+            proc_macro2::Span::call_site(),
+        );
+
+        quote! {
+            #(#[#attrs])*
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            #[repr(#repr)]
+            pub enum #name {
+                #(#variants,)*
+            }
+
+            impl ::dials::FieldEnum for #name {
+                const VARIANTS: &'static [(&'static str, u128)] = &[
+                    #((#name_strs, #discriminants),)*
+                ];
+
+                fn from_bits(bits: u128) -> ::core::option::Option<Self> {
+                    match bits {
+                        #(#discriminants => Some(Self::#names),)*
+                        _ => None,
+                    }
+                }
+
+                fn to_bits(&self) -> u128 {
+                    match self {
+                        #(Self::#names => #discriminants,)*
+                    }
+                }
+            }
+        }
+        .to_tokens(output);
+    }
+}
+
+impl SpecConst {
+    pub fn generate(&self, output: &mut proc_macro2::TokenStream) {
+        let Self {
+            attrs,
+            name,
+            ty,
+            value,
+        } = self;
+        quote! {
+            #(#[#attrs])*
+            pub const #name: #ty = #value;
+        }
+        .to_tokens(output);
+    }
+}
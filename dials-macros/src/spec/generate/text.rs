@@ -0,0 +1,243 @@
+use crate::spec::syntax::{Spec, SpecField};
+
+use quote::quote;
+
+impl Spec {
+    /// `Display` and `FromStr` implementations, see `dials::text` for the formats.
+    pub(super) fn text_impls(
+        &self,
+        struct_container_ty: &proc_macro2::Ident,
+    ) -> proc_macro2::TokenStream {
+        if self.is_flag_set() {
+            self.flag_set_text_impls(struct_container_ty)
+        } else {
+            self.field_text_impls(struct_container_ty)
+        }
+    }
+
+    fn is_flag_set(&self) -> bool {
+        self.variants.is_none()
+            && self.value_fields().count() > 0
+            && self.value_fields().all(|f| f.size == 1)
+    }
+
+    fn field_text_impls(
+        &self,
+        struct_container_ty: &proc_macro2::Ident,
+    ) -> proc_macro2::TokenStream {
+        let name = &self.name;
+        let initial = self.initial_value();
+
+        let writes = self.value_fields().enumerate().map(|(idx, f)| {
+            let fld = f.getter_ident();
+            let format = proc_macro2::Literal::string(&format!(
+                "{}{}={{}}",
+                if idx == 0 { "" } else { "," },
+                f.name
+            ));
+            quote! { write!(f, #format, self.#fld())?; }
+        });
+
+        let field_count = self.value_fields().count();
+        // Parses `value` and stores it in field `f` of `out`.
+        let assign = |f: &SpecField| {
+            let fld_str = proc_macro2::Literal::string(&f.name.to_string());
+            if f.size == 1 {
+                let set_fld = f.prefixed_ident("set");
+                quote! {
+                    if ::dials::text::parse_bool(#fld_str, value)? {
+                        out.#set_fld();
+                    }
+                }
+            } else {
+                let set_fld = f.setter_ident();
+                let fld_const_id_sz = f.const_ident("SIZE");
+                let value_ty = f.value_ty(struct_container_ty);
+                let parse = if f.signed {
+                    quote! { ::dials::text::parse_sint }
+                } else {
+                    quote! { ::dials::text::parse_uint }
+                };
+                quote! {
+                    let value = #parse(#fld_str, value, Self::#fld_const_id_sz)?;
+                    out.#set_fld(value as #value_ty);
+                }
+            }
+        };
+        let arms = self.value_fields().enumerate().map(|(idx, f)| {
+            let fld_str = proc_macro2::Literal::string(&f.name.to_string());
+            let assign = assign(f);
+            quote! {
+                #fld_str => {
+                    ::dials::text::first_occurrence(&mut seen[#idx], #fld_str)?;
+                    #assign
+                }
+            }
+        });
+
+        // A tagged value is written as its tag followed by the fields of its variant, and read
+        // back by the view of the variant that the tag selects. Unknown tags fall back to the
+        // common fields.
+        let (write_variant, parse_variant) = match self.tag_field() {
+            Some(tag) => {
+                let enum_name = self.variant_enum_ident();
+                let tag_fld = tag.getter_ident();
+                let tag_str = proc_macro2::Literal::string(&tag.name.to_string());
+                let assign_tag = assign(tag);
+                let (writes, parses): (Vec<_>, Vec<_>) = self
+                    .variant_views(None)
+                    .into_iter()
+                    .map(|(variant, view)| {
+                        let variant_name = &variant.name;
+                        let view_name = &view.name;
+                        let write = if view.value_fields().count() == 0 {
+                            let format =
+                                proc_macro2::Literal::string(&format!("{}={{}}", tag.name));
+                            quote! { write!(f, #format, self.#tag_fld()) }
+                        } else {
+                            let format =
+                                proc_macro2::Literal::string(&format!("{}={{}},{{}}", tag.name));
+                            quote! { write!(f, #format, self.#tag_fld(), view) }
+                        };
+                        (
+                            quote! { #enum_name::#variant_name(view) => #write, },
+                            quote! {
+                                Some(#enum_name::#variant_name(_)) => {
+                                    return Ok(Self::from(rest.parse::<#view_name>()?));
+                                }
+                            },
+                        )
+                    })
+                    .unzip();
+                (
+                    quote! {
+                        if let Some(variant) = self.decode() {
+                            return match variant {
+                                #(#writes)*
+                            };
+                        }
+                    },
+                    quote! {
+                        if let (Some(value), rest) = ::dials::text::split_tag(s, #tag_str)? {
+                            let mut out = #initial;
+                            #assign_tag
+                            match out.decode() {
+                                #(#parses)*
+                                None => {}
+                            }
+                        }
+                    },
+                )
+            }
+            None => (quote! {}, quote! {}),
+        };
+
+        quote! {
+            impl ::core::fmt::Display for #name {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    #write_variant
+                    #(#writes)*
+                    Ok(())
+                }
+            }
+
+            impl ::core::str::FromStr for #name {
+                type Err = ::dials::ParseError;
+
+                fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                    #parse_variant
+                    let mut out = #initial;
+                    let mut seen = [false; #field_count];
+                    for pair in ::dials::text::pairs(s) {
+                        let (field, value) = pair?;
+                        match field {
+                            #(#arms)*
+                            _ => return Err(::dials::ParseError::UnknownField(field.to_string())),
+                        }
+                    }
+                    out.validate()?;
+                    Ok(out)
+                }
+            }
+        }
+    }
+
+    fn flag_set_text_impls(
+        &self,
+        struct_container_ty: &proc_macro2::Ident,
+    ) -> proc_macro2::TokenStream {
+        let name = &self.name;
+        let initial = self.initial_value();
+        let name_str = proc_macro2::Literal::string(&name.to_string());
+        let all_flags = self.field_mask();
+
+        let writes = self.value_fields().map(|f| {
+            let fld = &f.name;
+            let flag_str = proc_macro2::Literal::string(&f.const_ident("").to_string());
+            quote! {
+                if self.#fld() {
+                    if !first {
+                        f.write_str("|")?;
+                    }
+                    first = false;
+                    f.write_str(#flag_str)?;
+                }
+            }
+        });
+
+        let field_count = self.value_fields().count();
+        let arms = self.value_fields().enumerate().map(|(idx, f)| {
+            let set_fld = f.prefixed_ident("set");
+            let fld_str = proc_macro2::Literal::string(&f.name.to_string());
+            let flag_str = proc_macro2::Literal::string(&f.const_ident("").to_string());
+            quote! {
+                #flag_str | #fld_str => {
+                    ::dials::text::first_occurrence(&mut seen[#idx], #flag_str)?;
+                    out.#set_fld();
+                }
+            }
+        });
+
+        quote! {
+            impl ::core::fmt::Display for #name {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    let mut first = true;
+                    #(#writes)*
+                    let unnamed = self.0 & !#all_flags;
+                    if unnamed != 0 {
+                        if !first {
+                            f.write_str("|")?;
+                        }
+                        write!(f, "{:#x}", unnamed)?;
+                    }
+                    Ok(())
+                }
+            }
+
+            impl ::core::str::FromStr for #name {
+                type Err = ::dials::ParseError;
+
+                fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                    let mut out = #initial;
+                    let mut seen = [false; #field_count];
+                    for flag in ::dials::text::flags(s) {
+                        match flag {
+                            #(#arms)*
+                            _ if flag.starts_with(|c: char| c.is_ascii_digit()) => {
+                                let bits = ::dials::text::parse_uint(
+                                    #name_str,
+                                    flag,
+                                    #struct_container_ty::BITS as usize,
+                                )?;
+                                out.0 |= bits as #struct_container_ty;
+                            }
+                            _ => return Err(::dials::ParseError::UnknownField(flag.to_string())),
+                        }
+                    }
+                    out.validate()?;
+                    Ok(out)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::spec::syntax::{Encoding, Spec};
+
+use std::str::FromStr;
+
+use quote::quote;
+
+impl Spec {
+    /// `validate`, checking the fixed, reserved and enum fields of a raw value, and a
+    /// `TryFrom<uN>` that only accepts valid values. The tuple constructor stays unchecked.
+    pub(super) fn validate_impls(
+        &self,
+        struct_total_bits: usize,
+        struct_container_ty: &proc_macro2::Ident,
+    ) -> syn::Result<proc_macro2::TokenStream> {
+        let name = &self.name;
+        let checks = self
+            .fields
+            .iter()
+            .map(|f| {
+                let fld_str = proc_macro2::Literal::string(&f.name.to_string());
+                let fld_const_id = f.const_ident("");
+                let raw_value = f.raw_bits_tokens(struct_total_bits);
+                if let Some(expected) = f.fixed.or(f.reserved) {
+                    let lit_placed =
+                        proc_macro2::Literal::from_str(&format!("{:#x}", f.place(expected)))?;
+                    let lit_expected = proc_macro2::Literal::u128_unsuffixed(expected);
+                    return Ok(quote! {
+                        if self.0 & Self::#fld_const_id != #lit_placed {
+                            return Err(::dials::ValidationError::Mismatch {
+                                field: #fld_str,
+                                value: u128::from(#raw_value),
+                                expected: #lit_expected,
+                            });
+                        }
+                    });
+                }
+                if let Some(check) = &f.check {
+                    let expected = self.check_value(f, check, struct_total_bits)?;
+                    return Ok(quote! {
+                        let expected = #expected;
+                        let value = u128::from(#raw_value);
+                        if value != expected {
+                            return Err(::dials::ValidationError::Mismatch {
+                                field: #fld_str,
+                                value,
+                                expected,
+                            });
+                        }
+                    });
+                }
+                let (valid, error) = match (&f.enum_ty, f.encoding) {
+                    (Some(enum_ty), _) => (
+                        quote! { <#enum_ty as ::dials::FieldEnum>::from_bits(value).is_some() },
+                        quote! { InvalidDiscriminant },
+                    ),
+                    (_, Some(Encoding::Bcd)) => (
+                        quote! { ::dials::encoding::bcd_is_valid(value) },
+                        quote! { InvalidEncoding },
+                    ),
+                    (_, Some(Encoding::OneHot)) => (
+                        quote! { value.count_ones() == 1 },
+                        quote! { InvalidEncoding },
+                    ),
+                    _ => return Ok(quote! {}),
+                };
+                Ok(quote! {
+                    let value = u128::from(#raw_value);
+                    if !(#valid) {
+                        return Err(::dials::ValidationError::#error {
+                            field: #fld_str,
+                            value,
+                        });
+                    }
+                })
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        // A tagged value is also checked against the layout its tag selects.
+        let variant_checks = self.variants.is_some().then(|| {
+            let enum_name = self.variant_enum_ident();
+            let variant_names = self.variant_views(None).into_iter().map(|(v, _)| &v.name);
+            quote! {
+                if let Some(variant) = self.decode() {
+                    match variant {
+                        #(#enum_name::#variant_names(view) => view.validate()?,)*
+                    }
+                }
+            }
+        });
+
+        Ok(quote! {
+            impl #name {
+                /// Checks that fixed, reserved and check fields hold their required values, and that
+                /// enum and encoded fields hold bits their type can produce.
+                pub fn validate(&self) -> ::core::result::Result<(), ::dials::ValidationError> {
+                    #(#checks)*
+                    #variant_checks
+                    Ok(())
+                }
+            }
+
+            impl ::core::convert::TryFrom<#struct_container_ty> for #name {
+                type Error = ::dials::ValidationError;
+
+                fn try_from(raw: #struct_container_ty) -> ::core::result::Result<Self, Self::Error> {
+                    let value = Self(raw);
+                    value.validate()?;
+                    Ok(value)
+                }
+            }
+        })
+    }
+}
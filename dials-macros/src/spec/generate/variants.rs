@@ -0,0 +1,129 @@
+use crate::spec::syntax::{Spec, SpecVariant};
+
+use quote::quote;
+
+impl Spec {
+    /// For a spec with a `match` on a tag field, a view spec per variant sharing the container
+    /// of the parent, a `{Name}Variant` enum of those views and `decode()` to pick one.
+    pub(super) fn variant_impls(
+        &self,
+        struct_total_bits: usize,
+    ) -> syn::Result<proc_macro2::TokenStream> {
+        let mut output = proc_macro2::TokenStream::new();
+        let Some(variants) = &self.variants else {
+            return Ok(output);
+        };
+        let name = &self.name;
+        let enum_name = self.variant_enum_ident();
+        let tag_const_id = proc_macro2::Ident::new(
+            &variants.tag.to_string().to_uppercase(),
+            variants.tag.span(),
+        );
+        let tag_const_id_st = proc_macro2::Ident::new(
+            &format!("{}_START", variants.tag.to_string().to_uppercase()),
+            variants.tag.span(),
+        );
+
+        let mut arms = vec![];
+        for (variant, view_spec) in self.variant_views(Some(struct_total_bits)) {
+            let view = view_spec.name.clone();
+            view_spec.generate_dials_impl(&mut output)?;
+
+            let variant_name = &variant.name;
+            let tag_value = proc_macro2::Literal::u128_unsuffixed(variant.tag_value);
+            arms.push(quote! { #tag_value => Some(#enum_name::#variant_name(#view(self.0))), });
+            output.extend(quote! {
+                impl ::core::convert::From<#view> for #name {
+                    fn from(value: #view) -> Self {
+                        Self(value.0)
+                    }
+                }
+            });
+        }
+
+        let variant_names = variants
+            .variants
+            .iter()
+            .map(|v| &v.name)
+            .collect::<Vec<_>>();
+        let views = variants.variants.iter().map(|v| self.view_ident(v));
+
+        output.extend(quote! {
+            pub enum #enum_name {
+                #(#variant_names(#views),)*
+            }
+
+            impl #name {
+                /// The view of this value selected by its tag, if the tag is a known variant.
+                pub fn decode(&self) -> ::core::option::Option<#enum_name> {
+                    match (self.0 & Self::#tag_const_id) >> Self::#tag_const_id_st {
+                        #(#arms)*
+                        _ => None,
+                    }
+                }
+            }
+
+            impl ::core::convert::From<#enum_name> for #name {
+                fn from(value: #enum_name) -> Self {
+                    match value {
+                        #(#enum_name::#variant_names(view) => Self(view.0),)*
+                    }
+                }
+            }
+        });
+
+        Ok(output)
+    }
+
+    /// The `{Name}Variant` enum of the views of a tagged spec.
+    pub(super) fn variant_enum_ident(&self) -> proc_macro2::Ident {
+        proc_macro2::Ident::new(&format!("{}Variant", self.name), self.name.span())
+    }
+
+    /// The name of the view of `variant`, `{Name}{Variant}`.
+    pub(super) fn view_ident(&self, variant: &SpecVariant) -> proc_macro2::Ident {
+        proc_macro2::Ident::new(
+            &format!("{}{}", self.name, variant.name),
+            variant.name.span(),
+        )
+    }
+
+    /// The variants of a tagged spec with the spec of their view, in which the tag is fixed to
+    /// the value selecting the variant, which gives views `new()` and `matches()`. Views of
+    /// `bits` bits share the container of their parent. Empty for untagged specs.
+    pub(super) fn variant_views(&self, bits: Option<usize>) -> Vec<(&SpecVariant, Spec)> {
+        let Some(variants) = &self.variants else {
+            return vec![];
+        };
+        let name = &self.name;
+        variants
+            .variants
+            .iter()
+            .map(|variant| {
+                let mut fields = variant.fields.clone();
+                for f in fields.iter_mut().filter(|f| f.name == variants.tag) {
+                    f.fixed = Some(variant.tag_value);
+                }
+                let view = Spec {
+                    name: self.view_ident(variant),
+                    fields,
+                    serde: self.serde,
+                    bits,
+                    variants: None,
+                    reset: self.reset,
+                    doc: variant
+                        .doc
+                        .iter()
+                        .cloned()
+                        .chain((!variant.doc.is_empty()).then(String::new))
+                        .chain([format!(
+                            " The `{}` variant of [`{name}`], selected by `{} == {}`.",
+                            variant.name, variants.tag, variant.tag_value
+                        )])
+                        .collect(),
+                };
+                (variant, view)
+            })
+            .collect()
+    }
+}
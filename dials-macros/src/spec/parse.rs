@@ -1,15 +1,148 @@
-use crate::spec::syntax::{Spec, SpecField};
+use crate::spec::syntax::{
+    Access, BitRange, Check, Encoding, Scale, SerdeRepr, Spec, SpecConst, SpecEnum,
+    SpecEnumVariant, SpecField, SpecItems, SpecVariant, SpecVariants, SplitBits,
+};
 
-use proc_macro2::{Span, TokenTree};
+use std::rc::Rc;
 
-pub struct SpecParser(pub proc_macro2::token_stream::IntoIter);
+use proc_macro2::{Delimiter, Spacing, Span, TokenTree};
+
+/// A cursor over the tokens of a declaration, along with the integer constants of the `spec!`
+/// block that may stand in for integers within it.
+pub struct SpecParser(
+    pub proc_macro2::token_stream::IntoIter,
+    Rc<Vec<(proc_macro2::Ident, u128)>>,
+);
 
 impl SpecParser {
     pub fn new(input: proc_macro2::TokenStream) -> Self {
-        Self(input.into_iter())
+        Self(input.into_iter(), Rc::default())
+    }
+
+    /// A parser of `input`, nested within this one and sharing its constants.
+    fn nested(&self, input: proc_macro2::TokenStream) -> Self {
+        Self(input.into_iter(), self.1.clone())
     }
 
-    pub fn parse(&mut self) -> syn::Result<Spec> {
+    /// Parses the whole of a `spec!` block, structs, enums and constants in any order.
+    pub fn parse_items(&mut self) -> syn::Result<SpecItems> {
+        self.1 = Rc::new(integer_consts(self.0.clone().collect()));
+
+        let mut items = SpecItems {
+            specs: vec![],
+            enums: vec![],
+            consts: vec![],
+        };
+        loop {
+            let mut attrs = vec![];
+            while let Some(attr) = self.parse_attribute()? {
+                attrs.push(attr);
+            }
+            match self.peek1() {
+                Some(TokenTree::Ident(kw)) if kw == "struct" => {
+                    items.specs.push(self.parse_struct(attrs)?)
+                }
+                Some(TokenTree::Ident(kw)) if kw == "enum" => {
+                    items.enums.push(self.parse_enum(attrs)?)
+                }
+                Some(TokenTree::Ident(kw)) if kw == "const" => {
+                    items.consts.push(self.parse_const(attrs)?)
+                }
+                None if attrs.is_empty() => break,
+                t => {
+                    return Err(syn::Error::new(
+                        t.map(|t| t.span()).unwrap_or(Span::call_site()),
+                        "expected a `struct`, `enum` or `const` declaration",
+                    ))
+                }
+            }
+        }
+
+        for spec in &items.specs {
+            let fields = spec.fields.iter().chain(
+                spec.variants
+                    .iter()
+                    .flat_map(|v| &v.variants)
+                    .flat_map(|v| &v.fields),
+            );
+            for f in fields {
+                let Some(e) = items.enums.iter().find(|e| {
+                    f.enum_ty.as_ref().map(|ty| ty.to_string()) == Some(e.name.to_string())
+                }) else {
+                    continue;
+                };
+                if let Some(v) = e
+                    .variants
+                    .iter()
+                    .find(|v| f.size < 128 && v.discriminant >> f.size != 0)
+                {
+                    return Err(syn::Error::new(
+                        f.name.span(),
+                        format!(
+                            "variant `{}::{}` = {} does not fit in {}-bit field `{}`",
+                            e.name, v.name, v.discriminant, f.size, f.name
+                        ),
+                    ));
+                }
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// Parses `struct Name { fields.. }`, whose attributes have already been read.
+    fn parse_struct(&mut self, attrs: Vec<proc_macro2::TokenStream>) -> syn::Result<Spec> {
+        let mut serde = SerdeRepr::Auto;
+        let mut doc = vec![];
+        let mut reset = None;
+        let mut c_bitfield = false;
+        for attr in attrs {
+            if let Some(line) = doc_comment(&attr) {
+                doc.push(line);
+                continue;
+            }
+            let a = SpecParser::new(attr.clone());
+            match a.peek2() {
+                (Some(TokenTree::Ident(id)), Some(TokenTree::Group(args)))
+                    if id == "serde" && args.delimiter() == Delimiter::Parenthesis =>
+                {
+                    serde = match &args.stream().into_iter().collect::<Vec<_>>()[..] {
+                        [TokenTree::Ident(repr)] if repr == "map" => SerdeRepr::Map,
+                        [TokenTree::Ident(repr)] if repr == "raw" => SerdeRepr::Raw,
+                        _ => {
+                            return Err(syn::Error::new(
+                                args.span(),
+                                "expected `#[serde(map)]` or `#[serde(raw)]`",
+                            ))
+                        }
+                    };
+                }
+                (Some(TokenTree::Ident(id)), Some(TokenTree::Group(args)))
+                    if id == "reset" && args.delimiter() == Delimiter::Parenthesis =>
+                {
+                    let mut a = self.nested(args.stream());
+                    reset = Some(a.parse_int::<u128>()?);
+                    if let Some(t) = a.peek1() {
+                        return Err(syn::Error::new(t.span(), "expected `#[reset(value)]`"));
+                    }
+                }
+                (Some(TokenTree::Ident(id)), Some(TokenTree::Group(args)))
+                    if id == "repr" && args.delimiter() == Delimiter::Parenthesis =>
+                {
+                    if args.stream().to_string() != "C_bitfield" {
+                        return Err(syn::Error::new(
+                            args.span(),
+                            "expected `#[repr(C_bitfield)]`, specs are always `#[repr(transparent)]`",
+                        ));
+                    }
+                    c_bitfield = true;
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(attr, "unknown spec attribute"));
+                }
+            }
+        }
+
         let name = match self.peek2() {
             (Some(TokenTree::Ident(s)), Some(TokenTree::Ident(name))) if s == "struct" => {
                 self.advance2();
@@ -23,93 +156,1147 @@ impl SpecParser {
             }
         };
 
-        let mut g = SpecParser(match self.advance1() {
-            Some(TokenTree::Group(g)) => g.stream().into_iter(),
+        let mut g = match self.advance1() {
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => {
+                self.nested(g.stream())
+            }
             _ => {
                 return Err(syn::Error::new(
-                    name.span().into(),
+                    name.span(),
                     "expected braced declaration of struct fields",
                 ))
             }
-        });
+        };
         let mut fields = vec![];
-        let mut start: usize = 0;
+        let start = g.parse_fields(&mut fields, 0, c_bitfield)?;
+        let variants = match g.peek1() {
+            Some(TokenTree::Ident(kw)) if kw == "match" && c_bitfield => {
+                return Err(syn::Error::new(
+                    kw.span(),
+                    "`#[repr(C_bitfield)]` specs cannot have tagged variants",
+                ));
+            }
+            Some(TokenTree::Ident(kw)) if kw == "match" => {
+                g.advance1();
+                Some(g.parse_variants(&fields, start)?)
+            }
+            _ => None,
+        };
+        if let Some(t) = g.peek1() {
+            return Err(syn::Error::new(
+                t.span(),
+                "expected end of struct declaration after `match`",
+            ));
+        }
+
+        let bits = match c_bitfield {
+            true => Some(c_bitfield_size(&name, &fields, start)?),
+            false => None,
+        };
+
+        Ok(Spec {
+            name,
+            fields,
+            serde,
+            bits,
+            variants,
+            doc,
+            reset,
+        })
+    }
+
+    /// Parses `enum Name { Variant = 0, .. }`, whose attributes have already been read.
+    /// Variants without a discriminant take the one after that of the previous variant.
+    fn parse_enum(&mut self, attrs: Vec<proc_macro2::TokenStream>) -> syn::Result<SpecEnum> {
+        let (kw, name, body) = match (self.advance1(), self.advance2()) {
+            (Some(kw), (Some(TokenTree::Ident(name)), Some(TokenTree::Group(body))))
+                if body.delimiter() == Delimiter::Brace =>
+            {
+                (kw, name, body)
+            }
+            (kw, _) => {
+                return Err(syn::Error::new(
+                    kw.map(|t| t.span()).unwrap_or(Span::call_site()),
+                    "expected an enum declaration of the form `enum Name { Variant = 0, .. }`",
+                ))
+            }
+        };
+        let mut b = self.nested(body.stream());
+        let mut variants: Vec<SpecEnumVariant> = vec![];
+        loop {
+            let mut attrs = vec![];
+            while let Some(attr) = b.parse_attribute()? {
+                attrs.push(attr);
+            }
+            let variant = match b.advance1() {
+                Some(TokenTree::Ident(variant)) => variant,
+                None if attrs.is_empty() => break,
+                t => {
+                    return Err(syn::Error::new(
+                        t.map(|t| t.span()).unwrap_or(body.span()),
+                        "expected an enum variant",
+                    ))
+                }
+            };
+            let discriminant = match b.peek1() {
+                Some(TokenTree::Punct(p)) if p.as_char() == '=' => {
+                    b.advance1();
+                    b.parse_int::<u128>()?
+                }
+                _ => match variants.last() {
+                    Some(prev) => prev.discriminant.checked_add(1).ok_or_else(|| {
+                        syn::Error::new(variant.span(), "enum discriminant overflowed")
+                    })?,
+                    None => 0,
+                },
+            };
+            if discriminant > u128::from(u64::MAX) {
+                return Err(syn::Error::new(
+                    variant.span(),
+                    format!("the discriminant of `{variant}` does not fit in a u64"),
+                ));
+            }
+            if let Some(other) = variants.iter().find(|v| v.discriminant == discriminant) {
+                return Err(syn::Error::new(
+                    variant.span(),
+                    format!(
+                        "variant `{variant}` has the same discriminant as `{}`",
+                        other.name
+                    ),
+                ));
+            }
+            variants.push(SpecEnumVariant {
+                attrs,
+                name: variant,
+                discriminant,
+            });
+            match b.advance1() {
+                Some(TokenTree::Punct(p)) if p.as_char() == ',' => {}
+                None => break,
+                Some(t) => return Err(syn::Error::new(t.span(), "expected ',' or end of enum")),
+            }
+        }
+        if variants.is_empty() {
+            return Err(syn::Error::new(
+                kw.span(),
+                format!("enum `{name}` must have at least one variant"),
+            ));
+        }
+        Ok(SpecEnum {
+            attrs,
+            name,
+            variants,
+        })
+    }
+
+    /// Parses `const NAME: ty = value;`, whose attributes have already been read.
+    fn parse_const(&mut self, attrs: Vec<proc_macro2::TokenStream>) -> syn::Result<SpecConst> {
+        let (kw, name) = match (self.advance1(), self.advance2()) {
+            (Some(kw), (Some(TokenTree::Ident(name)), Some(TokenTree::Punct(p))))
+                if p.as_char() == ':' =>
+            {
+                (kw, name)
+            }
+            (kw, _) => {
+                return Err(syn::Error::new(
+                    kw.map(|t| t.span()).unwrap_or(Span::call_site()),
+                    "expected a constant declaration of the form `const NAME: ty = value;`",
+                ))
+            }
+        };
+        let mut ty = proc_macro2::TokenStream::new();
+        let mut value = proc_macro2::TokenStream::new();
+        let mut in_value = false;
+        loop {
+            match self.advance1() {
+                Some(TokenTree::Punct(p)) if p.as_char() == ';' => break,
+                Some(TokenTree::Punct(p))
+                    if p.as_char() == '=' && p.spacing() == Spacing::Alone && !in_value =>
+                {
+                    in_value = true
+                }
+                Some(t) if in_value => value.extend([t]),
+                Some(t) => ty.extend([t]),
+                None => {
+                    return Err(syn::Error::new(
+                        kw.span(),
+                        format!("expected `;` after the value of `{name}`"),
+                    ))
+                }
+            }
+        }
+        if ty.is_empty() || value.is_empty() {
+            return Err(syn::Error::new(
+                name.span(),
+                format!("expected `{name}: ty = value`"),
+            ));
+        }
+        Ok(SpecConst {
+            attrs,
+            name,
+            ty,
+            value,
+        })
+    }
+
+    /// Parses field declarations up to the end of the input or a `match`, placing them from
+    /// bit `start` onwards, one after another or as C compilers place bitfields if
+    /// `c_bitfield`. Returns the bit after the last placed field.
+    fn parse_fields(
+        &mut self,
+        fields: &mut Vec<SpecField>,
+        mut start: usize,
+        c_bitfield: bool,
+    ) -> syn::Result<usize> {
         loop {
-            match g.peek2() {
+            let mut alias = false;
+            let mut reserved = None;
+            let mut encoding = None;
+            let mut check = None;
+            let mut access = None;
+            let mut doc = vec![];
+            while let Some(attr) = self.parse_attribute()? {
+                if let Some(line) = doc_comment(&attr) {
+                    doc.push(line);
+                    continue;
+                }
+                match &attr.clone().into_iter().collect::<Vec<_>>()[..] {
+                    [TokenTree::Ident(id)] if id == "alias" => alias = true,
+                    [TokenTree::Ident(id)] if id == "reserved" => reserved = Some(false),
+                    [TokenTree::Ident(id), TokenTree::Group(args)]
+                        if id == "reserved" && args.delimiter() == Delimiter::Parenthesis =>
+                    {
+                        reserved = match &args.stream().into_iter().collect::<Vec<_>>()[..] {
+                            [TokenTree::Ident(bits)] if bits == "zero" => Some(false),
+                            [TokenTree::Ident(bits)] if bits == "one" => Some(true),
+                            _ => {
+                                return Err(syn::Error::new(
+                                    args.span(),
+                                    "expected `#[reserved(zero)]` or `#[reserved(one)]`",
+                                ))
+                            }
+                        };
+                    }
+                    [TokenTree::Ident(id), TokenTree::Group(args)]
+                        if id == "encoding" && args.delimiter() == Delimiter::Parenthesis =>
+                    {
+                        encoding = Some(parse_encoding(args)?);
+                    }
+                    [TokenTree::Ident(id), TokenTree::Group(args)]
+                        if id == "access" && args.delimiter() == Delimiter::Parenthesis =>
+                    {
+                        let mode = args.stream().to_string();
+                        access = match Access::ALL.iter().find(|(name, _)| *name == mode) {
+                            Some((_, mode)) => Some(*mode),
+                            None => {
+                                return Err(syn::Error::new(
+                                    args.span(),
+                                    "expected `#[access(read_only|write_only|read_write|write_once|read_write_once)]`",
+                                ))
+                            }
+                        };
+                    }
+                    [TokenTree::Ident(id), TokenTree::Group(args)]
+                        if (id == "parity" || id == "crc")
+                            && args.delimiter() == Delimiter::Parenthesis =>
+                    {
+                        check = Some(parse_check(id, args)?);
+                    }
+                    _ => return Err(syn::Error::new_spanned(attr, "unknown field attribute")),
+                }
+            }
+            match self.peek2() {
+                (Some(TokenTree::Ident(kw)), _) if kw == "match" && !alias => return Ok(start),
                 (Some(TokenTree::Ident(field)), Some(TokenTree::Punct(p)))
                     if p.as_char() == ':' =>
                 {
-                    g.advance2();
-                    match g.peek1() {
-                        Some(TokenTree::Ident(ty)) if ty == "bool" || ty.to_string().starts_with('u') => {
-                            let size = if ty == "bool" {
-                                1
-                            } else {
-                                let Ok(s) = ty.to_string()[1..].parse::<usize>() else {
-                                    return Err(syn::Error::new(
-                                        ty.span().into(),
-                                        "expected a type declaration of the form `bool` or `u{N}`, where N is a usize",
-                                    ));
-                                };
-                                s
-                            };
-                            fields.push(SpecField { name: field, start, size });
-
-                            start += size;
-
-                            g.advance1();
-                            match self.peek1() {
-                                Some(TokenTree::Punct(p)) if p.as_char() == ',' => {}
-                                None => {}
-                                _ => {
-                                    return Err(syn::Error::new(
-                                        ty.span().into(),
-                                        "Expected ',' or end of field declarations",
-                                    ));
-                                }
+                    self.advance2();
+                    let (size, signed, scale) = self.parse_field_ty()?;
+                    let enum_ty = match self.peek1() {
+                        Some(TokenTree::Ident(kw)) if kw == "as" && scale.is_none() => {
+                            self.advance1();
+                            Some(self.parse_enum_ty(&field, size, signed)?)
+                        }
+                        _ => None,
+                    };
+                    let c_unit = match self.peek1() {
+                        Some(TokenTree::Ident(kw)) if kw == "in" => {
+                            self.advance1();
+                            if !c_bitfield || alias {
+                                return Err(syn::Error::new(
+                                    kw.span(),
+                                    "only the fields of `#[repr(C_bitfield)]` specs have storage units, and not their aliases",
+                                ));
                             }
-                            g.advance1();
+                            Some(self.parse_c_unit()?)
                         }
-                        Some(TokenTree::Ident(ty)) => {
+                        _ if c_bitfield && !alias => Some(32),
+                        _ => None,
+                    };
+                    if let Some(unit) = c_unit.filter(|unit| size > *unit) {
+                        return Err(syn::Error::new(
+                            field.span(),
+                            format!(
+                                "`{field}` is wider than its {unit}-bit storage unit, declare a wider one as in `{field}: u{size} in u{}`",
+                                size.next_power_of_two().max(8),
+                            ),
+                        ));
+                    }
+                    let (field_start, split) = match self.peek2() {
+                        (Some(TokenTree::Punct(p)), next)
+                            if c_bitfield
+                                && !alias
+                                && (p.as_char() == '@'
+                                    || p.as_char() == '='
+                                        && matches!(next, Some(TokenTree::Group(_)))) =>
+                        {
                             return Err(syn::Error::new(
-                                ty.span().into(),
-                                "expected a type declaration of the form `bool` or `u{N}`, where N is a usize",
-                            ))
+                                p.span(),
+                                "the fields of `#[repr(C_bitfield)]` specs are placed as C compilers place bitfields, and cannot give their position",
+                            ));
+                        }
+                        (Some(TokenTree::Punct(p)), _) if p.as_char() == '@' => {
+                            self.advance1();
+                            (self.parse_position(size)?, None)
+                        }
+                        (Some(TokenTree::Punct(p)), Some(TokenTree::Group(_)))
+                            if p.as_char() == '=' =>
+                        {
+                            self.advance1();
+                            let split = self.parse_split(size, &field)?;
+                            let lowest = split.segments.iter().map(|r| r.start).min();
+                            (lowest.unwrap_or(start), Some(split))
+                        }
+                        _ if alias => {
+                            return Err(syn::Error::new(
+                                field.span(),
+                                "alias fields must give their position, e.g. `@ 0..8`",
+                            ));
+                        }
+                        _ => match c_unit {
+                            // A bitfield that would straddle a boundary of its storage unit starts
+                            // the next one instead.
+                            Some(unit) if start / unit != (start + size - 1) / unit => {
+                                (start.next_multiple_of(unit), None)
+                            }
+                            _ => (start, None),
+                        },
+                    };
+                    let fixed = match self.peek2() {
+                        (Some(TokenTree::Punct(p0)), Some(TokenTree::Punct(p1)))
+                            if p0.as_char() == '=' && p1.as_char() == '=' =>
+                        {
+                            self.advance2();
+                            let span = self.peek1().map(|t| t.span()).unwrap_or(p1.span());
+                            let value = self.parse_int::<u128>()?;
+                            if size < 128 && value >> size != 0 {
+                                return Err(syn::Error::new(
+                                    span,
+                                    format!("{value} does not fit in {size}-bit field `{field}`"),
+                                ));
+                            }
+                            Some(value)
+                        }
+                        _ => None,
+                    };
+                    let reserved = match reserved {
+                        Some(_)
+                            if alias
+                                || fixed.is_some()
+                                || enum_ty.is_some()
+                                || scale.is_some()
+                                || encoding.is_some() =>
+                        {
+                            return Err(syn::Error::new(
+                                field.span(),
+                                "reserved fields cannot also be aliases, fixed, enums, scaled or encoded",
+                            ));
                         }
-                        t => {
-                            todo!("{t:?}");
+                        Some(ones) => Some(if ones { u128::MAX >> (128 - size) } else { 0 }),
+                        None => None,
+                    };
+                    if let Some(encoding) = encoding {
+                        let typed = signed || enum_ty.is_some() || scale.is_some();
+                        check_encoding(&field, encoding, size, typed)?;
+                    }
+                    let plain = !alias
+                        && !signed
+                        && fixed.is_none()
+                        && reserved.is_none()
+                        && enum_ty.is_none()
+                        && scale.is_none()
+                        && encoding.is_none();
+                    match &check {
+                        Some(Check::Parity { .. }) if size != 1 || !plain => {
+                            return Err(syn::Error::new(
+                                field.span(),
+                                format!("parity field `{field}` must be a plain `bool`"),
+                            ));
+                        }
+                        Some(Check::Crc { .. }) if size == 1 || !plain => {
+                            return Err(syn::Error::new(
+                                field.span(),
+                                format!("CRC field `{field}` must be a plain `u{{N}}` with N > 1"),
+                            ));
+                        }
+                        Some(Check::Crc { poly, init, .. })
+                            if size < 128 && (poly | init) >> size != 0 =>
+                        {
+                            return Err(syn::Error::new(
+                                field.span(),
+                                format!("the CRC polynomial and initial value of `{field}` must fit in {size} bits"),
+                            ));
+                        }
+                        _ => {}
+                    }
+
+                    let field = SpecField {
+                        name: field,
+                        start: field_start,
+                        size,
+                        alias,
+                        signed,
+                        split,
+                        fixed,
+                        reserved,
+                        enum_ty,
+                        scale,
+                        encoding,
+                        check,
+                        doc,
+                        access,
+                        c_unit,
+                    };
+                    if let Some(other) = fields.iter().find(|f| f.name == field.name) {
+                        return Err(syn::Error::new(
+                            field.name.span(),
+                            format!("field `{}` is declared more than once", other.name),
+                        ));
+                    }
+                    if !alias {
+                        for other in fields.iter().filter(|f| !f.alias) {
+                            if let Some(bits) = other.overlaps(&field) {
+                                return Err(syn::Error::new(
+                                    field.name.span(),
+                                    format!(
+                                        "field `{}` overlaps bits {}..{} of field `{}`, mark it `#[alias]` if this is intended",
+                                        field.name,
+                                        bits.start,
+                                        bits.start + bits.size,
+                                        other.name,
+                                    ),
+                                ));
+                            }
                         }
+                        start = field.end();
                     }
+                    fields.push(field);
+
+                    match self.peek1() {
+                        Some(TokenTree::Punct(p)) if p.as_char() == ',' => {
+                            self.advance1();
+                        }
+                        Some(TokenTree::Ident(kw)) if kw == "match" => {}
+                        None => {}
+                        Some(t) => {
+                            return Err(syn::Error::new(
+                                t.span(),
+                                "expected ',' or end of field declarations",
+                            ));
+                        }
+                    }
+                }
+                (None, _) if alias => {
+                    return Err(syn::Error::new(
+                        Span::call_site(),
+                        "expected a field declaration after `#[alias]`",
+                    ));
+                }
+                (None, _) => return Ok(start),
+                (Some(t), _) => {
+                    return Err(syn::Error::new(
+                        t.span(),
+                        "expected a field declaration of the form `name: type`",
+                    ));
                 }
-                (None, None) => break Ok(Spec { name, fields }),
-                _ => todo!("a {:?}", g.peek3()),
             }
         }
     }
 
-    fn peek1(&self) -> Option<TokenTree> {
-        self.0.clone().next()
+    /// Parses `tag { value => Name { fields.. }, .. }` following `match`. Each variant starts with
+    /// the `common` fields, and places its own from bit `start` onwards.
+    fn parse_variants(&mut self, common: &[SpecField], start: usize) -> syn::Result<SpecVariants> {
+        let tag = match self.advance1() {
+            Some(TokenTree::Ident(tag)) => tag,
+            t => {
+                return Err(syn::Error::new(
+                    t.map(|t| t.span()).unwrap_or(Span::call_site()),
+                    "expected the name of the tag field after `match`",
+                ))
+            }
+        };
+        let Some(tag_field) = common
+            .iter()
+            .find(|f| f.name == tag && !f.alias && f.split.is_none())
+        else {
+            return Err(syn::Error::new(
+                tag.span(),
+                format!("`{tag}` is not a contiguous field declared before `match`"),
+            ));
+        };
+
+        let mut g = match self.advance1() {
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => {
+                self.nested(g.stream())
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    tag.span(),
+                    "expected braced list of variants after the tag field",
+                ))
+            }
+        };
+        let mut variants: Vec<SpecVariant> = vec![];
+        loop {
+            let mut doc = vec![];
+            while let Some(attr) = g.parse_attribute()? {
+                match doc_comment(&attr) {
+                    Some(line) => doc.push(line),
+                    None => return Err(syn::Error::new_spanned(attr, "unknown variant attribute")),
+                }
+            }
+            let Some(t) = g.peek1() else {
+                break;
+            };
+            let tag_value = g.parse_int::<u128>()?;
+            if tag_field.size < 128 && tag_value >> tag_field.size != 0 {
+                return Err(syn::Error::new(
+                    t.span(),
+                    format!(
+                        "tag value {tag_value} does not fit in {}-bit field `{tag}`",
+                        tag_field.size
+                    ),
+                ));
+            }
+            match g.advance2() {
+                (Some(TokenTree::Punct(p0)), Some(TokenTree::Punct(p1)))
+                    if p0.as_char() == '=' && p1.as_char() == '>' => {}
+                _ => {
+                    return Err(syn::Error::new(
+                        t.span(),
+                        "expected `=>` after the tag value",
+                    ))
+                }
+            }
+            let (name, body) = match g.advance2() {
+                (Some(TokenTree::Ident(name)), Some(TokenTree::Group(body)))
+                    if body.delimiter() == Delimiter::Brace =>
+                {
+                    (name, body)
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        t.span(),
+                        "expected a variant of the form `value => Name { fields.. }`",
+                    ))
+                }
+            };
+            if let Some(other) = variants
+                .iter()
+                .find(|v| v.name == name || v.tag_value == tag_value)
+            {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!(
+                        "variant `{name}` repeats the name or tag value of `{}`",
+                        other.name
+                    ),
+                ));
+            }
+
+            let mut fields = common.to_vec();
+            let mut b = g.nested(body.stream());
+            b.parse_fields(&mut fields, start, false)?;
+            if let Some(t) = b.peek1() {
+                return Err(syn::Error::new(t.span(), "variants cannot be nested"));
+            }
+            variants.push(SpecVariant {
+                name,
+                tag_value,
+                fields,
+                doc,
+            });
+
+            match g.advance1() {
+                Some(TokenTree::Punct(p)) if p.as_char() == ',' => {}
+                None => {}
+                Some(t) => {
+                    return Err(syn::Error::new(
+                        t.span(),
+                        "expected ',' or end of variant declarations",
+                    ))
+                }
+            }
+        }
+
+        Ok(SpecVariants { tag, variants })
     }
 
-    fn peek2(&self) -> (Option<TokenTree>, Option<TokenTree>) {
-        let mut it = self.0.clone();
-        let x0 = it.next();
-        (x0, it.next())
+    /// Parses a field type, `bool`, `u{N}` or `i{N}`, returning its size in bits and whether it
+    /// is signed. Integer types may be scaled, `u10 * 0.0125 + 0.5`, and `q{M}.{N}`, `uq{M}.{N}`,
+    /// `fix<W, F>` and `ufix<W, F>` declare fixed-point fields with `N` or `F` fractional bits.
+    fn parse_field_ty(&mut self) -> syn::Result<(usize, bool, Option<Scale>)> {
+        let error = |span| {
+            syn::Error::new(
+                span,
+                "expected a type declaration of the form `bool`, `u{N}`, `i{N}`, `q{M}.{N}` or `fix<W, F>`, where N is a usize",
+            )
+        };
+        let fixed_point = |size: usize, signed: bool, fraction: usize| {
+            (
+                size,
+                signed,
+                Some(Scale {
+                    factor: 0.5f64.powi(fraction as i32),
+                    offset: 0.0,
+                }),
+            )
+        };
+        match self.advance1() {
+            Some(TokenTree::Ident(ty)) if ty == "bool" => Ok((1, false, None)),
+            Some(TokenTree::Ident(ty)) if ty == "fix" || ty == "ufix" => {
+                let signed = ty == "fix";
+                let args = match self.advance1() {
+                    Some(TokenTree::Punct(p)) if p.as_char() == '<' => {
+                        let width = self.parse_int::<usize>()?;
+                        let comma = self.advance1();
+                        let fraction = self.parse_int::<usize>()?;
+                        match (comma, self.advance1()) {
+                            (Some(TokenTree::Punct(c)), Some(TokenTree::Punct(gt)))
+                                if c.as_char() == ',' && gt.as_char() == '>' =>
+                            {
+                                Some((width, fraction))
+                            }
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                };
+                match args {
+                    Some((width, fraction)) if width > 1 && fraction <= 128 => {
+                        Ok(fixed_point(width, signed, fraction))
+                    }
+                    _ => Err(syn::Error::new(
+                        ty.span(),
+                        format!("expected `{ty}<W, F>` with a width W > 1 and F fractional bits"),
+                    )),
+                }
+            }
+            Some(TokenTree::Ident(ty))
+                if ty.to_string().trim_start_matches('u').starts_with('q') =>
+            {
+                let signed = !ty.to_string().starts_with('u');
+                let int = ty.to_string().trim_start_matches('u')[1..].parse::<usize>();
+                let fraction = match self.peek2() {
+                    (Some(TokenTree::Punct(dot)), Some(TokenTree::Literal(_)))
+                        if dot.as_char() == '.' =>
+                    {
+                        self.advance1();
+                        self.parse_int::<usize>().ok()
+                    }
+                    _ => None,
+                };
+                match (int, fraction) {
+                    (Ok(int), Some(fraction)) if int + fraction > 1 && fraction <= 128 => {
+                        Ok(fixed_point(int + fraction, signed, fraction))
+                    }
+                    _ => Err(syn::Error::new(
+                        ty.span(),
+                        "expected a fixed-point type of the form `q{M}.{N}` or `uq{M}.{N}`",
+                    )),
+                }
+            }
+            Some(TokenTree::Ident(ty)) if ty.to_string().starts_with(['u', 'i']) => {
+                let signed = ty.to_string().starts_with('i');
+                let size = match ty.to_string()[1..].parse::<usize>() {
+                    Ok(size) if size > usize::from(signed) => size,
+                    _ => return Err(error(ty.span())),
+                };
+                let scale = match self.peek1() {
+                    Some(TokenTree::Punct(p)) if p.as_char() == '*' => {
+                        self.advance1();
+                        Some(self.parse_scale(size)?)
+                    }
+                    _ => None,
+                };
+                Ok((size, signed, scale))
+            }
+            Some(t) => Err(error(t.span())),
+            None => Err(error(Span::call_site())),
+        }
+    }
+
+    /// Parses the scale following `*` of a scaled field, `0.0125 + 0.5`, where the offset is
+    /// optional and may be subtracted.
+    fn parse_scale(&mut self, size: usize) -> syn::Result<Scale> {
+        let span = self.peek1().map(|t| t.span()).unwrap_or(Span::call_site());
+        let factor = self.parse_real()?;
+        if factor == 0.0 || size == 1 {
+            return Err(syn::Error::new(
+                span,
+                "scaled fields need more than one bit and a non-zero scale factor",
+            ));
+        }
+        let offset = match self.peek1() {
+            Some(TokenTree::Punct(p)) if p.as_char() == '+' => {
+                self.advance1();
+                self.parse_real()?
+            }
+            Some(TokenTree::Punct(p)) if p.as_char() == '-' => {
+                self.advance1();
+                -self.parse_real()?
+            }
+            _ => 0.0,
+        };
+        Ok(Scale { factor, offset })
+    }
+
+    /// Parses an integer or float literal as an `f64`.
+    fn parse_real(&mut self) -> syn::Result<f64> {
+        match self.advance1() {
+            Some(TokenTree::Literal(lit)) => match syn::Lit::new(lit.clone()) {
+                syn::Lit::Float(f) => f.base10_parse::<f64>(),
+                syn::Lit::Int(i) => i.base10_parse::<f64>(),
+                _ => Err(syn::Error::new(lit.span(), "expected a number")),
+            },
+            Some(t) => Err(syn::Error::new(t.span(), "expected a number")),
+            None => Err(syn::Error::new(Span::call_site(), "expected a number")),
+        }
+    }
+
+    /// Parses the path of the enum following `as`, which may only be given to unsigned fields of
+    /// more than one bit.
+    fn parse_enum_ty(
+        &mut self,
+        field: &proc_macro2::Ident,
+        size: usize,
+        signed: bool,
+    ) -> syn::Result<proc_macro2::TokenStream> {
+        let mut path = proc_macro2::TokenStream::new();
+        loop {
+            match self.advance1() {
+                Some(TokenTree::Ident(id)) => path.extend([TokenTree::Ident(id)]),
+                t => {
+                    return Err(syn::Error::new(
+                        t.map(|t| t.span()).unwrap_or(field.span()),
+                        "expected the path of an enum after `as`",
+                    ))
+                }
+            }
+            match self.peek2() {
+                (Some(TokenTree::Punct(p0)), Some(TokenTree::Punct(p1)))
+                    if p0.as_char() == ':' && p1.as_char() == ':' =>
+                {
+                    self.advance2();
+                    path.extend([TokenTree::Punct(p0), TokenTree::Punct(p1)]);
+                }
+                _ => break,
+            }
+        }
+        if signed || size == 1 {
+            return Err(syn::Error::new(
+                field.span(),
+                format!("enum field `{field}` must be declared as `u{{N}}` with N > 1"),
+            ));
+        }
+        Ok(path)
+    }
+
+    /// Parses the bit list following `=` of a split field, `[31, 7, 30..25, 11..8] << 1`. Bits
+    /// are listed most significant first, and ranges `hi..lo` include both ends.
+    fn parse_split(&mut self, size: usize, field: &proc_macro2::Ident) -> syn::Result<SplitBits> {
+        let list = match self.advance1() {
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Bracket => g,
+            t => {
+                return Err(syn::Error::new(
+                    t.map(|t| t.span()).unwrap_or(field.span()),
+                    "expected a bracketed list of bits, e.g. `[31, 7, 30..25, 11..8]`",
+                ))
+            }
+        };
+        let mut l = self.nested(list.stream());
+        let mut segments = vec![];
+        while let Some(t) = l.peek1() {
+            let hi = l.parse_int::<usize>()?;
+            let lo = match l.peek2() {
+                (Some(TokenTree::Punct(p0)), Some(TokenTree::Punct(p1)))
+                    if p0.as_char() == '.' && p1.as_char() == '.' =>
+                {
+                    l.advance2();
+                    l.parse_int::<usize>()?
+                }
+                _ => hi,
+            };
+            if lo > hi {
+                return Err(syn::Error::new(
+                    t.span(),
+                    format!("bit ranges of split fields run from high to low, write `{lo}..{hi}`"),
+                ));
+            }
+            segments.push(BitRange {
+                start: lo,
+                size: hi - lo + 1,
+            });
+            match l.advance1() {
+                Some(TokenTree::Punct(p)) if p.as_char() == ',' => {}
+                None => {}
+                Some(t) => return Err(syn::Error::new(t.span(), "expected ',' or `]`")),
+            }
+        }
+
+        let shift = match self.peek2() {
+            (Some(TokenTree::Punct(p0)), Some(TokenTree::Punct(p1)))
+                if p0.as_char() == '<' && p1.as_char() == '<' =>
+            {
+                self.advance2();
+                self.parse_int::<usize>()?
+            }
+            _ => 0,
+        };
+
+        let bits = segments.iter().map(|r| r.size).sum::<usize>() + shift;
+        if segments.is_empty() || bits != size {
+            return Err(syn::Error::new(
+                list.span(),
+                format!("the split bits of `{field}` add up to {bits} bits, not {size}"),
+            ));
+        }
+        if size == 1 {
+            return Err(syn::Error::new(
+                list.span(),
+                "single bit fields are placed with `@`",
+            ));
+        }
+        for (idx, r) in segments.iter().enumerate() {
+            if segments[..idx]
+                .iter()
+                .any(|o| r.start < o.start + o.size && o.start < r.start + r.size)
+            {
+                return Err(syn::Error::new(
+                    list.span(),
+                    format!(
+                        "the split bits of `{field}` list bit {} more than once",
+                        r.start
+                    ),
+                ));
+            }
+        }
+
+        Ok(SplitBits { segments, shift })
     }
 
-    fn peek3(&self) -> (Option<TokenTree>, Option<TokenTree>, Option<TokenTree>) {
+    /// Parses the storage unit following `in` of a field of a `#[repr(C_bitfield)]` spec, the
+    /// integer type the bitfield is declared with in C, returning its width.
+    fn parse_c_unit(&mut self) -> syn::Result<usize> {
+        match self.advance1() {
+            Some(TokenTree::Ident(ty)) if ty == "bool" => Ok(8),
+            Some(TokenTree::Ident(ty)) => match ty.to_string().trim_start_matches(['u', 'i']) {
+                bits @ ("8" | "16" | "32" | "64") if ty.to_string().len() > bits.len() => {
+                    Ok(bits.parse().unwrap())
+                }
+                _ => Err(syn::Error::new(
+                    ty.span(),
+                    "expected a storage unit of `bool`, `u8`, `u16`, `u32`, `u64` or their signed counterparts",
+                )),
+            },
+            t => Err(syn::Error::new(
+                t.map(|t| t.span()).unwrap_or(Span::call_site()),
+                "expected a storage unit of `bool`, `u8`, `u16`, `u32`, `u64` or their signed counterparts",
+            )),
+        }
+    }
+
+    /// Parses the bit position following `@`, either `start` or `start..end`, checking that it
+    /// agrees with the field size.
+    fn parse_position(&mut self, size: usize) -> syn::Result<usize> {
+        let start = self.parse_int::<usize>()?;
+        match self.peek2() {
+            (Some(TokenTree::Punct(p0)), Some(TokenTree::Punct(p1)))
+                if p0.as_char() == '.' && p1.as_char() == '.' =>
+            {
+                self.advance2();
+                let end_span = self.peek1().map(|t| t.span()).unwrap_or(p1.span());
+                let end = self.parse_int::<usize>()?;
+                if end.checked_sub(start) != Some(size) {
+                    return Err(syn::Error::new(
+                        end_span,
+                        format!(
+                            "bit range {start}..{end} does not match the field size of {size} bits"
+                        ),
+                    ));
+                }
+                Ok(start)
+            }
+            _ => Ok(start),
+        }
+    }
+
+    pub(crate) fn parse_int<N>(&mut self) -> syn::Result<N>
+    where
+        N: std::str::FromStr,
+        N::Err: std::fmt::Display,
+    {
+        match self.advance1() {
+            Some(TokenTree::Literal(lit)) => match syn::Lit::new(lit) {
+                syn::Lit::Int(int) => int.base10_parse::<N>(),
+                lit => Err(syn::Error::new(lit.span(), "expected an integer")),
+            },
+            Some(TokenTree::Ident(id)) => match self.1.iter().find(|(name, _)| *name == id) {
+                Some((_, value)) => value.to_string().parse::<N>().map_err(|e| {
+                    syn::Error::new(id.span(), format!("`{id}` is out of range: {e}"))
+                }),
+                None => Err(syn::Error::new(
+                    id.span(),
+                    format!("`{id}` is not an integer constant declared in this `spec!`"),
+                )),
+            },
+            Some(t) => Err(syn::Error::new(t.span(), "expected an integer")),
+            None => Err(syn::Error::new(Span::call_site(), "expected an integer")),
+        }
+    }
+
+    /// Consumes a `#[...]` attribute, returning its contents.
+    pub(crate) fn parse_attribute(&mut self) -> syn::Result<Option<proc_macro2::TokenStream>> {
+        match self.peek2() {
+            (Some(TokenTree::Punct(p)), Some(TokenTree::Group(g)))
+                if p.as_char() == '#' && g.delimiter() == Delimiter::Bracket =>
+            {
+                self.advance2();
+                Ok(Some(g.stream()))
+            }
+            (Some(TokenTree::Punct(p)), t) if p.as_char() == '#' => Err(syn::Error::new(
+                t.map(|t| t.span()).unwrap_or(p.span()),
+                "expected `[` to open an attribute",
+            )),
+            _ => Ok(None),
+        }
+    }
+
+    pub(crate) fn peek1(&self) -> Option<TokenTree> {
+        self.0.clone().next()
+    }
+
+    pub(crate) fn peek2(&self) -> (Option<TokenTree>, Option<TokenTree>) {
         let mut it = self.0.clone();
         let x0 = it.next();
-        let x1 = it.next();
-        (x0, x1, it.next())
+        (x0, it.next())
     }
 
-    fn advance1(&mut self) -> Option<TokenTree> {
+    pub(crate) fn advance1(&mut self) -> Option<TokenTree> {
         self.0.next()
     }
 
-    fn advance2(&mut self) -> (Option<TokenTree>, Option<TokenTree>) {
+    pub(crate) fn advance2(&mut self) -> (Option<TokenTree>, Option<TokenTree>) {
         let x0 = self.0.next();
         (x0, self.0.next())
     }
 }
+
+/// The constants among `tokens` whose value is an integer literal, `const NAME: ty = 42;`.
+fn integer_consts(tokens: Vec<TokenTree>) -> Vec<(proc_macro2::Ident, u128)> {
+    tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(i, t)| match (t, tokens.get(i + 1)) {
+            (TokenTree::Ident(kw), Some(TokenTree::Ident(name))) if kw == "const" => {
+                let rest = &tokens[i + 2..];
+                let end = rest
+                    .iter()
+                    .position(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == ';'))?;
+                match &rest[..end] {
+                    [.., TokenTree::Punct(eq), TokenTree::Literal(lit)] if eq.as_char() == '=' => {
+                        match syn::Lit::new(lit.clone()) {
+                            syn::Lit::Int(int) => Some((name.clone(), int.base10_parse().ok()?)),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The width of a `#[repr(C_bitfield)]` spec whose fields end at bit `end`: the size of the C
+/// struct, rounded up to the alignment of its widest storage unit. Reserved fields stand for
+/// unnamed bitfields, which don't align the struct. It must be as large and as aligned as the
+/// container, so that the spec can stand in for the struct across FFI.
+fn c_bitfield_size(
+    name: &proc_macro2::Ident,
+    fields: &[SpecField],
+    end: usize,
+) -> syn::Result<usize> {
+    let align = fields
+        .iter()
+        .filter(|f| f.reserved.is_none())
+        .filter_map(|f| f.c_unit)
+        .max()
+        .unwrap_or(8);
+    let size = end.next_multiple_of(align).max(align);
+    if size != align {
+        return Err(syn::Error::new(
+            name.span(),
+            format!(
+                "the C struct `{name}` takes {size} bits aligned to {align}, which no integer container matches, declare a wider storage unit such as `in u{}`",
+                size.next_power_of_two().min(64),
+            ),
+        ));
+    }
+    Ok(size)
+}
+
+/// The line of a doc comment, which reaches the macro as `#[doc = "..."]`.
+fn doc_comment(attr: &proc_macro2::TokenStream) -> Option<String> {
+    match &attr.clone().into_iter().collect::<Vec<_>>()[..] {
+        [TokenTree::Ident(id), TokenTree::Punct(eq), TokenTree::Literal(lit)]
+            if id == "doc" && eq.as_char() == '=' =>
+        {
+            match syn::Lit::new(lit.clone()) {
+                syn::Lit::Str(s) => Some(s.value()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Parses the arguments of `#[encoding(..)]`: `bcd`, `gray`, `onehot` or `offset = N`.
+fn parse_encoding(args: &proc_macro2::Group) -> syn::Result<Encoding> {
+    let error = || {
+        syn::Error::new(
+            args.span(),
+            "expected `#[encoding(bcd)]`, `#[encoding(gray)]`, `#[encoding(onehot)]` or `#[encoding(offset = N)]`",
+        )
+    };
+    let mut a = SpecParser::new(args.stream());
+    let encoding = match a.advance1() {
+        Some(TokenTree::Ident(id)) if id == "bcd" => Encoding::Bcd,
+        Some(TokenTree::Ident(id)) if id == "gray" => Encoding::Gray,
+        Some(TokenTree::Ident(id)) if id == "onehot" => Encoding::OneHot,
+        Some(TokenTree::Ident(id)) if id == "offset" => {
+            match a.advance1() {
+                Some(TokenTree::Punct(eq)) if eq.as_char() == '=' => {}
+                _ => return Err(error()),
+            }
+            let negative = match a.peek1() {
+                Some(TokenTree::Punct(minus)) if minus.as_char() == '-' => {
+                    a.advance1();
+                    true
+                }
+                _ => false,
+            };
+            let offset = a.parse_int::<i128>()?;
+            Encoding::Offset(if negative { -offset } else { offset })
+        }
+        _ => return Err(error()),
+    };
+    if a.peek1().is_some() {
+        return Err(error());
+    }
+    Ok(encoding)
+}
+
+/// Checks that an encoding suits the field it is given to, which must be a plain unsigned
+/// field of more than one bit.
+fn check_encoding(
+    field: &proc_macro2::Ident,
+    encoding: Encoding,
+    size: usize,
+    typed: bool,
+) -> syn::Result<()> {
+    let error = |msg: String| Err(syn::Error::new(field.span(), msg));
+    if typed || size == 1 {
+        return error(format!(
+            "encoded field `{field}` must be declared as `u{{N}}` with N > 1"
+        ));
+    }
+    match encoding {
+        Encoding::Bcd if size % 4 != 0 => error(format!(
+            "BCD field `{field}` must be a whole number of 4-bit digits"
+        )),
+        Encoding::Offset(_) if size >= 127 => error(format!(
+            "offset field `{field}` must be narrower than 127 bits"
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Parses the arguments of `#[parity(even | odd, over = [..])]` or
+/// `#[crc(poly = N, over = [..], init = N)]`, where `init` is optional.
+fn parse_check(kind: &proc_macro2::Ident, args: &proc_macro2::Group) -> syn::Result<Check> {
+    let error = || {
+        syn::Error::new(
+            args.span(),
+            if kind == "parity" {
+                "expected `#[parity(even, over = [..])]` or `#[parity(odd, over = [..])]`"
+            } else {
+                "expected `#[crc(poly = N, over = [..])]`, optionally followed by `init = N`"
+            },
+        )
+    };
+    let mut a = SpecParser::new(args.stream());
+    let mut odd = None;
+    let mut poly = None;
+    let mut init = 0;
+    let mut over = None;
+    loop {
+        match a.advance1() {
+            Some(TokenTree::Ident(id)) if kind == "parity" && (id == "even" || id == "odd") => {
+                odd = Some(id == "odd");
+            }
+            Some(TokenTree::Ident(id)) if id == "over" || id == "poly" || id == "init" => {
+                match a.advance1() {
+                    Some(TokenTree::Punct(eq)) if eq.as_char() == '=' => {}
+                    _ => return Err(error()),
+                }
+                if id == "over" {
+                    let list = match a.advance1() {
+                        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Bracket => g,
+                        _ => return Err(error()),
+                    };
+                    let mut fields = vec![];
+                    for t in list.stream() {
+                        match t {
+                            TokenTree::Ident(field) => fields.push(field),
+                            TokenTree::Punct(p) if p.as_char() == ',' => {}
+                            t => return Err(syn::Error::new(t.span(), "expected a field name")),
+                        }
+                    }
+                    if fields.is_empty() {
+                        return Err(error());
+                    }
+                    over = Some(fields);
+                } else if kind == "crc" && id == "poly" {
+                    poly = Some(a.parse_int::<u128>()?);
+                } else if kind == "crc" {
+                    init = a.parse_int::<u128>()?;
+                } else {
+                    return Err(error());
+                }
+            }
+            None => break,
+            _ => return Err(error()),
+        }
+        match a.advance1() {
+            Some(TokenTree::Punct(p)) if p.as_char() == ',' => {}
+            None => break,
+            _ => return Err(error()),
+        }
+    }
+    match (odd, poly, over) {
+        (Some(odd), None, Some(over)) => Ok(Check::Parity { odd, over }),
+        (None, Some(poly), Some(over)) => Ok(Check::Crc { poly, init, over }),
+        _ => Err(error()),
+    }
+}
@@ -1,7 +1,71 @@
+/// The contents of a `spec!` block: any number of structs, and the enums and constants they
+/// share.
+#[derive(Debug, Clone)]
+pub struct SpecItems {
+    pub specs: Vec<Spec>,
+    pub enums: Vec<SpecEnum>,
+    pub consts: Vec<SpecConst>,
+}
+
+/// `enum Mode { Off = 0, Slow, Fast = 3 }`, generated with an implementation of `FieldEnum` for
+/// fields declared `as Mode`.
+#[derive(Debug, Clone)]
+pub struct SpecEnum {
+    pub attrs: Vec<proc_macro2::TokenStream>,
+    pub name: proc_macro2::Ident,
+    pub variants: Vec<SpecEnumVariant>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SpecEnumVariant {
+    pub attrs: Vec<proc_macro2::TokenStream>,
+    pub name: proc_macro2::Ident,
+    pub discriminant: u128,
+}
+
+/// `const NAME: ty = value;`, passed through as is. Constants whose value is an integer literal
+/// can also stand in for integers in the declarations of fields.
+#[derive(Debug, Clone)]
+pub struct SpecConst {
+    pub attrs: Vec<proc_macro2::TokenStream>,
+    pub name: proc_macro2::Ident,
+    pub ty: proc_macro2::TokenStream,
+    pub value: proc_macro2::TokenStream,
+}
+
 #[derive(Debug, Clone)]
 pub struct Spec {
     pub name: proc_macro2::Ident,
     pub fields: Vec<SpecField>,
+    pub serde: SerdeRepr,
+    /// Fixes the width of the container rather than fitting it to the fields, so that the views
+    /// of a tagged spec share the container of their parent, and `#[repr(C_bitfield)]` specs are
+    /// as wide as the C struct they stand in for.
+    pub bits: Option<usize>,
+    pub variants: Option<SpecVariants>,
+    /// The doc comments on the struct, forwarded ahead of its layout table.
+    pub doc: Vec<String>,
+    /// The value of the register after reset, declared with `#[reset(0x8000_0001)]`. New values
+    /// start from it rather than zero, apart from their fixed, reserved and check fields.
+    pub reset: Option<u128>,
+}
+
+/// The layouts selected by the value of a tag field, declared with
+/// `match tag { value => Name { fields.. }, .. }` after the common fields of a spec.
+#[derive(Debug, Clone)]
+pub struct SpecVariants {
+    pub tag: proc_macro2::Ident,
+    pub variants: Vec<SpecVariant>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SpecVariant {
+    pub name: proc_macro2::Ident,
+    pub tag_value: u128,
+    /// The common fields of the spec followed by those of the variant.
+    pub fields: Vec<SpecField>,
+    /// The doc comments on the variant, forwarded to its view.
+    pub doc: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -9,4 +73,198 @@ pub struct SpecField {
     pub name: proc_macro2::Ident,
     pub start: usize,
     pub size: usize,
+    /// An alternate view of bits that belong to other fields, declared with `#[alias]`.
+    pub alias: bool,
+    /// Declared as `i{N}`, read and written as a two's complement value.
+    pub signed: bool,
+    /// Bits scattered across the container, declared as `= [31, 7, 30..25, 11..8] << 1`. The
+    /// field `start` is then the lowest of those bits.
+    pub split: Option<SplitBits>,
+    /// The value the field must hold, declared as `== 0b0110011`. Such fields are part of the
+    /// pattern a value `matches` rather than data that can be set.
+    pub fixed: Option<u128>,
+    /// The value reserved bits must hold, declared with `#[reserved]` for zeros or
+    /// `#[reserved(one)]` for ones. They can be read but not set, and are checked by `validate`.
+    pub reserved: Option<u128>,
+    /// The enum the field holds, declared as `mode: u2 as Mode`. Its raw bits are then read and
+    /// written with `mode_bits` and `set_mode_bits`.
+    pub enum_ty: Option<proc_macro2::TokenStream>,
+    /// The real value the raw bits stand for, declared as `u10 * 0.0125 + 0.5`, `q4.4` or
+    /// `ufix<8, 3>`. The raw bits are then read and written with `_bits` accessors, as for enums.
+    pub scale: Option<Scale>,
+    /// How the raw bits encode the field's value, declared with `#[encoding(..)]`. The raw bits
+    /// are then read and written with `_bits` accessors, as for enums.
+    pub encoding: Option<Encoding>,
+    /// A parity bit or checksum over other fields, declared with `#[parity(..)]` or `#[crc(..)]`.
+    /// Setters keep it up to date, and `validate` checks it.
+    pub check: Option<Check>,
+    /// The doc comments on the field, forwarded to its getter and mask.
+    pub doc: Vec<String>,
+    /// How software may access the field in hardware, declared with `#[access(read_only)]`. It is
+    /// documented, but doesn't change the accessors generated.
+    pub access: Option<Access>,
+    /// The storage unit of a field of a `#[repr(C_bitfield)]` spec in bits, declared as
+    /// `u3 in u8` for `uint8_t x : 3` or left as 32 for `unsigned int x : 3`.
+    pub c_unit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+    WriteOnce,
+    ReadWriteOnce,
+}
+
+impl Access {
+    pub const ALL: [(&'static str, Access); 5] = [
+        ("read_only", Access::ReadOnly),
+        ("write_only", Access::WriteOnly),
+        ("read_write", Access::ReadWrite),
+        ("write_once", Access::WriteOnce),
+        ("read_write_once", Access::ReadWriteOnce),
+    ];
+}
+
+#[derive(Debug, Clone)]
+pub enum Check {
+    /// `#[parity(even, over = [a, b])]`, set so that the covered bits and the parity bit hold an
+    /// even (or odd) number of ones.
+    Parity {
+        odd: bool,
+        over: Vec<proc_macro2::Ident>,
+    },
+    /// `#[crc(poly = 0x07, over = [a, b])]`, a CRC as wide as the field over the covered fields,
+    /// taken most significant bit first in the order they are listed.
+    Crc {
+        poly: u128,
+        init: u128,
+        over: Vec<proc_macro2::Ident>,
+    },
+}
+
+impl Check {
+    pub fn over(&self) -> &[proc_macro2::Ident] {
+        match self {
+            Self::Parity { over, .. } | Self::Crc { over, .. } => over,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Packed BCD, one decimal digit per nibble.
+    Bcd,
+    /// Reflected binary Gray code.
+    Gray,
+    /// The raw bits plus a constant, `#[encoding(offset = -40)]`.
+    Offset(i128),
+    /// A single set bit, whose index is the value.
+    OneHot,
+}
+
+/// A field holding `bits * factor + offset`.
+#[derive(Debug, Clone, Copy)]
+pub struct Scale {
+    pub factor: f64,
+    pub offset: f64,
+}
+
+/// The bit ranges a split field is assembled from, most significant first, followed by `shift`
+/// implied zero bits.
+#[derive(Debug, Clone)]
+pub struct SplitBits {
+    pub segments: Vec<BitRange>,
+    pub shift: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BitRange {
+    pub start: usize,
+    pub size: usize,
+}
+
+impl SpecField {
+    /// The bits of the container occupied by the field.
+    pub fn bit_ranges(&self) -> Vec<BitRange> {
+        match &self.split {
+            Some(split) => split.segments.clone(),
+            None => vec![BitRange {
+                start: self.start,
+                size: self.size,
+            }],
+        }
+    }
+
+    /// One past the highest bit occupied by the field.
+    pub fn end(&self) -> usize {
+        self.bit_ranges()
+            .iter()
+            .map(|r| r.start + r.size)
+            .max()
+            .unwrap_or(self.start)
+    }
+
+    /// Whether the field has setters, which fixed, reserved and check fields don't.
+    pub fn is_settable(&self) -> bool {
+        self.fixed.is_none() && self.reserved.is_none() && self.check.is_none()
+    }
+
+    /// Whether the field's raw bits are read and written through `_bits` accessors, leaving the
+    /// plain names to the enum or real value they stand for.
+    pub fn has_raw_accessors(&self) -> bool {
+        self.enum_ty.is_some() || self.scale.is_some() || self.encoding.is_some()
+    }
+
+    /// The bits of the container that hold `value` in this field.
+    pub fn place(&self, value: u128) -> u128 {
+        match &self.split {
+            None => value << self.start,
+            Some(split) => {
+                let mut offset = split.shift;
+                let mut placed = 0;
+                for r in split.segments.iter().rev() {
+                    placed |= ((value >> offset) & (u128::MAX >> (128 - r.size))) << r.start;
+                    offset += r.size;
+                }
+                placed
+            }
+        }
+    }
+
+    /// The value of the field held by the bits `raw` of the container, the inverse of `place`.
+    pub fn extract(&self, raw: u128) -> u128 {
+        match &self.split {
+            None => (raw >> self.start) & (u128::MAX >> (128 - self.size)),
+            Some(split) => {
+                let mut offset = split.shift;
+                let mut value = 0;
+                for r in split.segments.iter().rev() {
+                    value |= ((raw >> r.start) & (u128::MAX >> (128 - r.size))) << offset;
+                    offset += r.size;
+                }
+                value
+            }
+        }
+    }
+
+    pub fn overlaps(&self, other: &SpecField) -> Option<BitRange> {
+        self.bit_ranges().into_iter().find(|r| {
+            other
+                .bit_ranges()
+                .iter()
+                .any(|o| r.start < o.start + o.size && o.start < r.start + r.size)
+        })
+    }
+}
+
+/// How a spec is represented when the `serde` feature of `dials` is enabled, chosen with
+/// `#[serde(map)]` or `#[serde(raw)]` on the struct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SerdeRepr {
+    /// A map of named fields for human-readable formats, the raw integer otherwise.
+    Auto,
+    Map,
+    Raw,
 }
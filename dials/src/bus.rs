@@ -0,0 +1,125 @@
+//! The backends that the accessors generated by `register_block!` read and write registers
+//! through.
+//!
+//! [`Mmio`] accesses memory-mapped registers with volatile reads and writes, while
+//! [`MockBus`](crate::mock::MockBus) keeps them in RAM for testing driver code without hardware.
+
+use crate::FieldValue;
+
+/// A value that can be held by a register of a block: a `spec!` struct or an unsigned integer.
+pub trait Register: Sized {
+    fn from_raw(raw: u128) -> Self;
+    fn to_raw(&self) -> u128;
+
+    /// The fields of `raw` and their values, for logging accesses. Plain integers have none.
+    fn fields(raw: u128) -> Vec<(&'static str, FieldValue)> {
+        let _ = raw;
+        vec![]
+    }
+}
+
+macro_rules! integer_register {
+    ($($ty:ty),*) => {
+        $(
+            impl Register for $ty {
+                fn from_raw(raw: u128) -> Self {
+                    raw as $ty
+                }
+
+                fn to_raw(&self) -> u128 {
+                    *self as u128
+                }
+            }
+        )*
+    };
+}
+
+integer_register!(u8, u16, u32, u64, u128);
+
+/// A single register read or write made by a generated accessor.
+#[derive(Debug, Clone, Copy)]
+pub struct Access {
+    /// The name of the register within its block.
+    pub register: &'static str,
+    /// The element of a register array being accessed.
+    pub index: Option<usize>,
+    /// The byte offset of the register from the start of the block.
+    pub offset: usize,
+    /// The width of the register in bytes.
+    pub bytes: usize,
+    /// Decodes the fields of a value of the register, see [`Register::fields`].
+    pub fields: fn(u128) -> Vec<(&'static str, FieldValue)>,
+}
+
+/// Where the registers of a block live.
+pub trait Bus {
+    /// Reads the register described by `access`.
+    fn read(&self, access: Access) -> u128;
+    /// Writes `value` to the register described by `access`.
+    fn write(&self, access: Access, value: u128);
+}
+
+impl<B: Bus + ?Sized> Bus for &B {
+    fn read(&self, access: Access) -> u128 {
+        (**self).read(access)
+    }
+
+    fn write(&self, access: Access, value: u128) {
+        (**self).write(access, value)
+    }
+}
+
+/// Registers mapped into memory at `base`, accessed with volatile reads and writes.
+#[derive(Debug, Clone, Copy)]
+pub struct Mmio {
+    base: *mut u8,
+}
+
+impl Mmio {
+    /// A bus over the block of registers at `base`.
+    ///
+    /// # Safety
+    ///
+    /// `base` must be valid for volatile reads and writes of every register of the block, each
+    /// aligned for its width, for as long as the bus or any copy of it is used.
+    pub const unsafe fn new(base: *mut u8) -> Self {
+        Self { base }
+    }
+
+    /// The address of the block.
+    pub const fn base(&self) -> *mut u8 {
+        self.base
+    }
+}
+
+impl Bus for Mmio {
+    fn read(&self, access: Access) -> u128 {
+        // SAFETY: `new` requires the register to be valid for volatile reads.
+        unsafe {
+            let ptr = self.base.add(access.offset);
+            match access.bytes {
+                1 => u128::from(ptr.read_volatile()),
+                2 => u128::from(ptr.cast::<u16>().read_volatile()),
+                4 => u128::from(ptr.cast::<u32>().read_volatile()),
+                8 => u128::from(ptr.cast::<u64>().read_volatile()),
+                16 => ptr.cast::<u128>().read_volatile(),
+                bytes => panic!("unsupported register width of {bytes} bytes"),
+            }
+        }
+    }
+
+    fn write(&self, access: Access, value: u128) {
+        // SAFETY: `new` requires the register to be valid for volatile writes.
+        unsafe {
+            let ptr = self.base.add(access.offset);
+            match access.bytes {
+                1 => ptr.write_volatile(value as u8),
+                2 => ptr.cast::<u16>().write_volatile(value as u16),
+                4 => ptr.cast::<u32>().write_volatile(value as u32),
+                8 => ptr.cast::<u64>().write_volatile(value as u64),
+                16 => ptr.cast::<u128>().write_volatile(value),
+                bytes => panic!("unsupported register width of {bytes} bytes"),
+            }
+        }
+    }
+}
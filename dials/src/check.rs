@@ -0,0 +1,19 @@
+//! Support code for the checksum fields generated by `spec!`.
+
+/// A `width`-bit CRC over `message`, a list of `(value, bits)` pairs fed in order, each most
+/// significant bit first. The CRC is neither reflected nor XORed on output.
+pub fn crc(message: &[(u128, usize)], width: usize, poly: u128, init: u128) -> u128 {
+    let mask = u128::MAX >> (128 - width);
+    let top = 1 << (width - 1);
+    let mut crc = init;
+    for &(value, bits) in message {
+        for bit in (0..bits).rev() {
+            let feedback = (crc & top != 0) != ((value >> bit) & 1 == 1);
+            crc = (crc << 1) & mask;
+            if feedback {
+                crc ^= poly;
+            }
+        }
+    }
+    crc
+}
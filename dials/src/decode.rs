@@ -0,0 +1,131 @@
+//! Support code for the decoders generated by `decoder!`.
+//!
+//! Every entry of a decoder is a spec with fixed fields, so it matches a raw word when the word
+//! holds `MATCH_VALUE` within `MATCH_MASK`. The bits fixed by every entry are used as a key to
+//! jump straight to the entries that could match, which are then checked in declaration order.
+
+/// The `(MATCH_MASK, MATCH_VALUE)` pairs of the entries of a decoder, sorted by the value of the
+/// bits they all fix.
+#[doc(hidden)]
+pub struct Table<const N: usize> {
+    common: u128,
+    keys: [u128; N],
+    order: [usize; N],
+}
+
+impl<const N: usize> Table<N> {
+    pub const fn new(patterns: &[(u128, u128); N]) -> Self {
+        let mut common = u128::MAX;
+        let mut idx = 0;
+        while idx < N {
+            common &= patterns[idx].0;
+            idx += 1;
+        }
+
+        let mut keys = [0; N];
+        let mut order = [0; N];
+        idx = 0;
+        while idx < N {
+            keys[idx] = patterns[idx].1 & common;
+            order[idx] = idx;
+            idx += 1;
+        }
+
+        // Insertion sort by key, which keeps entries with equal keys in declaration order.
+        idx = 1;
+        while idx < N {
+            let mut j = idx;
+            while j > 0 && keys[j - 1] > keys[j] {
+                let key = keys[j - 1];
+                keys[j - 1] = keys[j];
+                keys[j] = key;
+                let entry = order[j - 1];
+                order[j - 1] = order[j];
+                order[j] = entry;
+                j -= 1;
+            }
+            idx += 1;
+        }
+
+        Self {
+            common,
+            keys,
+            order,
+        }
+    }
+
+    /// The entries that could match `raw`, in declaration order.
+    pub fn candidates(&self, raw: u128) -> impl Iterator<Item = usize> + '_ {
+        let key = raw & self.common;
+        let first = self.keys.partition_point(|k| *k < key);
+        self.keys[first..]
+            .iter()
+            .zip(&self.order[first..])
+            .take_while(move |(k, _)| **k == key)
+            .map(|(_, entry)| *entry)
+    }
+}
+
+/// Finds an entry declared before `entry` that gets in its way. Returns the earlier entry and
+/// `true` if it matches every word `entry` does, or `false` if the two only partly overlap.
+///
+/// An earlier entry that fixes more bits than a later one is not a conflict, as listing the more
+/// specific pattern first is how overlapping encodings are meant to be resolved:
+///
+/// ```
+/// dials::spec! {
+///     struct Nop { op: u4 == 1, arg: u4 == 0 }
+///     struct Jump { op: u4 == 1, arg: u4 }
+/// }
+///
+/// dials::decoder! {
+///     enum Insn: u8 { Nop, Jump }
+/// }
+/// ```
+///
+/// `decoder!` fails to compile when an entry can never be decoded, because one listed before it
+/// matches every word it does,
+///
+/// ```compile_fail,E0080
+/// dials::spec! {
+///     struct Nop { op: u4 == 1, arg: u4 == 0 }
+///     struct Jump { op: u4 == 1, arg: u4 }
+/// }
+///
+/// dials::decoder! {
+///     enum Insn: u8 { Jump, Nop }
+/// }
+/// ```
+///
+/// or when two entries match some of the same words without either being more specific:
+///
+/// ```compile_fail,E0080
+/// dials::spec! {
+///     struct Load { op: u4 == 2, reg: u4 }
+///     struct Clear { op: u4, reg: u4 == 0 }
+/// }
+///
+/// dials::decoder! {
+///     enum Insn: u8 { Load, Clear }
+/// }
+/// ```
+#[doc(hidden)]
+pub const fn conflict<const N: usize>(
+    patterns: &[(u128, u128); N],
+    entry: usize,
+) -> Option<(usize, bool)> {
+    let (mask, value) = patterns[entry];
+    let mut idx = 0;
+    while idx < entry {
+        let (earlier_mask, earlier_value) = patterns[idx];
+        let overlap = (earlier_value ^ value) & earlier_mask & mask == 0;
+        if overlap && earlier_mask & mask == earlier_mask {
+            return Some((idx, true));
+        }
+        if overlap && earlier_mask & mask != mask {
+            return Some((idx, false));
+        }
+        idx += 1;
+    }
+    None
+}
@@ -0,0 +1,94 @@
+//! Support code for the `diff` method generated by `spec!`.
+//!
+//! A diff yields one [`FieldChange`] per field whose value differs between two values of a spec,
+//! and renders as `Rbga { red: 18 -> 255, alpha: 0 -> 3 }` with `{:?}`.
+
+use std::fmt;
+
+/// The value of a single field, as returned by its getter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldValue {
+    Bool(bool),
+    Uint(u128),
+    Int(i128),
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => value.fmt(f),
+            Self::Uint(value) => value.fmt(f),
+            Self::Int(value) => value.fmt(f),
+        }
+    }
+}
+
+impl From<bool> for FieldValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+macro_rules! field_value_from {
+    ($variant:ident($wide:ty): $($ty:ty),*) => {
+        $(
+            impl From<$ty> for FieldValue {
+                fn from(value: $ty) -> Self {
+                    Self::$variant(value as $wide)
+                }
+            }
+        )*
+    };
+}
+
+field_value_from!(Uint(u128): u8, u16, u32, u64, u128);
+field_value_from!(Int(i128): i8, i16, i32, i64, i128);
+
+/// A field whose value differs between two values of a spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub old: FieldValue,
+    pub new: FieldValue,
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.old, self.new)
+    }
+}
+
+/// The changed fields of a spec, formatted like a struct of `old -> new` values by `Debug`.
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct Diff<I> {
+    spec: &'static str,
+    changes: I,
+}
+
+impl<I: Iterator<Item = FieldChange> + Clone> Diff<I> {
+    pub fn new(spec: &'static str, changes: I) -> Self {
+        Self { spec, changes }
+    }
+}
+
+impl<I: Iterator<Item = FieldChange>> Iterator for Diff<I> {
+    type Item = FieldChange;
+
+    fn next(&mut self) -> Option<FieldChange> {
+        self.changes.next()
+    }
+}
+
+impl<I: Iterator<Item = FieldChange> + Clone> fmt::Debug for Diff<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = f.debug_struct(self.spec);
+        for change in self.changes.clone() {
+            out.field(
+                change.field,
+                &format_args!("{} -> {}", change.old, change.new),
+            );
+        }
+        out.finish()
+    }
+}
@@ -0,0 +1,56 @@
+//! Support code for fields declared with `#[encoding(..)]`, which are converted between their
+//! raw bits and the value they encode by the generated getters and setters.
+
+/// Reads packed BCD, one decimal digit per nibble.
+pub fn bcd_decode(raw: u128) -> u128 {
+    let mut value = 0;
+    let mut place = 1;
+    let mut raw = raw;
+    while raw != 0 {
+        value += (raw & 0xf) * place;
+        place *= 10;
+        raw >>= 4;
+    }
+    value
+}
+
+/// Packs `value` as BCD, one decimal digit per nibble.
+pub fn bcd_encode(value: u128) -> u128 {
+    let mut raw = 0;
+    let mut shift = 0;
+    let mut value = value;
+    while value != 0 {
+        raw |= (value % 10) << shift;
+        shift += 4;
+        value /= 10;
+    }
+    raw
+}
+
+/// Whether every nibble of `raw` is a decimal digit.
+pub fn bcd_is_valid(raw: u128) -> bool {
+    let mut raw = raw;
+    while raw != 0 {
+        if raw & 0xf > 9 {
+            return false;
+        }
+        raw >>= 4;
+    }
+    true
+}
+
+/// Converts a reflected binary Gray code to the value it encodes.
+pub fn gray_decode(raw: u128) -> u128 {
+    let mut value = raw;
+    let mut shift = 1;
+    while shift < 128 {
+        value ^= value >> shift;
+        shift <<= 1;
+    }
+    value
+}
+
+/// Converts `value` to its reflected binary Gray code.
+pub fn gray_encode(value: u128) -> u128 {
+    value ^ (value >> 1)
+}
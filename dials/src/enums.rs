@@ -0,0 +1,15 @@
+/// An enum held in a field of a spec, declared as `mode: u2 as Mode`.
+///
+/// Only the bit patterns `from_bits` accepts are valid for the field, which `validate` and
+/// `TryFrom<uN>` of the spec check.
+pub trait FieldEnum: Sized {
+    /// The names and discriminants of the variants, for exporting layouts. Enums declared in a
+    /// `spec!` list theirs, others none unless they declare them.
+    const VARIANTS: &'static [(&'static str, u128)] = &[];
+
+    /// The variant with the discriminant `bits`, if any.
+    fn from_bits(bits: u128) -> Option<Self>;
+
+    /// The discriminant of the variant.
+    fn to_bits(&self) -> u128;
+}
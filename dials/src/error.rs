@@ -0,0 +1,56 @@
+use crate::ValidationError;
+
+use std::fmt;
+
+/// A field value that does not fit in the bits the spec gives to that field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeError {
+    pub field: &'static str,
+    pub value: String,
+    pub bits: usize,
+}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "value {} does not fit in {}-bit field `{}`",
+            self.value, self.bits, self.field
+        )
+    }
+}
+
+impl std::error::Error for RangeError {}
+
+/// Why the unpacked `{Name}Fields` of a spec could not be packed back into it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldsError {
+    /// A field value that does not fit in its bits.
+    OutOfRange(RangeError),
+    /// Fields that fit but break a rule of the spec, such as an enum field holding bits that are
+    /// not the discriminant of any variant.
+    Invalid(ValidationError),
+}
+
+impl fmt::Display for FieldsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfRange(err) => err.fmt(f),
+            Self::Invalid(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for FieldsError {}
+
+impl From<RangeError> for FieldsError {
+    fn from(err: RangeError) -> Self {
+        Self::OutOfRange(err)
+    }
+}
+
+impl From<ValidationError> for FieldsError {
+    fn from(err: ValidationError) -> Self {
+        Self::Invalid(err)
+    }
+}
@@ -0,0 +1,157 @@
+//! Declarations of specs for other languages, written from their [`Layout`]s.
+//!
+//! The exporters are meant for build scripts and small tools that depend on the crate declaring
+//! the specs, so that every other language is generated from the one `spec!`:
+//!
+//! ```no_run
+//! dials::spec! {
+//!     /// The control register.
+//!     struct Ctrl {
+//!         enable: bool,
+//!         mode: u3,
+//!     }
+//! }
+//!
+//! dials::export::CHeader::new("ctrl.h")
+//!     .spec(&Ctrl::LAYOUT)
+//!     .bitfields()
+//!     .write("include/ctrl.h")
+//!     .unwrap();
+//! ```
+//!
+//! [`CHeader`] declares specs for firmware in C, and [`SystemVerilog`] and [`Vhdl`] declare them
+//! for hardware as packed structs and records, with every field at the same bits as in the spec.
+//! [`Dissector`] shows specs sent over the wire in Wireshark, and [`Json`] describes them for any
+//! other tool.
+
+mod c;
+mod json;
+mod lua;
+mod sv;
+mod vhdl;
+
+pub use c::CHeader;
+pub use json::Json;
+pub use lua::Dissector;
+pub use sv::SystemVerilog;
+pub use vhdl::Vhdl;
+
+use crate::layout::{BitRange, FieldKind, FieldLayout, Layout};
+
+/// A run of bits of the container of a spec, held by a field or part of a split field, or
+/// unused if `field` is `None`.
+struct Member<'a> {
+    bits: BitRange,
+    field: Option<&'a FieldLayout>,
+    /// The most and least significant bits of the value of a split field held by the run.
+    part: Option<(usize, usize)>,
+}
+
+/// The bits of the container of `layout` from the most significant down, as runs held by each
+/// field other than aliases, or by none.
+fn members(layout: &Layout) -> Vec<Member<'_>> {
+    let mut members = layout
+        .fields
+        .iter()
+        .filter(|field| !field.alias)
+        .flat_map(|field| {
+            field.segments().map(move |(bits, offset)| Member {
+                bits,
+                field: Some(field),
+                part: field.is_split().then_some((offset + bits.size - 1, offset)),
+            })
+        })
+        .collect::<Vec<_>>();
+    members.sort_by_key(|member| member.bits.start);
+
+    let mut all = vec![];
+    let mut bit = 0;
+    for member in members {
+        if member.bits.start > bit {
+            all.push(Member {
+                bits: BitRange {
+                    start: bit,
+                    size: member.bits.start - bit,
+                },
+                field: None,
+                part: None,
+            });
+        }
+        bit = member.bits.start + member.bits.size;
+        all.push(member);
+    }
+    if bit < layout.bits {
+        all.push(Member {
+            bits: BitRange {
+                start: bit,
+                size: layout.bits - bit,
+            },
+            field: None,
+            part: None,
+        });
+    }
+    all.reverse();
+    all
+}
+
+/// An enum with known variants held by a field.
+struct Enum {
+    name: &'static str,
+    /// The width of the first field holding the enum.
+    size: usize,
+    variants: &'static [(&'static str, u128)],
+}
+
+impl Enum {
+    /// The name of the enum without its path.
+    fn short_name(&self) -> &'static str {
+        self.name.rsplit("::").next().unwrap_or(self.name)
+    }
+}
+
+/// The enums with known variants held by the fields of `layouts`.
+fn enums(layouts: &[&'static Layout]) -> Vec<Enum> {
+    let mut enums: Vec<Enum> = vec![];
+    for field in layouts.iter().flat_map(|layout| layout.fields) {
+        if let FieldKind::Enum { name, variants } = field.kind {
+            if !variants.is_empty() && !enums.iter().any(|e| e.name == name) {
+                enums.push(Enum {
+                    name,
+                    size: field.size,
+                    variants,
+                });
+            }
+        }
+    }
+    enums
+}
+
+/// The type of the enum declared for a field, if it is one of `enums` as wide as the field.
+fn enum_of<'a>(enums: &'a [Enum], field: &FieldLayout) -> Option<&'a Enum> {
+    match field.kind {
+        FieldKind::Enum { name, .. } => enums
+            .iter()
+            .find(|e| e.name == name && e.size == field.size),
+        _ => None,
+    }
+}
+
+/// `LinkStatus` as `link_status`.
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (idx, c) in name.char_indices() {
+        let prev = name[..idx].chars().next_back();
+        if c.is_ascii_uppercase()
+            && prev.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit())
+        {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+/// `LinkStatus` or `link_status` as `LINK_STATUS`.
+fn screaming_case(name: &str) -> String {
+    snake_case(name).to_ascii_uppercase()
+}
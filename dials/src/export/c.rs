@@ -0,0 +1,305 @@
+use super::{screaming_case, snake_case};
+use crate::layout::{FieldKind, FieldLayout, Layout};
+
+use std::fmt;
+use std::path::Path;
+
+/// A C header declaring specs with `#define`d masks and shifts and `static inline` accessors,
+/// written by `Display`.
+///
+/// For a spec `Ctrl` with a field `mode`, the header declares `CTRL_RESET`, `CTRL_MODE_MASK`,
+/// `CTRL_MODE_SHIFT`, `CTRL_MODE_WIDTH`, `ctrl_get_mode(reg)` and `ctrl_set_mode(reg, value)`,
+/// along with a C `enum` for each enum with known variants.
+#[derive(Debug, Clone)]
+pub struct CHeader {
+    name: String,
+    layouts: Vec<&'static Layout>,
+    bitfields: bool,
+}
+
+impl CHeader {
+    /// A header to be saved as `name`, such as `uart_regs.h`, from which its include guard is
+    /// made.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            layouts: vec![],
+            bitfields: false,
+        }
+    }
+
+    /// Declares the spec with the layout `layout`, as in `.spec(&Ctrl::LAYOUT)`.
+    pub fn spec(mut self, layout: &'static Layout) -> Self {
+        self.layouts.push(layout);
+        self
+    }
+
+    /// Also declares a C bitfield struct for each spec, in a union with the raw register, and
+    /// asserts that it is as wide as the register. Bitfields are laid out from the least
+    /// significant bit, as GCC and Clang do for little-endian targets. Specs with split fields or
+    /// wider than 64 bits have no bitfield struct.
+    pub fn bitfields(mut self) -> Self {
+        self.bitfields = true;
+        self
+    }
+
+    /// Writes the header to `path`.
+    pub fn write(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+impl fmt::Display for CHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let guard = self
+            .name
+            .chars()
+            .map(|c| match c.is_ascii_alphanumeric() {
+                true => c.to_ascii_uppercase(),
+                false => '_',
+            })
+            .collect::<String>();
+        writeln!(f, "/* {}, generated by dials. Do not edit. */", self.name)?;
+        writeln!(f, "#ifndef {guard}\n#define {guard}\n")?;
+        writeln!(f, "#include <stdbool.h>\n#include <stdint.h>")?;
+
+        for e in super::enums(&self.layouts) {
+            write_enum(f, e.short_name(), e.variants)?;
+        }
+        for layout in &self.layouts {
+            write_spec(f, layout, self.bitfields)?;
+        }
+        writeln!(f, "\n#endif /* {guard} */")
+    }
+}
+
+fn write_enum(f: &mut fmt::Formatter<'_>, name: &str, variants: &[(&str, u128)]) -> fmt::Result {
+    writeln!(f, "\nenum {} {{", ident(&snake_case(name)))?;
+    for (variant, value) in variants {
+        let variant = screaming_case(&format!("{name}{variant}"));
+        writeln!(f, "    {variant} = {value:#x},")?;
+    }
+    writeln!(f, "}};")
+}
+
+fn write_spec(f: &mut fmt::Formatter<'_>, layout: &Layout, bitfields: bool) -> fmt::Result {
+    let prefix = screaming_case(layout.name);
+    let lower = snake_case(layout.name);
+    let reg = uint(layout.bits);
+
+    writeln!(f, "\n/*\n * {}", layout.name)?;
+    if !layout.doc.is_empty() {
+        writeln!(f, " *")?;
+        comment_lines(f, " *", layout.doc)?;
+    }
+    writeln!(f, " */")?;
+    writeln!(
+        f,
+        "#define {prefix}_RESET {}",
+        literal(layout.reset, layout.bits)
+    )?;
+
+    for field in layout.fields {
+        let name = screaming_case(field.name);
+        let value = value_type(field);
+        writeln!(f)?;
+        if !field.doc.is_empty() {
+            writeln!(f, "/*")?;
+            comment_lines(f, " *", field.doc)?;
+            writeln!(f, " */")?;
+        }
+        writeln!(
+            f,
+            "#define {prefix}_{name}_MASK {}",
+            literal(field.mask(), layout.bits)
+        )?;
+        if !field.is_split() {
+            writeln!(f, "#define {prefix}_{name}_SHIFT {}", field.start)?;
+        }
+        writeln!(f, "#define {prefix}_{name}_WIDTH {}", field.size)?;
+        match field.kind {
+            FieldKind::Fixed(v) | FieldKind::Reserved(v) => writeln!(
+                f,
+                "#define {prefix}_{name}_VALUE {}",
+                literal(v, layout.bits)
+            )?,
+            FieldKind::Scaled { factor, offset } => {
+                writeln!(f, "#define {prefix}_{name}_SCALE {factor:?}")?;
+                writeln!(f, "#define {prefix}_{name}_OFFSET {offset:?}")?;
+            }
+            _ => {}
+        }
+
+        // The value of the field, assembled from its segments.
+        let raw = field
+            .segments()
+            .map(|(r, offset)| {
+                let mask = literal(u128::MAX >> (128 - r.size), layout.bits);
+                let segment = match r.start {
+                    0 => format!("(reg & {mask})"),
+                    start => format!("((reg >> {start}) & {mask})"),
+                };
+                match offset {
+                    0 => segment,
+                    offset => format!("({reg}){segment} << {offset}"),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" | ");
+        let get = format!("{lower}_get_{}", ident(field.name));
+        write!(f, "static inline {value} {get}({reg} reg)\n{{\n    ")?;
+        if value == "bool" {
+            writeln!(f, "return (reg & {prefix}_{name}_MASK) != 0;")?;
+        } else if field.signed && field.size < 64 {
+            let sign = literal(1 << (field.size - 1), layout.bits.max(field.size));
+            writeln!(
+                f,
+                "{reg} raw = ({reg})({raw});\n    return ({value})((int64_t)raw - (int64_t)((raw & {sign}) << 1));"
+            )?;
+        } else {
+            writeln!(f, "return ({value})({raw});")?;
+        }
+        writeln!(f, "}}")?;
+
+        if field.is_settable() {
+            let placed = field
+                .segments()
+                .map(|(r, offset)| {
+                    let mask = literal(u128::MAX >> (128 - r.size), layout.bits);
+                    let segment = match offset {
+                        0 => format!("(({reg})value & {mask})"),
+                        offset => format!("((({reg})value >> {offset}) & {mask})"),
+                    };
+                    match r.start {
+                        0 => segment,
+                        start => format!("{segment} << {start}"),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" | ");
+            let set = format!("{lower}_set_{}", ident(field.name));
+            writeln!(
+                f,
+                "static inline {reg} {set}({reg} reg, {value} value)\n{{\n    return ({reg})((reg & ~{prefix}_{name}_MASK) | {placed});\n}}"
+            )?;
+        }
+    }
+
+    if bitfields {
+        write_bitfields(f, layout)?;
+    }
+    Ok(())
+}
+
+/// `struct ctrl_bits` with a bitfield for each field, and `union ctrl` of it and the raw value.
+fn write_bitfields(f: &mut fmt::Formatter<'_>, layout: &Layout) -> fmt::Result {
+    let mut fields = layout
+        .fields
+        .iter()
+        .filter(|field| !field.alias)
+        .collect::<Vec<_>>();
+    if layout.bits > 64 || fields.iter().any(|field| field.is_split()) {
+        return Ok(());
+    }
+    fields.sort_by_key(|field| field.start);
+
+    let lower = snake_case(layout.name);
+    let reg = uint(layout.bits);
+    writeln!(f, "\nstruct {lower}_bits {{")?;
+    let mut bit = 0;
+    for field in fields {
+        if field.start > bit {
+            writeln!(f, "    {reg} : {};", field.start - bit)?;
+        }
+        let ty = match field.signed {
+            true => int(layout.bits),
+            false => reg.clone(),
+        };
+        writeln!(f, "    {ty} {} : {};", ident(field.name), field.size)?;
+        bit = field.start + field.size;
+    }
+    if bit < layout.bits {
+        writeln!(f, "    {reg} : {};", layout.bits - bit)?;
+    }
+    writeln!(f, "}};")?;
+    writeln!(
+        f,
+        "\nunion {lower} {{\n    {reg} raw;\n    struct {lower}_bits bits;\n}};"
+    )?;
+    writeln!(
+        f,
+        "\n_Static_assert(sizeof(struct {lower}_bits) == sizeof({reg}), \"struct {lower}_bits must be as wide as {}\");",
+        layout.name
+    )?;
+    writeln!(
+        f,
+        "_Static_assert(sizeof(union {lower}) == sizeof({reg}), \"union {lower} must be as wide as {}\");",
+        layout.name
+    )
+}
+
+fn comment_lines(f: &mut fmt::Formatter<'_>, leader: &str, text: &str) -> fmt::Result {
+    for line in text.lines() {
+        match line.is_empty() {
+            true => writeln!(f, "{leader}")?,
+            false => writeln!(f, "{leader} {}", line.replace("*/", "* /"))?,
+        }
+    }
+    Ok(())
+}
+
+/// The C type accessors take and return the value of `field` as.
+fn value_type(field: &FieldLayout) -> String {
+    match (field.size, field.signed) {
+        (1, false) if matches!(field.kind, FieldKind::Int) => "bool".to_string(),
+        (size, true) => int(size),
+        (size, false) => uint(size),
+    }
+}
+
+/// The narrowest unsigned C integer of at least `bits` bits.
+fn uint(bits: usize) -> String {
+    match bits {
+        0..=8 => "uint8_t".to_string(),
+        9..=16 => "uint16_t".to_string(),
+        17..=32 => "uint32_t".to_string(),
+        33..=64 => "uint64_t".to_string(),
+        _ => "unsigned __int128".to_string(),
+    }
+}
+
+fn int(bits: usize) -> String {
+    match bits {
+        0..=64 => uint(bits)[1..].to_string(),
+        _ => "__int128".to_string(),
+    }
+}
+
+/// `value` as a C constant of a `bits`-wide register.
+fn literal(value: u128, bits: usize) -> String {
+    match bits {
+        0..=8 => format!("UINT8_C({value:#x})"),
+        9..=16 => format!("UINT16_C({value:#x})"),
+        17..=32 => format!("UINT32_C({value:#x})"),
+        33..=64 => format!("UINT64_C({value:#x})"),
+        _ => format!(
+            "(((unsigned __int128)UINT64_C({:#x}) << 64) | UINT64_C({:#x}))",
+            value >> 64,
+            value as u64
+        ),
+    }
+}
+
+/// `name`, renamed with a trailing underscore if it is a C keyword.
+fn ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "auto", "bool", "break", "case", "char", "const", "continue", "default", "do", "double",
+        "else", "enum", "extern", "false", "float", "for", "goto", "if", "inline", "int", "long",
+        "register", "restrict", "return", "short", "signed", "sizeof", "static", "struct",
+        "switch", "true", "typedef", "union", "unsigned", "void", "volatile", "while",
+    ];
+    match KEYWORDS.contains(&name) {
+        true => format!("{name}_"),
+        false => name.to_string(),
+    }
+}
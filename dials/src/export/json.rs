@@ -0,0 +1,311 @@
+use crate::layout::{Access, Encoding, FieldKind, FieldLayout, Layout};
+
+use std::fmt;
+use std::path::Path;
+
+/// A JSON manifest of the layouts of specs, for register viewers, documentation generators and
+/// test benches, written by `Display`.
+///
+/// The manifest is an object with the `version` of its format, currently 1, and `specs`, an
+/// array with an object for each spec:
+///
+/// ```json
+/// {
+///   "name": "Ctrl",
+///   "doc": "The control register.",
+///   "bits": 16,
+///   "reset": 64,
+///   "fields": [
+///     {
+///       "name": "speed",
+///       "doc": "",
+///       "start": 1,
+///       "size": 2,
+///       "bits": [{ "start": 1, "size": 2 }],
+///       "shift": 0,
+///       "signed": false,
+///       "alias": false,
+///       "access": "read-write",
+///       "reset": 0,
+///       "kind": "enum",
+///       "enum": "Speed",
+///       "variants": [{ "name": "Off", "value": 0 }, { "name": "Fast", "value": 3 }]
+///     }
+///   ],
+///   "variants": null
+/// }
+/// ```
+///
+/// `kind` is one of `int`, `enum`, `scaled` with `factor` and `offset`, `encoded` with
+/// `encoding` (`bcd`, `gray`, `one_hot` or `offset` along with `offset`), `fixed` or `reserved`
+/// with `value`, and `check` with the fields it is `over`. `access` is one of `read-write`,
+/// `read-only`, `write-only`, `write-once` or `read-write-once`, and `reset` the value the field
+/// holds after reset. The `variants` of a tagged spec are an object with the `tag` field and a
+/// `views` array, each with the `name` and `tag_value` of a view and its `layout`, a spec object
+/// of its own. Integers are written in full, even those wider than 53 bits.
+#[derive(Debug, Clone, Default)]
+pub struct Json {
+    layouts: Vec<&'static Layout>,
+}
+
+impl Json {
+    /// A manifest without any specs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Describes the spec with the layout `layout`, as in `.spec(&Ctrl::LAYOUT)`.
+    pub fn spec(mut self, layout: &'static Layout) -> Self {
+        self.layouts.push(layout);
+        self
+    }
+
+    /// Writes the manifest to `path`.
+    pub fn write(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let manifest = Value::Object(vec![
+            ("version", Value::Int(1)),
+            (
+                "specs",
+                Value::Array(self.layouts.iter().map(|layout| spec(layout)).collect()),
+            ),
+        ]);
+        manifest.write(f, 0)?;
+        writeln!(f)
+    }
+}
+
+fn spec(layout: &Layout) -> Value {
+    let variants = match layout.variants {
+        Some(variants) => Value::Object(vec![
+            ("tag", Value::String(variants.tag.to_string())),
+            (
+                "views",
+                Value::Array(
+                    variants
+                        .variants
+                        .iter()
+                        .map(|v| {
+                            Value::Object(vec![
+                                ("name", Value::String(v.name.to_string())),
+                                ("tag_value", Value::Int(v.tag_value)),
+                                ("layout", spec(v.layout)),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+        ]),
+        None => Value::Null,
+    };
+    Value::Object(vec![
+        ("name", Value::String(layout.name.to_string())),
+        ("doc", Value::String(layout.doc.to_string())),
+        ("bits", Value::Int(layout.bits as u128)),
+        ("reset", Value::Int(layout.reset)),
+        (
+            "fields",
+            Value::Array(
+                layout
+                    .fields
+                    .iter()
+                    .map(|f| field(f, layout.reset))
+                    .collect(),
+            ),
+        ),
+        ("variants", variants),
+    ])
+}
+
+fn field(field: &FieldLayout, reset: u128) -> Value {
+    let bits = field
+        .bits
+        .iter()
+        .map(|r| {
+            Value::Object(vec![
+                ("start", Value::Int(r.start as u128)),
+                ("size", Value::Int(r.size as u128)),
+            ])
+        })
+        .collect();
+    let access = match field.access {
+        Access::ReadWrite => "read-write",
+        Access::ReadOnly => "read-only",
+        Access::WriteOnly => "write-only",
+        Access::WriteOnce => "write-once",
+        Access::ReadWriteOnce => "read-write-once",
+    };
+    let mut members = vec![
+        ("name", Value::String(field.name.to_string())),
+        ("doc", Value::String(field.doc.to_string())),
+        ("start", Value::Int(field.start as u128)),
+        ("size", Value::Int(field.size as u128)),
+        ("bits", Value::Array(bits)),
+        ("shift", Value::Int(field.shift as u128)),
+        ("signed", Value::Bool(field.signed)),
+        ("alias", Value::Bool(field.alias)),
+        ("access", Value::String(access.to_string())),
+        ("reset", Value::Int(field.extract(reset))),
+    ];
+
+    let kind = |kind: &str| ("kind", Value::String(kind.to_string()));
+    match field.kind {
+        FieldKind::Int => members.push(kind("int")),
+        FieldKind::Enum { name, variants } => {
+            let variants = variants
+                .iter()
+                .map(|(name, value)| {
+                    Value::Object(vec![
+                        ("name", Value::String(name.to_string())),
+                        ("value", Value::Int(*value)),
+                    ])
+                })
+                .collect();
+            members.extend([
+                kind("enum"),
+                ("enum", Value::String(name.to_string())),
+                ("variants", Value::Array(variants)),
+            ]);
+        }
+        FieldKind::Scaled { factor, offset } => members.extend([
+            kind("scaled"),
+            ("factor", Value::Real(factor)),
+            ("offset", Value::Real(offset)),
+        ]),
+        FieldKind::Encoded(encoding) => {
+            let name = match encoding {
+                Encoding::Bcd => "bcd",
+                Encoding::Gray => "gray",
+                Encoding::OneHot => "one_hot",
+                Encoding::Offset(_) => "offset",
+            };
+            members.extend([
+                kind("encoded"),
+                ("encoding", Value::String(name.to_string())),
+            ]);
+            if let Encoding::Offset(offset) = encoding {
+                members.push(("offset", Value::SignedInt(offset)));
+            }
+        }
+        FieldKind::Fixed(value) => members.extend([kind("fixed"), ("value", Value::Int(value))]),
+        FieldKind::Reserved(value) => {
+            members.extend([kind("reserved"), ("value", Value::Int(value))])
+        }
+        FieldKind::Check { over } => members.extend([
+            kind("check"),
+            (
+                "over",
+                Value::Array(
+                    over.iter()
+                        .map(|name| Value::String(name.to_string()))
+                        .collect(),
+                ),
+            ),
+        ]),
+    }
+    Value::Object(members)
+}
+
+/// A JSON value, with the members of objects in the order they are written.
+enum Value {
+    Null,
+    Bool(bool),
+    Int(u128),
+    SignedInt(i128),
+    Real(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(&'static str, Value)>),
+}
+
+impl Value {
+    /// Writes the value indented by `depth` levels, with arrays of scalars and objects of
+    /// scalars on a single line.
+    fn write(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let indent = "  ".repeat(depth + 1);
+        match self {
+            Self::Null => write!(f, "null"),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Int(n) => write!(f, "{n}"),
+            Self::SignedInt(n) => write!(f, "{n}"),
+            Self::Real(x) => write!(f, "{x:?}"),
+            Self::String(s) => write_string(f, s),
+            Self::Array(items) if items.is_empty() => write!(f, "[]"),
+            Self::Array(items) if items.iter().all(Value::is_flat) => {
+                write!(f, "[")?;
+                for (idx, item) in items.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    item.write(f, depth)?;
+                }
+                write!(f, "]")
+            }
+            Self::Array(items) => {
+                writeln!(f, "[")?;
+                for (idx, item) in items.iter().enumerate() {
+                    write!(f, "{indent}")?;
+                    item.write(f, depth + 1)?;
+                    writeln!(f, "{}", if idx + 1 < items.len() { "," } else { "" })?;
+                }
+                write!(f, "{}]", "  ".repeat(depth))
+            }
+            Self::Object(members) if self.is_flat() => {
+                write!(f, "{{ ")?;
+                for (idx, (name, value)) in members.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write_string(f, name)?;
+                    write!(f, ": ")?;
+                    value.write(f, depth)?;
+                }
+                write!(f, " }}")
+            }
+            Self::Object(members) => {
+                writeln!(f, "{{")?;
+                for (idx, (name, value)) in members.iter().enumerate() {
+                    write!(f, "{indent}")?;
+                    write_string(f, name)?;
+                    write!(f, ": ")?;
+                    value.write(f, depth + 1)?;
+                    writeln!(f, "{}", if idx + 1 < members.len() { "," } else { "" })?;
+                }
+                write!(f, "{}}}", "  ".repeat(depth))
+            }
+        }
+    }
+
+    /// Whether the value is a scalar, or a small object of scalars, written on a single line.
+    fn is_flat(&self) -> bool {
+        match self {
+            Self::Array(_) => false,
+            Self::Object(members) => {
+                members.len() <= 2
+                    && members
+                        .iter()
+                        .all(|(_, v)| !matches!(v, Self::Array(_) | Self::Object(_)))
+            }
+            _ => true,
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "\"")
+}
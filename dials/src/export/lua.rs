@@ -0,0 +1,381 @@
+use super::snake_case;
+use crate::layout::{FieldKind, FieldLayout, Layout};
+
+use std::fmt;
+use std::path::Path;
+
+/// A Wireshark dissector in Lua for a protocol whose packets start with specs, written by
+/// `Display`.
+///
+/// The specs are dissected one after another from the start of the packet, each as a subtree
+/// of the protocol with a `ProtoField` per field, masked to its bits. Enum fields show the
+/// names of their variants, and the fields of the variant selected by the tag of a tagged spec
+/// are shown in a subtree of their own. Split fields are reassembled, and specs wider than
+/// 64 bits are shown as bytes without their fields.
+#[derive(Debug, Clone)]
+pub struct Dissector {
+    proto: String,
+    description: String,
+    layouts: Vec<&'static Layout>,
+    little_endian: bool,
+    ports: Vec<(&'static str, u16)>,
+}
+
+impl Dissector {
+    /// A dissector of the protocol `proto`, as in `link.ctrl.speed` filters, described as
+    /// `description` in the packet tree.
+    pub fn new(proto: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            proto: proto.into(),
+            description: description.into(),
+            layouts: vec![],
+            little_endian: false,
+            ports: vec![],
+        }
+    }
+
+    /// Dissects the spec with the layout `layout` after those before it, as in
+    /// `.spec(&Header::LAYOUT)`.
+    pub fn spec(mut self, layout: &'static Layout) -> Self {
+        self.layouts.push(layout);
+        self
+    }
+
+    /// Reads specs from packets least significant byte first, rather than in network order.
+    pub fn little_endian(mut self) -> Self {
+        self.little_endian = true;
+        self
+    }
+
+    /// Registers the dissector for UDP packets to or from `port`.
+    pub fn udp_port(mut self, port: u16) -> Self {
+        self.ports.push(("udp.port", port));
+        self
+    }
+
+    /// Registers the dissector for TCP packets to or from `port`.
+    pub fn tcp_port(mut self, port: u16) -> Self {
+        self.ports.push(("tcp.port", port));
+        self
+    }
+
+    /// Writes the dissector to `path`, such as a `.lua` file in Wireshark's plugin directory.
+    pub fn write(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    /// `tree:add` or `tree:add_le`.
+    fn add(&self) -> &'static str {
+        match self.little_endian {
+            true => "add_le",
+            false => "add",
+        }
+    }
+}
+
+impl fmt::Display for Dissector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "-- {}, generated by dials. Do not edit.", self.proto)?;
+        writeln!(
+            f,
+            "local proto = Proto({}, {})\nlocal f = proto.fields",
+            string(&self.proto),
+            string(&self.description)
+        )?;
+        writeln!(
+            f,
+            "
+-- The value of `size` bits of `value` from bit `start`, counting from the least significant.
+local function bits(value, start, size)
+    if type(value) == \"number\" then
+        return math.floor(value / 2 ^ start % 2 ^ size)
+    end
+    return value:rshift(start):band(UInt64.max():rshift(64 - size)):tonumber()
+end"
+        )?;
+
+        let enums = super::enums(&self.layouts);
+        for e in &enums {
+            writeln!(f, "\nlocal {}_values = {{", snake_case(e.short_name()))?;
+            for (variant, value) in e.variants {
+                writeln!(f, "    [{value}] = {},", string(variant))?;
+            }
+            writeln!(f, "}}")?;
+        }
+
+        for layout in &self.layouts {
+            let key = snake_case(layout.name);
+            let abbr = format!("{}.{key}", self.proto);
+            writeln!(f)?;
+            write_proto_fields(f, &enums, layout, &key, &abbr, &[])?;
+            for variant in layout.variants.iter().flat_map(|v| v.variants) {
+                let variant_key = snake_case(variant.name);
+                write_proto_fields(
+                    f,
+                    &enums,
+                    variant.layout,
+                    &format!("{key}_{variant_key}"),
+                    &format!("{abbr}.{variant_key}"),
+                    layout.fields,
+                )?;
+            }
+        }
+
+        for layout in &self.layouts {
+            self.write_dissect_fn(f, layout)?;
+        }
+
+        writeln!(f, "\nfunction proto.dissector(buffer, pinfo, tree)")?;
+        writeln!(f, "    pinfo.cols.protocol = proto.name")?;
+        writeln!(f, "    local subtree = tree:add(proto, buffer())")?;
+        writeln!(f, "    local offset = 0")?;
+        for layout in &self.layouts {
+            let key = snake_case(layout.name);
+            writeln!(f, "    offset = dissect_{key}(buffer, offset, subtree)")?;
+        }
+        writeln!(f, "    return offset\nend")?;
+
+        if !self.ports.is_empty() {
+            writeln!(f)?;
+        }
+        for (table, port) in &self.ports {
+            writeln!(f, "DissectorTable.get(\"{table}\"):add({port}, proto)")?;
+        }
+        Ok(())
+    }
+}
+
+impl Dissector {
+    /// `dissect_ctrl(buffer, offset, tree)`, which adds the spec at `offset` to `tree` and returns
+    /// the offset after it.
+    fn write_dissect_fn(&self, f: &mut fmt::Formatter<'_>, layout: &Layout) -> fmt::Result {
+        let key = snake_case(layout.name);
+        let bytes = layout.bits / 8;
+        let add = self.add();
+        writeln!(
+            f,
+            "\nlocal function dissect_{key}(buffer, offset, tree)\n    local range = buffer(offset, {bytes})\n    local subtree = tree:{add}(f.{key}, range)"
+        )?;
+        if layout.bits > 64 {
+            return writeln!(f, "    return offset + {bytes}\nend");
+        }
+
+        // Split fields and the tag of the variants are read from the value of the whole spec.
+        if layout.variants.is_some() || layout.fields.iter().any(|field| field.is_split()) {
+            let read = match (self.little_endian, layout.bits) {
+                (false, 64) => "uint64",
+                (true, 64) => "le_uint64",
+                (false, _) => "uint",
+                (true, _) => "le_uint",
+            };
+            writeln!(f, "    local value = range:{read}()")?;
+        }
+
+        self.write_adds(f, layout, &key, &[], "    ")?;
+        if let Some(variants) = layout.variants {
+            if let Some(tag) = layout.field(variants.tag) {
+                writeln!(
+                    f,
+                    "    local tag = bits(value, {}, {})",
+                    tag.start, tag.size
+                )?;
+            }
+            for (idx, variant) in variants.variants.iter().enumerate() {
+                let branch = if idx == 0 { "if" } else { "elseif" };
+                writeln!(f, "    {branch} tag == {} then", variant.tag_value)?;
+                writeln!(
+                    f,
+                    "        local variant = subtree:add(range, {})",
+                    string(variant.name)
+                )?;
+                let variant_key = format!("{key}_{}", snake_case(variant.name));
+                self.write_adds(f, variant.layout, &variant_key, layout.fields, "        ")?;
+            }
+            if !variants.variants.is_empty() {
+                writeln!(f, "    end")?;
+            }
+        }
+        writeln!(f, "    return offset + {bytes}\nend")
+    }
+
+    /// Adds the fields of `layout` other than those in `skip` to the subtree, or to the variant
+    /// subtree if `skip` holds the fields of the tagged spec.
+    fn write_adds(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        layout: &Layout,
+        key: &str,
+        skip: &[FieldLayout],
+        indent: &str,
+    ) -> fmt::Result {
+        let tree = if skip.is_empty() {
+            "subtree"
+        } else {
+            "variant"
+        };
+        let add = self.add();
+        for field in fields(layout, skip) {
+            let name = format!("{key}_{}", field.name);
+            if !field.is_split() {
+                writeln!(f, "{indent}{tree}:{add}(f.{name}, range)")?;
+                continue;
+            }
+            let parts = field
+                .segments()
+                .map(|(r, offset)| match offset {
+                    0 => format!("bits(value, {}, {})", r.start, r.size),
+                    offset => format!("bits(value, {}, {}) * {}", r.start, r.size, 1u64 << offset),
+                })
+                .collect::<Vec<_>>()
+                .join(" + ");
+            writeln!(f, "{indent}do\n{indent}    local v = {parts}")?;
+            if field.signed {
+                writeln!(
+                    f,
+                    "{indent}    if v >= {} then\n{indent}        v = v - {}\n{indent}    end",
+                    1u64 << (field.size - 1),
+                    1u128 << field.size,
+                )?;
+            }
+            writeln!(f, "{indent}    {tree}:add(f.{name}, range, v)\n{indent}end")?;
+        }
+        Ok(())
+    }
+}
+
+/// The `ProtoField`s of `layout` and its fields other than those in `skip`, keyed `{key}` and
+/// `{key}_{field}` in the fields of the protocol.
+fn write_proto_fields(
+    f: &mut fmt::Formatter<'_>,
+    enums: &[super::Enum],
+    layout: &Layout,
+    key: &str,
+    abbr: &str,
+    skip: &[FieldLayout],
+) -> fmt::Result {
+    if skip.is_empty() {
+        let spec_field = match layout.bits {
+            bits @ ..=64 => format!("uint{bits}"),
+            _ => "bytes".to_string(),
+        };
+        let base = match layout.bits {
+            ..=64 => ", base.HEX",
+            _ => "",
+        };
+        writeln!(
+            f,
+            "f.{key} = ProtoField.{spec_field}({}, {}{base}{})",
+            string(abbr),
+            string(layout.name),
+            desc_arg(layout.doc, ", nil, nil"),
+        )?;
+    }
+    if layout.bits > 64 {
+        return Ok(());
+    }
+
+    for field in fields(layout, skip) {
+        let field_abbr = format!("{abbr}.{}", field.name);
+        let mask = match field.is_split() {
+            true => "nil".to_string(),
+            false => mask(field.mask(), layout.bits),
+        };
+        let mut desc = field.doc.to_string();
+        let note = match field.kind {
+            FieldKind::Fixed(value) => Some(format!("Always {value:#x}.")),
+            FieldKind::Reserved(value) => Some(format!("Reserved, {value:#x}.")),
+            FieldKind::Scaled { factor, offset } => Some(format!(
+                "The raw value, scaled by {factor:?} with an offset of {offset:?}."
+            )),
+            FieldKind::Encoded(encoding) => {
+                Some(format!("The raw value, encoded as {encoding:?}."))
+            }
+            FieldKind::Check { over } => Some(format!("Checks {}.", over.join(", "))),
+            FieldKind::Int | FieldKind::Enum { .. } => None,
+        };
+        if let Some(note) = note {
+            if !desc.is_empty() {
+                desc.push('\n');
+            }
+            desc.push_str(&note);
+        }
+
+        let name = string(field.name);
+        if field.size == 1 && field.kind == FieldKind::Int {
+            writeln!(
+                f,
+                "f.{key}_{} = ProtoField.bool({}, {name}, {}, nil, {mask}{})",
+                field.name,
+                string(&field_abbr),
+                layout.bits,
+                desc_arg(&desc, ""),
+            )?;
+            continue;
+        }
+        let ty = match field.signed {
+            true => format!("int{}", layout.bits),
+            false => format!("uint{}", layout.bits),
+        };
+        let (base, values) = match (field.kind, super::enum_of(enums, field)) {
+            (_, Some(e)) => ("base.DEC", format!("{}_values", snake_case(e.short_name()))),
+            (FieldKind::Int | FieldKind::Scaled { .. }, None) => ("base.DEC", "nil".to_string()),
+            _ => ("base.HEX", "nil".to_string()),
+        };
+        writeln!(
+            f,
+            "f.{key}_{} = ProtoField.{ty}({}, {name}, {base}, {values}, {mask}{})",
+            field.name,
+            string(&field_abbr),
+            desc_arg(&desc, ""),
+        )?;
+    }
+    Ok(())
+}
+
+/// The fields of `layout` shown in the packet tree, leaving out aliases and those in `skip`.
+fn fields<'a>(
+    layout: &'a Layout,
+    skip: &'a [FieldLayout],
+) -> impl Iterator<Item = &'a FieldLayout> + 'a {
+    layout
+        .fields
+        .iter()
+        .filter(move |field| !field.alias && !skip.iter().any(|s| s.name == field.name))
+}
+
+/// `mask` as a Lua value for a `bits`-wide `ProtoField`, a `UInt64` if it might not fit in the
+/// integers of older Lua versions.
+fn mask(mask: u128, bits: usize) -> String {
+    match bits {
+        ..=32 => format!("{mask:#0width$x}", width = bits / 4 + 2),
+        _ => format!(
+            "UInt64({:#010x}, {:#010x})",
+            mask as u32,
+            (mask >> 32) as u32
+        ),
+    }
+}
+
+/// The description argument of a `ProtoField`, after the arguments `skipped` before it, or
+/// nothing without one.
+fn desc_arg(desc: &str, skipped: &str) -> String {
+    match desc.is_empty() {
+        true => String::new(),
+        false => format!("{skipped}, {}", string(desc)),
+    }
+}
+
+/// `text` as a Lua string literal.
+fn string(text: &str) -> String {
+    let mut string = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => string.push_str("\\\""),
+            '\\' => string.push_str("\\\\"),
+            '\n' => string.push_str("\\n"),
+            c => string.push(c),
+        }
+    }
+    string.push('"');
+    string
+}
@@ -0,0 +1,212 @@
+use super::{screaming_case, snake_case};
+use crate::layout::{FieldLayout, Layout};
+
+use std::fmt;
+use std::path::Path;
+
+/// A SystemVerilog package declaring specs as `typedef struct packed`, written by `Display`.
+///
+/// A spec `Ctrl` becomes `ctrl_t`, with its fields as members from the most significant down so
+/// that each sits at the same bits as in the spec, and unused bits as `unusedN` members. The
+/// parts of split fields are members named after the bits of the value they hold, as `imm_10_5`,
+/// and `ctrl_imm(r)` reassembles the value. Alias fields are left out. The package also declares
+/// `CTRL_RESET`, and a `typedef enum` for each enum with known variants.
+#[derive(Debug, Clone)]
+pub struct SystemVerilog {
+    package: String,
+    layouts: Vec<&'static Layout>,
+}
+
+impl SystemVerilog {
+    /// A package called `package`, such as `uart_regs_pkg`.
+    pub fn new(package: impl Into<String>) -> Self {
+        Self {
+            package: package.into(),
+            layouts: vec![],
+        }
+    }
+
+    /// Declares the spec with the layout `layout`, as in `.spec(&Ctrl::LAYOUT)`.
+    pub fn spec(mut self, layout: &'static Layout) -> Self {
+        self.layouts.push(layout);
+        self
+    }
+
+    /// Writes the package to `path`.
+    pub fn write(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+impl fmt::Display for SystemVerilog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "// {}, generated by dials. Do not edit.", self.package)?;
+        writeln!(f, "package {};", self.package)?;
+
+        let enums = super::enums(&self.layouts);
+        for e in &enums {
+            let (size, prefix) = (e.size, screaming_case(e.short_name()));
+            writeln!(f, "\n    typedef enum logic [{}:0] {{", size - 1)?;
+            for (idx, (variant, value)) in e.variants.iter().enumerate() {
+                let comma = if idx + 1 < e.variants.len() { "," } else { "" };
+                let variant = screaming_case(variant);
+                writeln!(f, "        {prefix}_{variant} = {size}'h{value:x}{comma}")?;
+            }
+            writeln!(f, "    }} {}_e;", snake_case(e.short_name()))?;
+        }
+
+        for layout in &self.layouts {
+            let lower = snake_case(layout.name);
+            writeln!(f, "\n    // {}", layout.name)?;
+            if !layout.doc.is_empty() {
+                writeln!(f, "    //")?;
+                comment_lines(f, "    //", layout.doc)?;
+            }
+            writeln!(f, "    typedef struct packed {{")?;
+            for member in super::members(layout) {
+                let field = match member.field {
+                    Some(field) => field,
+                    None => {
+                        let ty = logic(member.bits.size, false);
+                        writeln!(f, "        {ty} unused{};", member.bits.start)?;
+                        continue;
+                    }
+                };
+                if let Some((hi, lo)) = member.part {
+                    let ty = logic(member.bits.size, false);
+                    let name = ident(field.name);
+                    match hi == lo {
+                        true => writeln!(f, "        {ty} {name}_{hi};")?,
+                        false => writeln!(f, "        {ty} {name}_{hi}_{lo};")?,
+                    }
+                    continue;
+                }
+                for line in field.doc.lines() {
+                    writeln!(f, "        // {line}")?;
+                }
+                let ty = match super::enum_of(&enums, field) {
+                    Some(e) => format!("{}_e", snake_case(e.short_name())),
+                    None => logic(field.size, field.signed),
+                };
+                writeln!(f, "        {ty} {};", ident(field.name))?;
+            }
+            writeln!(f, "    }} {lower}_t;")?;
+            writeln!(
+                f,
+                "\n    localparam {lower}_t {}_RESET = {}'h{:x};",
+                screaming_case(layout.name),
+                layout.bits,
+                layout.reset
+            )?;
+
+            for field in layout.fields.iter().filter(|f| !f.alias && f.is_split()) {
+                write_split_getter(f, &lower, field)?;
+            }
+        }
+        writeln!(f, "\nendpackage")
+    }
+}
+
+/// `ctrl_imm(r)`, the value of the split field `imm` reassembled from its parts.
+fn write_split_getter(f: &mut fmt::Formatter<'_>, lower: &str, field: &FieldLayout) -> fmt::Result {
+    let name = ident(field.name);
+    let mut parts = field
+        .segments()
+        .map(|(r, offset)| match r.size {
+            1 => format!("r.{name}_{offset}"),
+            size => format!("r.{name}_{}_{offset}", offset + size - 1),
+        })
+        .collect::<Vec<_>>();
+    if field.shift > 0 {
+        parts.insert(0, format!("{}'b0", field.shift));
+    }
+    parts.reverse();
+    writeln!(
+        f,
+        "\n    function automatic {} {lower}_{name}({lower}_t r);\n        return {{{}}};\n    endfunction",
+        logic(field.size, field.signed),
+        parts.join(", ")
+    )
+}
+
+fn comment_lines(f: &mut fmt::Formatter<'_>, leader: &str, text: &str) -> fmt::Result {
+    for line in text.lines() {
+        match line.is_empty() {
+            true => writeln!(f, "{leader}")?,
+            false => writeln!(f, "{leader} {line}")?,
+        }
+    }
+    Ok(())
+}
+
+fn logic(size: usize, signed: bool) -> String {
+    match (size, signed) {
+        (1, false) => "logic".to_string(),
+        (size, false) => format!("logic [{}:0]", size - 1),
+        (size, true) => format!("logic signed [{}:0]", size - 1),
+    }
+}
+
+/// `name`, renamed with a trailing underscore if it is a SystemVerilog keyword.
+fn ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "always",
+        "and",
+        "assign",
+        "begin",
+        "bit",
+        "buf",
+        "byte",
+        "case",
+        "class",
+        "const",
+        "default",
+        "do",
+        "edge",
+        "else",
+        "end",
+        "enum",
+        "event",
+        "final",
+        "for",
+        "force",
+        "function",
+        "if",
+        "initial",
+        "inout",
+        "input",
+        "int",
+        "integer",
+        "interface",
+        "logic",
+        "longint",
+        "module",
+        "not",
+        "or",
+        "output",
+        "package",
+        "parameter",
+        "real",
+        "reg",
+        "return",
+        "shortint",
+        "signed",
+        "string",
+        "struct",
+        "task",
+        "time",
+        "type",
+        "typedef",
+        "union",
+        "unsigned",
+        "var",
+        "void",
+        "while",
+        "wire",
+        "xor",
+    ];
+    match KEYWORDS.contains(&name) {
+        true => format!("{name}_"),
+        false => name.to_string(),
+    }
+}
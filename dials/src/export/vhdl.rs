@@ -0,0 +1,302 @@
+use super::{screaming_case, snake_case};
+use crate::layout::{FieldLayout, Layout};
+
+use std::fmt;
+use std::path::Path;
+
+/// A VHDL package declaring specs as records, with functions converting them from and to
+/// `std_logic_vector`, written by `Display`.
+///
+/// A spec `Ctrl` becomes the record `ctrl_t`, with its fields in the order they are declared,
+/// along with `to_ctrl(v)` and an overload of `to_slv(r)` that place each field at the same bits
+/// as in the spec. Single bits are `std_logic`, signed fields `signed` and others
+/// `std_logic_vector`. Alias fields are left out. The package also declares `CTRL_RESET`, and
+/// for each enum with known variants a subtype with a constant for each variant.
+#[derive(Debug, Clone)]
+pub struct Vhdl {
+    package: String,
+    layouts: Vec<&'static Layout>,
+}
+
+impl Vhdl {
+    /// A package called `package`, such as `uart_regs_pkg`.
+    pub fn new(package: impl Into<String>) -> Self {
+        Self {
+            package: package.into(),
+            layouts: vec![],
+        }
+    }
+
+    /// Declares the spec with the layout `layout`, as in `.spec(&Ctrl::LAYOUT)`.
+    pub fn spec(mut self, layout: &'static Layout) -> Self {
+        self.layouts.push(layout);
+        self
+    }
+
+    /// Writes the package to `path`.
+    pub fn write(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+impl fmt::Display for Vhdl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let package = &self.package;
+        writeln!(f, "-- {package}, generated by dials. Do not edit.")?;
+        writeln!(
+            f,
+            "library ieee;\nuse ieee.std_logic_1164.all;\nuse ieee.numeric_std.all;"
+        )?;
+        writeln!(f, "\npackage {package} is")?;
+
+        let enums = super::enums(&self.layouts);
+        for e in &enums {
+            let (size, short) = (e.size, e.short_name());
+            let ty = format!("{}_t", snake_case(short));
+            writeln!(
+                f,
+                "\n    subtype {ty} is std_logic_vector({} downto 0);",
+                size - 1
+            )?;
+            for (variant, value) in e.variants {
+                let variant = screaming_case(&format!("{short}{variant}"));
+                writeln!(f, "    constant {variant} : {ty} := \"{value:0size$b}\";")?;
+            }
+        }
+
+        for layout in &self.layouts {
+            let lower = snake_case(layout.name);
+            writeln!(f, "\n    -- {}", layout.name)?;
+            if !layout.doc.is_empty() {
+                writeln!(f, "    --")?;
+                comment_lines(f, "    --", layout.doc)?;
+            }
+            writeln!(f, "    type {lower}_t is record")?;
+            for field in layout.fields.iter().filter(|field| !field.alias) {
+                for line in field.doc.lines() {
+                    writeln!(f, "        -- {line}")?;
+                }
+                let ty = match super::enum_of(&enums, field) {
+                    Some(e) => format!("{}_t", snake_case(e.short_name())),
+                    None => field_type(field),
+                };
+                writeln!(f, "        {} : {ty};", ident(field.name))?;
+            }
+            writeln!(f, "    end record;")?;
+            writeln!(
+                f,
+                "\n    constant {}_RESET : std_logic_vector({} downto 0) := x\"{:0digits$x}\";",
+                screaming_case(layout.name),
+                layout.bits - 1,
+                layout.reset,
+                digits = layout.bits / 4,
+            )?;
+            writeln!(
+                f,
+                "    function to_{lower}(v : std_logic_vector({} downto 0)) return {lower}_t;",
+                layout.bits - 1
+            )?;
+            writeln!(
+                f,
+                "    function to_slv(r : {lower}_t) return std_logic_vector;"
+            )?;
+        }
+        writeln!(f, "\nend package;")?;
+
+        writeln!(f, "\npackage body {package} is")?;
+        for layout in &self.layouts {
+            write_conversions(f, layout)?;
+        }
+        writeln!(f, "\nend package body;")
+    }
+}
+
+/// The bodies of `to_ctrl` and `to_slv`, copying each field, or each part of a split field, to
+/// and from its bits.
+fn write_conversions(f: &mut fmt::Formatter<'_>, layout: &Layout) -> fmt::Result {
+    let lower = snake_case(layout.name);
+    let top = layout.bits - 1;
+    let fields = layout.fields.iter().filter(|field| !field.alias);
+
+    let mut to_record = vec![];
+    let mut to_slv = vec![];
+    for field in fields {
+        let name = ident(field.name);
+        if field.size == 1 {
+            to_record.push(format!("r.{name} := v({});", field.start));
+            to_slv.push(format!("v({}) := r.{name};", field.start));
+            continue;
+        }
+        for (bits, offset) in field.segments() {
+            let container = match bits.size {
+                1 => format!("v({})", bits.start),
+                size => format!("v({} downto {})", bits.start + size - 1, bits.start),
+            };
+            let value = match (bits.size, field.is_split()) {
+                (_, false) => format!("r.{name}"),
+                (1, true) => format!("r.{name}({offset})"),
+                (size, true) => format!("r.{name}({} downto {offset})", offset + size - 1),
+            };
+            match (bits.size, field.signed) {
+                (1, _) | (_, false) => {
+                    to_record.push(format!("{value} := {container};"));
+                    to_slv.push(format!("{container} := {value};"));
+                }
+                (_, true) => {
+                    to_record.push(format!("{value} := signed({container});"));
+                    to_slv.push(format!("{container} := std_logic_vector({value});"));
+                }
+            }
+        }
+        if field.shift > 0 {
+            to_record.push(format!(
+                "r.{name}({} downto 0) := (others => '0');",
+                field.shift - 1
+            ));
+        }
+    }
+
+    writeln!(
+        f,
+        "\n    function to_{lower}(v : std_logic_vector({top} downto 0)) return {lower}_t is\n        variable r : {lower}_t;\n    begin"
+    )?;
+    for line in to_record {
+        writeln!(f, "        {line}")?;
+    }
+    writeln!(f, "        return r;\n    end function;")?;
+
+    writeln!(
+        f,
+        "\n    function to_slv(r : {lower}_t) return std_logic_vector is\n        variable v : std_logic_vector({top} downto 0) := (others => '0');\n    begin"
+    )?;
+    for line in to_slv {
+        writeln!(f, "        {line}")?;
+    }
+    writeln!(f, "        return v;\n    end function;")
+}
+
+fn comment_lines(f: &mut fmt::Formatter<'_>, leader: &str, text: &str) -> fmt::Result {
+    for line in text.lines() {
+        match line.is_empty() {
+            true => writeln!(f, "{leader}")?,
+            false => writeln!(f, "{leader} {line}")?,
+        }
+    }
+    Ok(())
+}
+
+fn field_type(field: &FieldLayout) -> String {
+    match (field.size, field.signed) {
+        (1, _) => "std_logic".to_string(),
+        (size, false) => format!("std_logic_vector({} downto 0)", size - 1),
+        (size, true) => format!("signed({} downto 0)", size - 1),
+    }
+}
+
+/// `name`, renamed with a suffix if it is a VHDL keyword, since identifiers cannot end in an
+/// underscore.
+fn ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "abs",
+        "access",
+        "after",
+        "alias",
+        "all",
+        "and",
+        "architecture",
+        "array",
+        "assert",
+        "attribute",
+        "begin",
+        "block",
+        "body",
+        "buffer",
+        "bus",
+        "case",
+        "component",
+        "configuration",
+        "constant",
+        "disconnect",
+        "downto",
+        "else",
+        "elsif",
+        "end",
+        "entity",
+        "exit",
+        "file",
+        "for",
+        "function",
+        "generate",
+        "generic",
+        "group",
+        "guarded",
+        "if",
+        "impure",
+        "in",
+        "inertial",
+        "inout",
+        "is",
+        "label",
+        "library",
+        "linkage",
+        "literal",
+        "loop",
+        "map",
+        "mod",
+        "nand",
+        "new",
+        "next",
+        "nor",
+        "not",
+        "null",
+        "of",
+        "on",
+        "open",
+        "or",
+        "others",
+        "out",
+        "package",
+        "port",
+        "postponed",
+        "procedure",
+        "process",
+        "pure",
+        "range",
+        "record",
+        "register",
+        "reject",
+        "rem",
+        "report",
+        "return",
+        "rol",
+        "ror",
+        "select",
+        "severity",
+        "signal",
+        "shared",
+        "sla",
+        "sll",
+        "sra",
+        "srl",
+        "subtype",
+        "then",
+        "to",
+        "transport",
+        "type",
+        "unaffected",
+        "units",
+        "until",
+        "use",
+        "variable",
+        "wait",
+        "when",
+        "while",
+        "with",
+        "xnor",
+        "xor",
+    ];
+    match KEYWORDS.contains(&name) {
+        true => format!("{name}_field"),
+        false => name.to_string(),
+    }
+}
@@ -0,0 +1,151 @@
+//! Descriptions of the bits of specs, for tools outside Rust.
+//!
+//! Every `spec!` struct has a `LAYOUT` constant describing its fields, which the exporters in
+//! [`export`](crate::export) turn into declarations for other languages.
+
+/// The layout of a `spec!` struct, held in its `LAYOUT` constant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layout {
+    pub name: &'static str,
+    /// The doc comments of the struct, one line after another.
+    pub doc: &'static str,
+    /// The width of the integer holding the spec.
+    pub bits: usize,
+    /// The value new values start from, with the fixed and reserved fields, the reset value and
+    /// up to date checks.
+    pub reset: u128,
+    /// The fields, in the order they are declared.
+    pub fields: &'static [FieldLayout],
+    /// The views selected by the value of a tag field, declared with `match`.
+    pub variants: Option<Variants>,
+}
+
+/// The views of a tagged spec, each a spec of its own with the tag field fixed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Variants {
+    pub tag: &'static str,
+    pub variants: &'static [Variant],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Variant {
+    pub name: &'static str,
+    pub tag_value: u128,
+    /// The layout of the view, which starts with the fields of the tagged spec.
+    pub layout: &'static Layout,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldLayout {
+    pub name: &'static str,
+    /// The doc comments of the field, one line after another.
+    pub doc: &'static str,
+    /// The lowest bit of the field.
+    pub start: usize,
+    /// The width of the value of the field.
+    pub size: usize,
+    /// The bits of the container the field is stored in, most significant first. There is a
+    /// single range unless the field is split.
+    pub bits: &'static [BitRange],
+    /// The number of implied zero bits below the stored bits of a split field.
+    pub shift: usize,
+    /// Whether the field is read and written as a two's complement value.
+    pub signed: bool,
+    /// Whether the field is an alternate view of bits that belong to other fields.
+    pub alias: bool,
+    pub kind: FieldKind,
+    pub access: Access,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitRange {
+    pub start: usize,
+    pub size: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldKind {
+    /// An integer, or a `bool` for a single bit.
+    Int,
+    /// An enum declared with `as`. The variants are listed if the enum was declared in a
+    /// `spec!`, see [`FieldEnum::VARIANTS`](crate::FieldEnum::VARIANTS).
+    Enum {
+        name: &'static str,
+        variants: &'static [(&'static str, u128)],
+    },
+    /// A real value held as `bits * factor + offset`.
+    Scaled { factor: f64, offset: f64 },
+    /// A value held in the encoding given with `#[encoding(..)]`.
+    Encoded(Encoding),
+    /// A value the field always holds, declared with `==`.
+    Fixed(u128),
+    /// Bits that must hold the given value, declared with `#[reserved]`.
+    Reserved(u128),
+    /// A parity bit or CRC over the listed fields, kept up to date by setters.
+    Check { over: &'static [&'static str] },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    Bcd,
+    Gray,
+    OneHot,
+    Offset(i128),
+}
+
+/// How software may access a field in hardware, declared with `#[access(..)]`. Fixed and check
+/// fields are read-only unless declared otherwise, and others read-write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Access {
+    ReadWrite,
+    ReadOnly,
+    WriteOnly,
+    WriteOnce,
+    ReadWriteOnce,
+}
+
+impl Layout {
+    /// The field called `name`.
+    pub fn field(&self, name: &str) -> Option<&FieldLayout> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+}
+
+impl FieldLayout {
+    /// The bits of the container the field is stored in.
+    pub fn mask(&self) -> u128 {
+        self.bits
+            .iter()
+            .fold(0, |mask, r| mask | (u128::MAX >> (128 - r.size)) << r.start)
+    }
+
+    /// Whether the field is stored in more than one range of bits.
+    pub fn is_split(&self) -> bool {
+        self.bits.len() > 1 || self.shift > 0
+    }
+
+    /// Whether the field has setters, which fixed, reserved and check fields don't.
+    pub fn is_settable(&self) -> bool {
+        !matches!(
+            self.kind,
+            FieldKind::Fixed(_) | FieldKind::Reserved(_) | FieldKind::Check { .. }
+        )
+    }
+
+    /// The value of the field in the value `raw` of its container, as in `.extract(layout.reset)`.
+    pub fn extract(&self, raw: u128) -> u128 {
+        self.segments().fold(0, |value, (r, offset)| {
+            value | ((raw >> r.start) & (u128::MAX >> (128 - r.size))) << offset
+        })
+    }
+
+    /// The bits of the container holding `value` in this field, least significant range first
+    /// along with the offset of each range within the value.
+    pub fn segments(&self) -> impl Iterator<Item = (BitRange, usize)> + '_ {
+        self.bits.iter().rev().scan(self.shift, |offset, r| {
+            let segment = (*r, *offset);
+            *offset += r.size;
+            Some(segment)
+        })
+    }
+}
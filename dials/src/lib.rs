@@ -1,4 +1,42 @@
 // mod modulo;
+pub mod bus;
+#[doc(hidden)]
+pub mod check;
+#[doc(hidden)]
+pub mod decode;
+pub mod diff;
+#[doc(hidden)]
+pub mod encoding;
+mod enums;
+mod error;
+pub mod export;
+pub mod layout;
+pub mod mock;
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub mod serde_support;
+pub mod text;
+mod validate;
 
-pub use dials_macros::spec;
+pub use dials_macros::{decoder, include_spec, pat, register_block, spec};
+pub use diff::{FieldChange, FieldValue};
+pub use enums::FieldEnum;
+pub use error::{FieldsError, RangeError};
+pub use text::ParseError;
+pub use validate::ValidationError;
 
+/// Expands to its input only when the `serde` feature is enabled, so that `spec!` can emit
+/// serde implementations unconditionally.
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __with_serde {
+    ($($item:tt)*) => { $($item)* };
+}
+
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __with_serde {
+    ($($item:tt)*) => {};
+}
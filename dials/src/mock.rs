@@ -0,0 +1,200 @@
+//! An in-memory [`Bus`] for unit-testing driver code without hardware.
+//!
+//! A [`MockBus`] keeps the registers of a block in RAM, records every access made through it as
+//! a [`Transaction`] with the fields of the value decoded, and runs the reactions a test scripts
+//! with [`MockBus::on_read`] and [`MockBus::on_write`], standing in for the hardware.
+
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::bus::{Access, Bus, Register};
+use crate::FieldValue;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Read,
+    Write,
+}
+
+/// A register access made through a [`MockBus`], formatted as
+/// `write ctrl = 0x6 (enable: false, baud: 3)` by `Display`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Transaction {
+    pub direction: Direction,
+    pub register: &'static str,
+    /// The element of a register array that was accessed.
+    pub index: Option<usize>,
+    pub offset: usize,
+    pub value: u128,
+    /// The fields of `value`, decoded by the spec the register holds.
+    pub fields: Vec<(&'static str, FieldValue)>,
+}
+
+impl fmt::Display for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = match self.direction {
+            Direction::Read => "read",
+            Direction::Write => "write",
+        };
+        write!(f, "{direction} {}", self.register)?;
+        if let Some(index) = self.index {
+            write!(f, "[{index}]")?;
+        }
+        write!(f, " = {:#x}", self.value)?;
+        for (idx, (field, value)) in self.fields.iter().enumerate() {
+            let sep = if idx == 0 { " (" } else { ", " };
+            write!(f, "{sep}{field}: {value}")?;
+        }
+        if !self.fields.is_empty() {
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+/// The bytes of the registers of a [`MockBus`], which read as zero until written. Registers are
+/// stored little-endian.
+#[derive(Debug, Clone, Default)]
+pub struct Memory {
+    bytes: BTreeMap<usize, u8>,
+}
+
+impl Memory {
+    /// The `bytes`-wide value at `offset`.
+    pub fn read(&self, offset: usize, bytes: usize) -> u128 {
+        (0..bytes).rev().fold(0, |value, byte| {
+            value << 8 | u128::from(self.bytes.get(&(offset + byte)).copied().unwrap_or(0))
+        })
+    }
+
+    /// Stores the low `bytes` bytes of `value` at `offset`.
+    pub fn write(&mut self, offset: usize, bytes: usize, value: u128) {
+        for byte in 0..bytes {
+            self.bytes
+                .insert(offset + byte, (value >> (8 * byte)) as u8);
+        }
+    }
+
+    /// The register at `offset`.
+    pub fn get<R: Register>(&self, offset: usize) -> R {
+        R::from_raw(self.read(offset, std::mem::size_of::<R>()))
+    }
+
+    /// Stores `value` in the register at `offset`.
+    pub fn set<R: Register>(&mut self, offset: usize, value: R) {
+        self.write(offset, std::mem::size_of::<R>(), value.to_raw())
+    }
+
+    /// Lets `f` change the register at `offset`.
+    pub fn modify<R: Register>(&mut self, offset: usize, f: impl FnOnce(&mut R)) {
+        let mut value = self.get::<R>(offset);
+        f(&mut value);
+        self.set(offset, value)
+    }
+}
+
+type Reaction = Box<dyn FnMut(&Transaction, &mut Memory)>;
+
+/// Registers kept in RAM, recording every access made through them.
+#[derive(Default)]
+pub struct MockBus {
+    memory: RefCell<Memory>,
+    transactions: RefCell<Vec<Transaction>>,
+    reactions: RefCell<Vec<(Direction, &'static str, Reaction)>>,
+}
+
+impl MockBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The registers, for setting up or inspecting them without recording an access.
+    pub fn memory(&self) -> Ref<'_, Memory> {
+        self.memory.borrow()
+    }
+
+    /// The registers, for setting up or inspecting them without recording an access.
+    pub fn memory_mut(&self) -> RefMut<'_, Memory> {
+        self.memory.borrow_mut()
+    }
+
+    /// Runs `reaction` after every read of `register`, to emulate clear-on-read bits and the
+    /// like.
+    pub fn on_read(
+        &self,
+        register: &'static str,
+        reaction: impl FnMut(&Transaction, &mut Memory) + 'static,
+    ) {
+        self.reactions
+            .borrow_mut()
+            .push((Direction::Read, register, Box::new(reaction)));
+    }
+
+    /// Runs `reaction` after every write to `register`, for example to set a `ready` bit once
+    /// `start` is written.
+    pub fn on_write(
+        &self,
+        register: &'static str,
+        reaction: impl FnMut(&Transaction, &mut Memory) + 'static,
+    ) {
+        self.reactions
+            .borrow_mut()
+            .push((Direction::Write, register, Box::new(reaction)));
+    }
+
+    /// The accesses made so far, oldest first.
+    pub fn transactions(&self) -> Vec<Transaction> {
+        self.transactions.borrow().clone()
+    }
+
+    /// The accesses made since the last call, oldest first.
+    pub fn take_transactions(&self) -> Vec<Transaction> {
+        std::mem::take(&mut *self.transactions.borrow_mut())
+    }
+
+    fn record(&self, direction: Direction, access: Access, value: u128) {
+        let transaction = Transaction {
+            direction,
+            register: access.register,
+            index: access.index,
+            offset: access.offset,
+            value,
+            fields: (access.fields)(value),
+        };
+        let mut memory = self.memory.borrow_mut();
+        for (_, _, reaction) in self
+            .reactions
+            .borrow_mut()
+            .iter_mut()
+            .filter(|(d, register, _)| *d == direction && *register == access.register)
+        {
+            reaction(&transaction, &mut memory);
+        }
+        self.transactions.borrow_mut().push(transaction);
+    }
+}
+
+impl Bus for MockBus {
+    fn read(&self, access: Access) -> u128 {
+        let value = self.memory.borrow().read(access.offset, access.bytes);
+        self.record(Direction::Read, access, value);
+        value
+    }
+
+    fn write(&self, access: Access, value: u128) {
+        self.memory
+            .borrow_mut()
+            .write(access.offset, access.bytes, value);
+        self.record(Direction::Write, access, value);
+    }
+}
+
+impl fmt::Debug for MockBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockBus")
+            .field("memory", &self.memory)
+            .field("transactions", &self.transactions)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,189 @@
+//! Support code for the `Serialize` and `Deserialize` implementations generated by `spec!`.
+//!
+//! A spec is serialized either as a struct of its named fields or as its raw backing integer,
+//! chosen per spec with `#[serde(map)]` or `#[serde(raw)]`. Without either attribute the
+//! struct form is used for human-readable formats and the raw integer for everything else.
+//!
+//! The struct form of a tagged spec holds its tag and the fields of the variant the tag selects,
+//! in any order. It is read ahead of picking the variant, so it needs a self-describing format.
+
+pub use serde as lib;
+
+use crate::FieldValue;
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::value::MapDeserializer;
+use serde::de::{Deserialize, Deserializer, Error, IntoDeserializer, MapAccess, Visitor};
+
+/// Fails if `value` does not fit in the `bits` bits of `field`.
+pub fn check_range<E: Error>(field: &'static str, value: u128, bits: usize) -> Result<(), E> {
+    if bits < 128 && value >> bits != 0 {
+        return Err(E::custom(format_args!(
+            "value {value} does not fit in {bits}-bit field `{field}`"
+        )));
+    }
+    Ok(())
+}
+
+/// Fails if `value` does not fit in the `bits` bit two's complement field `field`.
+pub fn check_range_signed<E: Error>(
+    field: &'static str,
+    value: i128,
+    bits: usize,
+) -> Result<(), E> {
+    if bits < 128 && !matches!(value >> (bits - 1), 0 | -1) {
+        return Err(E::custom(format_args!(
+            "value {value} does not fit in {bits}-bit field `{field}`"
+        )));
+    }
+    Ok(())
+}
+
+/// Fails if `raw` has bits set outside of the `used` bits of the spec.
+pub fn check_unused<E: Error>(spec: &'static str, raw: u128, used: u128) -> Result<(), E> {
+    if raw & !used != 0 {
+        return Err(E::custom(format_args!(
+            "{raw:#x} sets bits {:#x} that are not part of any field of `{spec}`",
+            raw & !used
+        )));
+    }
+    Ok(())
+}
+
+impl<'de> Deserialize<'de> for FieldValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ValueVisitor;
+
+        impl Visitor<'_> for ValueVisitor {
+            type Value = FieldValue;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a boolean or an integer")
+            }
+
+            fn visit_bool<E: Error>(self, value: bool) -> Result<FieldValue, E> {
+                Ok(FieldValue::Bool(value))
+            }
+
+            fn visit_u64<E: Error>(self, value: u64) -> Result<FieldValue, E> {
+                Ok(FieldValue::Uint(value.into()))
+            }
+
+            fn visit_u128<E: Error>(self, value: u128) -> Result<FieldValue, E> {
+                Ok(FieldValue::Uint(value))
+            }
+
+            fn visit_i64<E: Error>(self, value: i64) -> Result<FieldValue, E> {
+                Ok(FieldValue::Int(value.into()))
+            }
+
+            fn visit_i128<E: Error>(self, value: i128) -> Result<FieldValue, E> {
+                Ok(FieldValue::Int(value))
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+impl<'de, E: Error> IntoDeserializer<'de, E> for FieldValue {
+    type Deserializer = FieldValueDeserializer<E>;
+
+    fn into_deserializer(self) -> FieldValueDeserializer<E> {
+        FieldValueDeserializer {
+            value: self,
+            error: PhantomData,
+        }
+    }
+}
+
+/// Hands a read-ahead field value to the `Deserialize` implementation of its field's type.
+#[doc(hidden)]
+pub struct FieldValueDeserializer<E> {
+    value: FieldValue,
+    error: PhantomData<E>,
+}
+
+impl<'de, E: Error> Deserializer<'de> for FieldValueDeserializer<E> {
+    type Error = E;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        match self.value {
+            FieldValue::Bool(value) => visitor.visit_bool(value),
+            FieldValue::Uint(value) => match u64::try_from(value) {
+                Ok(value) => visitor.visit_u64(value),
+                Err(_) => visitor.visit_u128(value),
+            },
+            FieldValue::Int(value) => match i64::try_from(value) {
+                Ok(value) => visitor.visit_i64(value),
+                Err(_) => visitor.visit_i128(value),
+            },
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+/// The struct form of a tagged spec, read ahead so that its tag can pick the variant that the
+/// other fields belong to.
+#[doc(hidden)]
+pub struct TaggedFields(Vec<(String, FieldValue)>);
+
+impl TaggedFields {
+    /// The value of the tag field `tag`, if it was given exactly once.
+    pub fn tag(&self, tag: &str) -> Option<FieldValue> {
+        let mut values = self.0.iter().filter(|(field, _)| field == tag);
+        match (values.next(), values.next()) {
+            (Some((_, value)), None) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The fields other than `tag`, to be read by the view of the selected variant.
+    pub fn without(&self, tag: &str) -> Self {
+        Self(
+            self.0
+                .iter()
+                .filter(|(field, _)| field != tag)
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// The fields as a map, read like the struct they came from.
+    pub fn into_deserializer<'de, E: Error>(
+        self,
+    ) -> MapDeserializer<'de, std::vec::IntoIter<(String, FieldValue)>, E> {
+        MapDeserializer::new(self.0.into_iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for TaggedFields {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FieldsVisitor;
+
+        impl<'de> Visitor<'de> for FieldsVisitor {
+            type Value = TaggedFields;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a map of field names to values")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<TaggedFields, A::Error> {
+                let mut fields = vec![];
+                while let Some(entry) = map.next_entry()? {
+                    fields.push(entry);
+                }
+                Ok(TaggedFields(fields))
+            }
+        }
+
+        deserializer.deserialize_map(FieldsVisitor)
+    }
+}
@@ -0,0 +1,187 @@
+//! Support code for the `FromStr` and `Display` implementations generated by `spec!`.
+//!
+//! Specs made up entirely of `bool` fields are formatted as a flag set, `FLAG_0|FLAG_3`, and
+//! every other spec as comma separated `field=value` pairs, `red=18,blue=0,green=0,alpha=3`.
+//! Integer values may be written in decimal, or with a `0x`, `0o` or `0b` prefix. Tagged specs
+//! write their tag followed by the fields of the variant it selects, `opcode=51,rd=3,..`.
+
+use crate::ValidationError;
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A field or flag name that the spec does not declare.
+    UnknownField(String),
+    /// A field or flag that was given more than once.
+    DuplicateField(&'static str),
+    /// A value that does not fit in the bits available to its field.
+    OutOfRange {
+        field: &'static str,
+        value: String,
+        bits: usize,
+    },
+    /// A value that could not be parsed as an integer (or boolean, for `bool` fields).
+    InvalidValue { field: &'static str, value: String },
+    /// An entry that is not of the form `field=value`.
+    Malformed(String),
+    /// Fields that parsed but together break a rule of the spec, such as an enum field set to a
+    /// value that is not one of its variants.
+    Invalid(ValidationError),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownField(name) => write!(f, "unknown field `{name}`"),
+            Self::DuplicateField(name) => write!(f, "field `{name}` given more than once"),
+            Self::OutOfRange { field, value, bits } => {
+                write!(
+                    f,
+                    "value `{value}` does not fit in {bits}-bit field `{field}`"
+                )
+            }
+            Self::InvalidValue { field, value } => {
+                write!(f, "invalid value `{value}` for field `{field}`")
+            }
+            Self::Malformed(entry) => write!(f, "expected `field=value`, found `{entry}`"),
+            Self::Invalid(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<ValidationError> for ParseError {
+    fn from(err: ValidationError) -> Self {
+        Self::Invalid(err)
+    }
+}
+
+/// Splits `a=1, b=2` into its `(field, value)` pairs, ignoring surrounding whitespace.
+#[doc(hidden)]
+pub fn pairs(s: &str) -> impl Iterator<Item = Result<(&str, &str), ParseError>> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((field, value)) => Ok((field.trim(), value.trim())),
+            None => Err(ParseError::Malformed(entry.to_string())),
+        })
+}
+
+/// Splits the value of the tag field `tag` of a tagged spec off `s`, leaving the other pairs to
+/// be parsed by the view of the variant the tag selects.
+#[doc(hidden)]
+pub fn split_tag<'a>(
+    s: &'a str,
+    tag: &'static str,
+) -> Result<(Option<&'a str>, String), ParseError> {
+    let mut seen = false;
+    let mut value = None;
+    let mut rest = vec![];
+    for pair in pairs(s) {
+        let (field, v) = pair?;
+        if field == tag {
+            first_occurrence(&mut seen, tag)?;
+            value = Some(v);
+        } else {
+            rest.push(format!("{field}={v}"));
+        }
+    }
+    Ok((value, rest.join(",")))
+}
+
+/// Splits `A | B` into its flag names, ignoring surrounding whitespace.
+#[doc(hidden)]
+pub fn flags(s: &str) -> impl Iterator<Item = &str> {
+    s.split('|').map(str::trim).filter(|flag| !flag.is_empty())
+}
+
+/// Marks a field as seen, failing if it already was.
+#[doc(hidden)]
+pub fn first_occurrence(seen: &mut bool, field: &'static str) -> Result<(), ParseError> {
+    if std::mem::replace(seen, true) {
+        return Err(ParseError::DuplicateField(field));
+    }
+    Ok(())
+}
+
+/// Parses an unsigned integer that must fit in `bits` bits.
+#[doc(hidden)]
+pub fn parse_uint(field: &'static str, value: &str, bits: usize) -> Result<u128, ParseError> {
+    let digits = value.replace('_', "");
+    let (radix, digits) = match digits.get(..2) {
+        Some("0x" | "0X") => (16, &digits[2..]),
+        Some("0o" | "0O") => (8, &digits[2..]),
+        Some("0b" | "0B") => (2, &digits[2..]),
+        _ => (10, &digits[..]),
+    };
+    let invalid = || ParseError::InvalidValue {
+        field,
+        value: value.to_string(),
+    };
+    let out_of_range = || ParseError::OutOfRange {
+        field,
+        value: value.to_string(),
+        bits,
+    };
+    if digits.starts_with('+') {
+        return Err(invalid());
+    }
+    let parsed = u128::from_str_radix(digits, radix).map_err(|e| match e.kind() {
+        std::num::IntErrorKind::PosOverflow => out_of_range(),
+        _ => invalid(),
+    })?;
+    if bits < 128 && parsed >> bits != 0 {
+        return Err(out_of_range());
+    }
+    Ok(parsed)
+}
+
+/// Parses a signed integer that must fit in a `bits` bit two's complement field.
+#[doc(hidden)]
+pub fn parse_sint(field: &'static str, value: &str, bits: usize) -> Result<i128, ParseError> {
+    let (negative, magnitude) = match value.trim_start().strip_prefix('-') {
+        Some(magnitude) => (true, magnitude),
+        None => (false, value),
+    };
+    let magnitude = parse_uint(field, magnitude, 128).map_err(|e| match e {
+        ParseError::OutOfRange { .. } => ParseError::OutOfRange {
+            field,
+            value: value.to_string(),
+            bits,
+        },
+        _ => ParseError::InvalidValue {
+            field,
+            value: value.to_string(),
+        },
+    })?;
+    // A magnitude of up to 2^(bits - 1) fits when negative, one less when positive.
+    let limit = 1_u128 << (bits - 1);
+    if magnitude > limit || !negative && magnitude == limit {
+        return Err(ParseError::OutOfRange {
+            field,
+            value: value.to_string(),
+            bits,
+        });
+    }
+    Ok(if negative {
+        (magnitude as i128).wrapping_neg()
+    } else {
+        magnitude as i128
+    })
+}
+
+/// Parses a `bool` field, accepting `true`/`false` as well as `1`/`0`.
+#[doc(hidden)]
+pub fn parse_bool(field: &'static str, value: &str) -> Result<bool, ParseError> {
+    match value {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        _ => Err(ParseError::InvalidValue {
+            field,
+            value: value.to_string(),
+        }),
+    }
+}
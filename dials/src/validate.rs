@@ -0,0 +1,38 @@
+use std::fmt;
+
+/// A raw value that breaks a rule of its spec, found by the generated `validate` and
+/// `TryFrom<uN>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// A reserved, fixed or check field that does not hold the value the spec requires of it.
+    Mismatch {
+        field: &'static str,
+        value: u128,
+        expected: u128,
+    },
+    /// An enum field holding bits that are not the discriminant of any variant.
+    InvalidDiscriminant { field: &'static str, value: u128 },
+    /// A field declared with `#[encoding(bcd)]` or `#[encoding(onehot)]` holding bits that its
+    /// encoding never produces.
+    InvalidEncoding { field: &'static str, value: u128 },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mismatch {
+                field,
+                value,
+                expected,
+            } => write!(f, "field `{field}` must be {expected:#x}, found {value:#x}"),
+            Self::InvalidDiscriminant { field, value } => {
+                write!(f, "{value:#x} is not a valid value of enum field `{field}`")
+            }
+            Self::InvalidEncoding { field, value } => {
+                write!(f, "{value:#x} is not a valid encoding for field `{field}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
@@ -0,0 +1,124 @@
+/* link.h, generated by dials. Do not edit. */
+#ifndef LINK_H
+#define LINK_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+enum speed {
+    SPEED_OFF = 0x0,
+    SPEED_SLOW = 0x1,
+    SPEED_FAST = 0x3,
+};
+
+/*
+ * LinkCtrl
+ *
+ * The control register of the link.
+ */
+#define LINK_CTRL_RESET UINT16_C(0x2040)
+
+/*
+ * Starts the link.
+ */
+#define LINK_CTRL_ENABLE_MASK UINT16_C(0x1)
+#define LINK_CTRL_ENABLE_SHIFT 0
+#define LINK_CTRL_ENABLE_WIDTH 1
+static inline bool link_ctrl_get_enable(uint16_t reg)
+{
+    return (reg & LINK_CTRL_ENABLE_MASK) != 0;
+}
+static inline uint16_t link_ctrl_set_enable(uint16_t reg, bool value)
+{
+    return (uint16_t)((reg & ~LINK_CTRL_ENABLE_MASK) | ((uint16_t)value & UINT16_C(0x1)));
+}
+
+#define LINK_CTRL_SPEED_MASK UINT16_C(0x6)
+#define LINK_CTRL_SPEED_SHIFT 1
+#define LINK_CTRL_SPEED_WIDTH 2
+static inline uint8_t link_ctrl_get_speed(uint16_t reg)
+{
+    return (uint8_t)(((reg >> 1) & UINT16_C(0x3)));
+}
+static inline uint16_t link_ctrl_set_speed(uint16_t reg, uint8_t value)
+{
+    return (uint16_t)((reg & ~LINK_CTRL_SPEED_MASK) | ((uint16_t)value & UINT16_C(0x3)) << 1);
+}
+
+#define LINK_CTRL_OFFSET_MASK UINT16_C(0xf8)
+#define LINK_CTRL_OFFSET_SHIFT 3
+#define LINK_CTRL_OFFSET_WIDTH 5
+static inline int8_t link_ctrl_get_offset(uint16_t reg)
+{
+    uint16_t raw = (uint16_t)(((reg >> 3) & UINT16_C(0x1f)));
+    return (int8_t)((int64_t)raw - (int64_t)((raw & UINT16_C(0x10)) << 1));
+}
+static inline uint16_t link_ctrl_set_offset(uint16_t reg, int8_t value)
+{
+    return (uint16_t)((reg & ~LINK_CTRL_OFFSET_MASK) | ((uint16_t)value & UINT16_C(0x1f)) << 3);
+}
+
+#define LINK_CTRL_RESERVED8_MASK UINT16_C(0xf00)
+#define LINK_CTRL_RESERVED8_SHIFT 8
+#define LINK_CTRL_RESERVED8_WIDTH 4
+#define LINK_CTRL_RESERVED8_VALUE UINT16_C(0x0)
+static inline uint8_t link_ctrl_get_reserved8(uint16_t reg)
+{
+    return (uint8_t)(((reg >> 8) & UINT16_C(0xf)));
+}
+
+#define LINK_CTRL_VERSION_MASK UINT16_C(0xf000)
+#define LINK_CTRL_VERSION_SHIFT 12
+#define LINK_CTRL_VERSION_WIDTH 4
+#define LINK_CTRL_VERSION_VALUE UINT16_C(0x2)
+static inline uint8_t link_ctrl_get_version(uint16_t reg)
+{
+    return (uint8_t)(((reg >> 12) & UINT16_C(0xf)));
+}
+
+struct link_ctrl_bits {
+    uint16_t enable : 1;
+    uint16_t speed : 2;
+    int16_t offset : 5;
+    uint16_t reserved8 : 4;
+    uint16_t version : 4;
+};
+
+union link_ctrl {
+    uint16_t raw;
+    struct link_ctrl_bits bits;
+};
+
+_Static_assert(sizeof(struct link_ctrl_bits) == sizeof(uint16_t), "struct link_ctrl_bits must be as wide as LinkCtrl");
+_Static_assert(sizeof(union link_ctrl) == sizeof(uint16_t), "union link_ctrl must be as wide as LinkCtrl");
+
+/*
+ * Branch
+ */
+#define BRANCH_RESET UINT32_C(0x0)
+
+#define BRANCH_OPCODE_MASK UINT32_C(0x7f)
+#define BRANCH_OPCODE_SHIFT 0
+#define BRANCH_OPCODE_WIDTH 7
+static inline uint8_t branch_get_opcode(uint32_t reg)
+{
+    return (uint8_t)((reg & UINT32_C(0x7f)));
+}
+static inline uint32_t branch_set_opcode(uint32_t reg, uint8_t value)
+{
+    return (uint32_t)((reg & ~BRANCH_OPCODE_MASK) | ((uint32_t)value & UINT32_C(0x7f)));
+}
+
+#define BRANCH_IMM_MASK UINT32_C(0xfe000f80)
+#define BRANCH_IMM_WIDTH 13
+static inline int16_t branch_get_imm(uint32_t reg)
+{
+    uint32_t raw = (uint32_t)((uint32_t)((reg >> 8) & UINT32_C(0xf)) << 1 | (uint32_t)((reg >> 25) & UINT32_C(0x3f)) << 5 | (uint32_t)((reg >> 7) & UINT32_C(0x1)) << 11 | (uint32_t)((reg >> 31) & UINT32_C(0x1)) << 12);
+    return (int16_t)((int64_t)raw - (int64_t)((raw & UINT32_C(0x1000)) << 1));
+}
+static inline uint32_t branch_set_imm(uint32_t reg, int16_t value)
+{
+    return (uint32_t)((reg & ~BRANCH_IMM_MASK) | (((uint32_t)value >> 1) & UINT32_C(0xf)) << 8 | (((uint32_t)value >> 5) & UINT32_C(0x3f)) << 25 | (((uint32_t)value >> 11) & UINT32_C(0x1)) << 7 | (((uint32_t)value >> 12) & UINT32_C(0x1)) << 31);
+}
+
+#endif /* LINK_H */
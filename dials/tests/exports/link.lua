@@ -0,0 +1,89 @@
+-- link, generated by dials. Do not edit.
+local proto = Proto("link", "Link protocol")
+local f = proto.fields
+
+-- The value of `size` bits of `value` from bit `start`, counting from the least significant.
+local function bits(value, start, size)
+    if type(value) == "number" then
+        return math.floor(value / 2 ^ start % 2 ^ size)
+    end
+    return value:rshift(start):band(UInt64.max():rshift(64 - size)):tonumber()
+end
+
+local speed_values = {
+    [0] = "Off",
+    [1] = "Slow",
+    [3] = "Fast",
+}
+
+f.link_ctrl = ProtoField.uint16("link.link_ctrl", "LinkCtrl", base.HEX, nil, nil, "The control register of the link.")
+f.link_ctrl_enable = ProtoField.bool("link.link_ctrl.enable", "enable", 16, nil, 0x0001, "Starts the link.")
+f.link_ctrl_speed = ProtoField.uint16("link.link_ctrl.speed", "speed", base.DEC, speed_values, 0x0006)
+f.link_ctrl_offset = ProtoField.int16("link.link_ctrl.offset", "offset", base.DEC, nil, 0x00f8)
+f.link_ctrl_reserved8 = ProtoField.uint16("link.link_ctrl.reserved8", "reserved8", base.HEX, nil, 0x0f00, "Reserved, 0x0.")
+f.link_ctrl_version = ProtoField.uint16("link.link_ctrl.version", "version", base.HEX, nil, 0xf000, "Always 0x2.")
+
+f.branch = ProtoField.uint32("link.branch", "Branch", base.HEX)
+f.branch_opcode = ProtoField.uint32("link.branch.opcode", "opcode", base.DEC, nil, 0x0000007f)
+f.branch_imm = ProtoField.int32("link.branch.imm", "imm", base.DEC, nil, nil)
+
+f.frame = ProtoField.uint16("link.frame", "Frame", base.HEX)
+f.frame_kind = ProtoField.uint16("link.frame.kind", "kind", base.DEC, nil, 0x000f)
+f.frame_data_len = ProtoField.uint16("link.frame.data.len", "len", base.DEC, nil, 0xfff0)
+f.frame_ack_seq = ProtoField.uint16("link.frame.ack.seq", "seq", base.DEC, nil, 0x0ff0)
+f.frame_ack_speed = ProtoField.uint16("link.frame.ack.speed", "speed", base.DEC, speed_values, 0x3000)
+
+local function dissect_link_ctrl(buffer, offset, tree)
+    local range = buffer(offset, 2)
+    local subtree = tree:add(f.link_ctrl, range)
+    subtree:add(f.link_ctrl_enable, range)
+    subtree:add(f.link_ctrl_speed, range)
+    subtree:add(f.link_ctrl_offset, range)
+    subtree:add(f.link_ctrl_reserved8, range)
+    subtree:add(f.link_ctrl_version, range)
+    return offset + 2
+end
+
+local function dissect_branch(buffer, offset, tree)
+    local range = buffer(offset, 4)
+    local subtree = tree:add(f.branch, range)
+    local value = range:uint()
+    subtree:add(f.branch_opcode, range)
+    do
+        local v = bits(value, 8, 4) * 2 + bits(value, 25, 6) * 32 + bits(value, 7, 1) * 2048 + bits(value, 31, 1) * 4096
+        if v >= 4096 then
+            v = v - 8192
+        end
+        subtree:add(f.branch_imm, range, v)
+    end
+    return offset + 4
+end
+
+local function dissect_frame(buffer, offset, tree)
+    local range = buffer(offset, 2)
+    local subtree = tree:add(f.frame, range)
+    local value = range:uint()
+    subtree:add(f.frame_kind, range)
+    local tag = bits(value, 0, 4)
+    if tag == 1 then
+        local variant = subtree:add(range, "Data")
+        variant:add(f.frame_data_len, range)
+    elseif tag == 2 then
+        local variant = subtree:add(range, "Ack")
+        variant:add(f.frame_ack_seq, range)
+        variant:add(f.frame_ack_speed, range)
+    end
+    return offset + 2
+end
+
+function proto.dissector(buffer, pinfo, tree)
+    pinfo.cols.protocol = proto.name
+    local subtree = tree:add(proto, buffer())
+    local offset = 0
+    offset = dissect_link_ctrl(buffer, offset, subtree)
+    offset = dissect_branch(buffer, offset, subtree)
+    offset = dissect_frame(buffer, offset, subtree)
+    return offset
+end
+
+DissectorTable.get("udp.port"):add(9000, proto)
@@ -0,0 +1,40 @@
+// link_pkg, generated by dials. Do not edit.
+package link_pkg;
+
+    typedef enum logic [1:0] {
+        SPEED_OFF = 2'h0,
+        SPEED_SLOW = 2'h1,
+        SPEED_FAST = 2'h3
+    } speed_e;
+
+    // LinkCtrl
+    //
+    // The control register of the link.
+    typedef struct packed {
+        logic [3:0] version;
+        logic [3:0] reserved8;
+        logic signed [4:0] offset;
+        speed_e speed;
+        // Starts the link.
+        logic enable;
+    } link_ctrl_t;
+
+    localparam link_ctrl_t LINK_CTRL_RESET = 16'h2040;
+
+    // Branch
+    typedef struct packed {
+        logic imm_12;
+        logic [5:0] imm_10_5;
+        logic [12:0] unused12;
+        logic [3:0] imm_4_1;
+        logic imm_11;
+        logic [6:0] opcode;
+    } branch_t;
+
+    localparam branch_t BRANCH_RESET = 32'h0;
+
+    function automatic logic signed [12:0] branch_imm(branch_t r);
+        return {r.imm_12, r.imm_11, r.imm_10_5, r.imm_4_1, 1'b0};
+    endfunction
+
+endpackage
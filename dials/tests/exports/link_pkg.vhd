@@ -0,0 +1,88 @@
+-- link_pkg, generated by dials. Do not edit.
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+
+package link_pkg is
+
+    subtype speed_t is std_logic_vector(1 downto 0);
+    constant SPEED_OFF : speed_t := "00";
+    constant SPEED_SLOW : speed_t := "01";
+    constant SPEED_FAST : speed_t := "11";
+
+    -- LinkCtrl
+    --
+    -- The control register of the link.
+    type link_ctrl_t is record
+        -- Starts the link.
+        enable : std_logic;
+        speed : speed_t;
+        offset : signed(4 downto 0);
+        reserved8 : std_logic_vector(3 downto 0);
+        version : std_logic_vector(3 downto 0);
+    end record;
+
+    constant LINK_CTRL_RESET : std_logic_vector(15 downto 0) := x"2040";
+    function to_link_ctrl(v : std_logic_vector(15 downto 0)) return link_ctrl_t;
+    function to_slv(r : link_ctrl_t) return std_logic_vector;
+
+    -- Branch
+    type branch_t is record
+        opcode : std_logic_vector(6 downto 0);
+        imm : signed(12 downto 0);
+    end record;
+
+    constant BRANCH_RESET : std_logic_vector(31 downto 0) := x"00000000";
+    function to_branch(v : std_logic_vector(31 downto 0)) return branch_t;
+    function to_slv(r : branch_t) return std_logic_vector;
+
+end package;
+
+package body link_pkg is
+
+    function to_link_ctrl(v : std_logic_vector(15 downto 0)) return link_ctrl_t is
+        variable r : link_ctrl_t;
+    begin
+        r.enable := v(0);
+        r.speed := v(2 downto 1);
+        r.offset := signed(v(7 downto 3));
+        r.reserved8 := v(11 downto 8);
+        r.version := v(15 downto 12);
+        return r;
+    end function;
+
+    function to_slv(r : link_ctrl_t) return std_logic_vector is
+        variable v : std_logic_vector(15 downto 0) := (others => '0');
+    begin
+        v(0) := r.enable;
+        v(2 downto 1) := r.speed;
+        v(7 downto 3) := std_logic_vector(r.offset);
+        v(11 downto 8) := r.reserved8;
+        v(15 downto 12) := r.version;
+        return v;
+    end function;
+
+    function to_branch(v : std_logic_vector(31 downto 0)) return branch_t is
+        variable r : branch_t;
+    begin
+        r.opcode := v(6 downto 0);
+        r.imm(4 downto 1) := signed(v(11 downto 8));
+        r.imm(10 downto 5) := signed(v(30 downto 25));
+        r.imm(11) := v(7);
+        r.imm(12) := v(31);
+        r.imm(0 downto 0) := (others => '0');
+        return r;
+    end function;
+
+    function to_slv(r : branch_t) return std_logic_vector is
+        variable v : std_logic_vector(31 downto 0) := (others => '0');
+    begin
+        v(6 downto 0) := r.opcode;
+        v(11 downto 8) := std_logic_vector(r.imm(4 downto 1));
+        v(30 downto 25) := std_logic_vector(r.imm(10 downto 5));
+        v(7) := r.imm(11);
+        v(31) := r.imm(12);
+        return v;
+    end function;
+
+end package body;
@@ -1,4 +1,27 @@
 mod spec {
+    mod alias;
+    mod arith;
+    mod c_bitfield;
+    mod check;
     mod colors;
+    mod decoder;
+    mod diff;
+    mod docs;
+    mod encoding;
+    mod export;
+    mod fields;
+    mod include;
+    mod items;
+    mod mock;
+    mod pattern;
+    mod register_block;
+    mod reset;
+    mod scaled;
     // mod flags;
+    #[cfg(feature = "serde")]
+    mod serde;
+    mod split;
+    mod text;
+    mod validate;
+    mod variants;
 }
@@ -0,0 +1,71 @@
+use dials::spec;
+
+spec! {
+    struct Rgba {
+        red: u8,
+        green: u8,
+        blue: u8,
+        alpha: u8,
+        #[alias]
+        rgb: u24 @ 0..24,
+        #[alias]
+        word: u32 @ 0..32,
+    }
+}
+
+spec! {
+    struct Placed {
+        low: u4 @ 2..6,
+        next: u2,
+        #[alias]
+        low_msb: bool @ 5,
+        high: u8 @ 16,
+    }
+}
+
+#[test]
+fn aliases_view_the_same_bits() {
+    let mut x = Rgba(0);
+    x.set_red(0x12)
+        .set_green(0x34)
+        .set_blue(0x56)
+        .set_alpha(0xff);
+    assert_eq!(x.rgb(), 0x56_34_12);
+    assert_eq!(x.word(), 0xff56_3412);
+
+    x.set_rgb(0xabcdef);
+    assert_eq!((x.red(), x.green(), x.blue()), (0xef, 0xcd, 0xab));
+    assert_eq!(x.alpha(), 0xff);
+
+    x.set_word(0x0102_0304);
+    assert_eq!(x.alpha(), 0x01);
+    assert_eq!(Rgba::RGB, 0x00ff_ffff);
+}
+
+#[test]
+fn aliases_are_not_value_fields() {
+    let x: Rgba = "red=1,blue=2".parse().unwrap();
+    assert_eq!(x.to_string(), "red=1,green=0,blue=2,alpha=0");
+    assert!("rgb=1".parse::<Rgba>().is_err());
+
+    let RgbaFields {
+        red,
+        green,
+        blue,
+        alpha,
+    } = x.into();
+    assert_eq!((red, green, blue, alpha), (1, 0, 2, 0));
+}
+
+#[test]
+fn fields_can_be_placed_explicitly() {
+    assert_eq!(Placed::LOW_START, 2);
+    assert_eq!(Placed::NEXT_START, 6);
+    assert_eq!(Placed::HIGH_START, 16);
+    assert_eq!(Placed::HIGH, 0x00ff_0000);
+
+    let mut x = Placed(0);
+    x.set_low(0b1000);
+    assert!(x.low_msb());
+    assert_eq!(x.0, 0b10_0000);
+}
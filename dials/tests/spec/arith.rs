@@ -0,0 +1,84 @@
+use dials::spec;
+
+spec! {
+    struct Header {
+        flag: bool,
+        seq: u4,
+        window: u3,
+        delta: i5,
+        credit: u19,
+    }
+}
+
+spec! {
+    struct Wide {
+        count: u16,
+    }
+}
+
+#[test]
+fn wrapping_add() {
+    let mut h = Header(0);
+    h.set_flag().set_window(0b111).set_seq(14);
+
+    h.wrapping_add_seq(3);
+    assert_eq!(h.seq(), 1);
+    assert!(h.flag());
+    assert_eq!(h.window(), 0b111);
+
+    h.set_delta(15).wrapping_add_delta(1);
+    assert_eq!(h.delta(), -16);
+
+    let mut w = Wide(u16::MAX);
+    w.wrapping_add_count(2);
+    assert_eq!(w.count(), 1);
+}
+
+#[test]
+fn increment() {
+    let mut h = Header(0);
+    for expected in (1..16).chain([0, 1]) {
+        h.increment_seq();
+        assert_eq!(h.seq(), expected);
+    }
+    assert_eq!(h.0 & !Header::SEQ, 0);
+
+    h.set_delta(15).increment_delta();
+    assert_eq!(h.delta(), -16);
+}
+
+#[test]
+fn checked_add() {
+    let mut h = Header(0);
+    h.set_seq(13);
+    assert!(h.checked_add_seq(2).is_some());
+    assert_eq!(h.seq(), 15);
+    assert!(h.checked_add_seq(1).is_none());
+    assert_eq!(h.seq(), 15);
+
+    h.set_delta(-14);
+    assert!(h.checked_add_delta(-2).is_some());
+    assert_eq!(h.delta(), -16);
+    assert!(h.checked_add_delta(-1).is_none());
+    assert!(h.checked_add_delta(31).is_some());
+    assert_eq!(h.delta(), 15);
+
+    let mut w = Wide(u16::MAX - 1);
+    assert!(w.checked_add_count(1).is_some());
+    assert!(w.checked_add_count(1).is_none());
+    assert_eq!(w.count(), u16::MAX);
+}
+
+#[test]
+fn saturating_sub() {
+    let mut h = Header(0);
+    h.set_credit(5).set_window(3);
+    h.saturating_sub_credit(7);
+    assert_eq!(h.credit(), 0);
+    assert_eq!(h.window(), 3);
+
+    h.set_delta(-10).saturating_sub_delta(10);
+    assert_eq!(h.delta(), -16);
+    h.saturating_sub_delta(-40);
+    assert_eq!(h.delta(), 15);
+}
@@ -0,0 +1,63 @@
+use dials::spec;
+
+spec! {
+    /// As declared in C:
+    ///
+    /// ```c
+    /// struct flags {
+    ///     unsigned char kind : 3;
+    ///     unsigned short len : 10;
+    ///     unsigned char tag : 4;
+    ///     int delta : 9;
+    ///     unsigned : 2;
+    ///     _Bool last : 1;
+    /// };
+    /// ```
+    #[repr(C_bitfield)]
+    struct Flags {
+        kind: u3 in u8,
+        len: u10 in u16,
+        tag: u4 in u8,
+        delta: i9,
+        #[reserved]
+        reserved29: u2,
+        last: bool in bool,
+    }
+
+    /// `struct wide { unsigned long long a : 30; unsigned b : 4; };`
+    #[repr(C_bitfield)]
+    struct Wide {
+        a: u30 in u64,
+        b: u4,
+    }
+}
+
+#[test]
+fn fields_do_not_straddle_their_storage_units() {
+    let starts = Flags::LAYOUT
+        .fields
+        .iter()
+        .map(|f| f.start)
+        .collect::<Vec<_>>();
+    assert_eq!(starts, [0, 3, 16, 20, 29, 31]);
+    assert_eq!(Wide::LAYOUT.field("b").unwrap().start, 32);
+    assert_eq!(Wide::LAYOUT.bits, 64);
+}
+
+#[test]
+fn matches_the_c_struct() {
+    let mut flags = Flags(0);
+    flags
+        .set_kind(5)
+        .set_len(0x2a5)
+        .set_tag(0xc)
+        .set_delta(-3)
+        .set_last();
+    // The bytes of the C struct above with the same values, as laid out by GCC on x86-64.
+    assert_eq!(flags.0, 0x9fdc_152d);
+    assert_eq!(size_of::<Flags>(), 4);
+
+    let mut wide = Wide(0);
+    wide.set_b(0xf);
+    assert_eq!(wide.0, 0xf_0000_0000);
+}
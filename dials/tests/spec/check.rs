@@ -0,0 +1,164 @@
+use dials::{spec, ValidationError};
+
+spec! {
+    struct Frame {
+        data: u7,
+        #[parity(even, over = [data])]
+        even: bool,
+        #[parity(odd, over = [data, flag])]
+        odd: bool,
+        flag: bool,
+    }
+}
+
+spec! {
+    struct Message {
+        d0: u8,
+        d1: u8,
+        d2: u8,
+        d3: u8,
+        d4: u8,
+        d5: u8,
+        d6: u8,
+        d7: u8,
+        d8: u8,
+        #[crc(poly = 0x07, over = [d0, d1, d2, d3, d4, d5, d6, d7, d8])]
+        crc: u8,
+    }
+}
+
+spec! {
+    /// `Message` holding `123456789` after reset, so that its CRC is worked out by `spec!`.
+    #[reset(0x39_38_37_36_35_34_33_32_31)]
+    struct Preset {
+        d0: u8,
+        d1: u8,
+        d2: u8,
+        d3: u8,
+        d4: u8,
+        d5: u8,
+        d6: u8,
+        d7: u8,
+        d8: u8,
+        #[crc(poly = 0x07, over = [d0, d1, d2, d3, d4, d5, d6, d7, d8])]
+        crc: u8,
+    }
+}
+
+spec! {
+    #[reset(0x1_5a3c_96e1)]
+    struct Odd {
+        a: u3,
+        b: u13,
+        c: u1,
+        d: u16,
+        #[crc(poly = 0x15, over = [d, a, c, b], init = 0x1f)]
+        crc: u5,
+    }
+}
+
+#[test]
+fn parity_follows_setters() {
+    let mut f = Frame::new();
+    assert!(!f.even());
+    assert!(f.odd());
+
+    f.set_data(0b101_0110);
+    assert!(!f.even());
+    assert!(f.odd());
+
+    f.set_data(0b000_0111);
+    assert!(f.even());
+    assert!(!f.odd());
+
+    f.set_flag();
+    assert!(f.even());
+    assert!(f.odd());
+
+    f.unset_flag().toggle_flag();
+    assert!(f.odd());
+    assert_eq!(f.validate(), Ok(()));
+}
+
+#[test]
+fn parity_is_validated() {
+    let raw = Frame::new().0;
+    assert_eq!(
+        Frame::try_from(raw ^ (1 << 7)).err(),
+        Some(ValidationError::Mismatch {
+            field: "even",
+            value: 1,
+            expected: 0,
+        })
+    );
+    assert_eq!(
+        Frame::try_from(raw ^ 1).err(),
+        Some(ValidationError::Mismatch {
+            field: "even",
+            value: 0,
+            expected: 1,
+        })
+    );
+}
+
+#[test]
+fn parity_from_text() {
+    let f: Frame = "data=3,flag=false".parse().ok().unwrap();
+    assert!(!f.even());
+    assert!(f.odd());
+    let f: Frame = "".parse().ok().unwrap();
+    assert!(f.odd());
+}
+
+#[test]
+fn crc() {
+    let mut m = Message::new();
+    for (idx, digit) in b"123456789".iter().enumerate() {
+        match idx {
+            0 => m.set_d0(*digit as u128),
+            1 => m.set_d1(*digit as u128),
+            2 => m.set_d2(*digit as u128),
+            3 => m.set_d3(*digit as u128),
+            4 => m.set_d4(*digit as u128),
+            5 => m.set_d5(*digit as u128),
+            6 => m.set_d6(*digit as u128),
+            7 => m.set_d7(*digit as u128),
+            _ => m.set_d8(*digit as u128),
+        };
+    }
+    // The CRC-8 check value.
+    assert_eq!(m.crc(), 0xf4);
+    assert_eq!(m.validate(), Ok(()));
+
+    m.0 ^= 1 << 20;
+    assert!(matches!(
+        m.validate(),
+        Err(ValidationError::Mismatch { field: "crc", .. })
+    ));
+}
+
+#[test]
+fn crc_of_reset_values_matches_setters() {
+    // The CRC of the reset value is worked out by `spec!`, and that of every other value by
+    // `dials::check::crc`, so both must agree.
+    let preset = Preset::new();
+    assert_eq!(preset.crc(), 0xf4);
+    let mut m = Message(preset.0);
+    m.set_d0(preset.d0());
+    assert_eq!(m.0, preset.0);
+
+    let odd = Odd::new();
+    let message = [
+        (odd.d() as u128, 16),
+        (odd.a() as u128, 3),
+        (odd.c() as u128, 1),
+        (odd.b() as u128, 13),
+    ];
+    assert_eq!(
+        odd.crc() as u128,
+        dials::check::crc(&message, 5, 0x15, 0x1f)
+    );
+    let mut updated = Odd(odd.0 ^ (0x1f << 33));
+    updated.set_a(odd.a());
+    assert_eq!(updated.0, odd.0);
+}
@@ -0,0 +1,97 @@
+use dials::{decoder, spec};
+
+spec! {
+    struct Add {
+        opcode: u7 == 0b0110011,
+        rd: u5,
+        funct3: u3 == 0,
+        rs1: u5,
+        rs2: u5,
+        funct7: u7 == 0,
+    }
+}
+
+spec! {
+    struct Sub {
+        opcode: u7 == 0b0110011,
+        rd: u5,
+        funct3: u3 == 0,
+        rs1: u5,
+        rs2: u5,
+        funct7: u7 == 0b0100000,
+    }
+}
+
+spec! {
+    struct Addi {
+        opcode: u7 == 0b0010011,
+        rd: u5,
+        funct3: u3 == 0,
+        rs1: u5,
+        imm: i12,
+    }
+}
+
+spec! {
+    struct Ecall {
+        word: u32 == 0x0000_0073,
+    }
+}
+
+spec! {
+    struct System {
+        opcode: u7 == 0b1110011,
+        rest: u25,
+    }
+}
+
+decoder! {
+    pub enum Rv32: u32 {
+        Add,
+        Sub,
+        AddImmediate(Addi),
+        Ecall,
+        System,
+    }
+}
+
+#[test]
+fn fixed_fields_form_a_pattern() {
+    assert_eq!(Add::MATCH_MASK, 0xfe00_707f);
+    assert_eq!(Sub::MATCH_VALUE, 0x4000_0033);
+    assert!(Add::matches(0x0020_81b3));
+    assert!(!Add::matches(0x4020_81b3));
+    assert!(Sub::matches(0x4020_81b3));
+
+    let mut addi = Addi::new();
+    addi.set_rd(5).set_rs1(6).set_imm(-1);
+    assert_eq!(addi.0, 0xfff3_0293);
+    assert_eq!(Addi::default().0, Addi::MATCH_VALUE);
+}
+
+#[test]
+fn fixed_fields_are_not_value_fields() {
+    let addi: Addi = "rd=5,rs1=6,imm=-1".parse().unwrap();
+    assert_eq!(addi.0, 0xfff3_0293);
+    assert_eq!(addi.to_string(), "rd=5,rs1=6,imm=-1");
+    assert!("opcode=1".parse::<Addi>().is_err());
+
+    let raw = addi.0;
+    let fields = AddiFields::from(addi);
+    assert_eq!(Addi::try_from(fields).unwrap().0, raw);
+}
+
+#[test]
+fn decoder_dispatches_on_patterns() {
+    assert!(matches!(Rv32::decode(0x0020_81b3), Some(Rv32::Add(add)) if add.rd() == 3));
+    assert!(matches!(Rv32::decode(0x4020_81b3), Some(Rv32::Sub(sub)) if sub.rs2() == 2));
+    assert!(matches!(
+        Rv32::decode(0xfff3_0293),
+        Some(Rv32::AddImmediate(addi)) if addi.imm() == -1
+    ));
+    assert!(matches!(Rv32::decode(0x0000_0073), Some(Rv32::Ecall(_))));
+    assert!(matches!(Rv32::decode(0x0010_0073), Some(Rv32::System(_))));
+    assert!(Rv32::decode(0x0000_1033).is_none());
+    assert!(Rv32::decode(0).is_none());
+    assert_eq!(Rv32::decode(0x4020_81b3).unwrap().raw(), 0x4020_81b3);
+}
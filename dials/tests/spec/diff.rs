@@ -0,0 +1,60 @@
+use dials::{spec, FieldChange, FieldValue};
+
+spec! {
+    struct Status {
+        ready: bool,
+        error: bool,
+        level: u6,
+        offset: i8,
+        #[alias]
+        low: u4 @ 2,
+    }
+}
+
+#[test]
+fn changed_fields() {
+    let old = Status(0b0000_0001);
+    let mut new = Status(old.0);
+    new.set_error().set_offset(-3);
+
+    assert_eq!(
+        old.diff(&new).collect::<Vec<_>>(),
+        [
+            FieldChange {
+                field: "error",
+                old: FieldValue::Bool(false),
+                new: FieldValue::Bool(true),
+            },
+            FieldChange {
+                field: "offset",
+                old: FieldValue::Int(0),
+                new: FieldValue::Int(-3),
+            },
+        ]
+    );
+}
+
+#[test]
+fn equal_values() {
+    let a = Status(0x1234);
+    let b = Status(0x1234);
+    assert_eq!(a.diff(&b).count(), 0);
+    assert_eq!(format!("{:?}", a.diff(&b)), "Status");
+}
+
+#[test]
+fn debug_rendering() {
+    let old = Status(0);
+    let mut new = Status(0);
+    new.set_level(42).set_ready();
+
+    let diff = old.diff(&new);
+    assert_eq!(
+        format!("{diff:?}"),
+        "Status { ready: false -> true, level: 0 -> 42 }"
+    );
+    assert_eq!(
+        diff.map(|change| change.to_string()).collect::<Vec<_>>(),
+        ["ready: false -> true", "level: 0 -> 42"]
+    );
+}
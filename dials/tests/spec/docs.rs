@@ -0,0 +1,39 @@
+use dials::spec;
+
+spec! {
+    /// The control register of a UART.
+    ///
+    /// Documented fields keep their doc comments.
+    struct Ctrl {
+        /// Enables the transmitter.
+        tx_enable: bool,
+        #[reserved(one)]
+        rsvd: u2,
+        /// Odd parity over the divider.
+        #[parity(odd, over = [div])]
+        parity: bool,
+        /// Log2 of the clock divider.
+        div: u4,
+        match tx_enable {
+            /// The transmitter is off.
+            0 => Off { },
+            1 => On {
+                /// The number of stop bits.
+                stop: u2,
+            },
+        }
+    }
+}
+
+#[test]
+fn documented_fields() {
+    let mut ctrl = Ctrl::new();
+    assert_eq!(ctrl.0, 0x0e);
+    ctrl.set_div(3).set_tx_enable();
+    assert_eq!(ctrl.0, 0x3f);
+    match ctrl.decode() {
+        Some(CtrlVariant::On(mut on)) => assert_eq!(on.set_stop(2).0, 0x23f),
+        _ => panic!("expected the transmitter to be on"),
+    }
+    assert_eq!(CtrlFields::from(ctrl).div, 3);
+}
@@ -0,0 +1,97 @@
+use dials::{spec, RangeError, ValidationError};
+
+spec! {
+    struct Clock {
+        #[encoding(bcd)]
+        minutes: u8,
+        #[encoding(gray)]
+        pos: u10,
+        #[encoding(offset = -40)]
+        temp: u8,
+        #[encoding(onehot)]
+        sel: u6,
+    }
+}
+
+#[test]
+fn bcd() {
+    let mut c = Clock(0);
+    c.set_minutes_bits(0x59);
+    assert_eq!(c.minutes(), 59);
+    assert!(c.set_minutes(7).is_ok());
+    assert_eq!(c.minutes_bits(), 0x07);
+    assert_eq!(
+        c.set_minutes(100).err(),
+        Some(RangeError {
+            field: "minutes",
+            value: "100".to_string(),
+            bits: 8,
+        })
+    );
+    assert_eq!(c.minutes(), 7);
+}
+
+#[test]
+fn gray() {
+    let mut c = Clock(0);
+    let mut previous = None;
+    for value in 0..1024 {
+        assert!(c.set_pos(value).is_ok());
+        assert_eq!(c.pos(), value);
+        // Consecutive values differ in a single bit.
+        if let Some(bits) = previous.replace(c.pos_bits()) {
+            assert_eq!((bits ^ c.pos_bits()).count_ones(), 1);
+        }
+    }
+    assert!(c.set_pos(1024).is_err());
+    assert_eq!(c.minutes_bits(), 0);
+}
+
+#[test]
+fn offset() {
+    let mut c = Clock(0);
+    assert_eq!(c.temp(), -40i16);
+    assert!(c.set_temp(25).is_ok());
+    assert_eq!(c.temp_bits(), 65);
+    assert!(c.set_temp(215).is_ok());
+    assert_eq!(c.temp_bits(), 255);
+    assert!(c.set_temp(216).is_err());
+    assert!(c.set_temp(-41).is_err());
+    assert_eq!(c.temp(), 215);
+}
+
+#[test]
+fn onehot() {
+    let mut c = Clock(0);
+    assert!(c.set_sel(3).is_ok());
+    assert_eq!(c.sel_bits(), 0b001000);
+    assert_eq!(c.sel(), 3);
+    assert!(c.set_sel(6).is_err());
+    assert_eq!(c.sel(), 3);
+}
+
+#[test]
+fn validate_encodings() {
+    let mut c = Clock(0);
+    c.set_sel(0).ok();
+    assert_eq!(c.validate(), Ok(()));
+
+    c.set_sel_bits(0b000110);
+    assert_eq!(
+        c.validate(),
+        Err(ValidationError::InvalidEncoding {
+            field: "sel",
+            value: 0b000110,
+        })
+    );
+
+    c.set_sel(1).ok();
+    c.set_minutes_bits(0x5a);
+    assert_eq!(
+        c.validate(),
+        Err(ValidationError::InvalidEncoding {
+            field: "minutes",
+            value: 0x5a,
+        })
+    );
+}
@@ -0,0 +1,153 @@
+use dials::export::{CHeader, Dissector, Json, SystemVerilog, Vhdl};
+use dials::layout::{Access, BitRange, FieldKind};
+use dials::spec;
+
+spec! {
+    enum Speed {
+        Off = 0,
+        Slow = 1,
+        Fast = 3,
+    }
+
+    /// The control register of the link.
+    #[reset(0x40)]
+    struct LinkCtrl {
+        /// Starts the link.
+        enable: bool,
+        speed: u2 as Speed,
+        offset: i5,
+        #[reserved]
+        reserved8: u4,
+        #[access(read_only)]
+        version: u4 == 2,
+    }
+
+    struct Branch {
+        opcode: u7,
+        imm: i13 = [31, 7, 30..25, 11..8] << 1,
+    }
+}
+
+#[test]
+fn layout_describes_fields() {
+    let layout = LinkCtrl::LAYOUT;
+    assert_eq!((layout.name, layout.bits), ("LinkCtrl", 16));
+    assert_eq!(layout.doc, "The control register of the link.");
+    assert_eq!(layout.reset, 0x2040);
+
+    let speed = layout.field("speed").unwrap();
+    assert_eq!((speed.start, speed.size, speed.mask()), (1, 2, 0b110));
+    assert_eq!(
+        speed.kind,
+        FieldKind::Enum {
+            name: "Speed",
+            variants: &[("Off", 0), ("Slow", 1), ("Fast", 3)],
+        }
+    );
+    assert!(layout.field("offset").unwrap().signed);
+    assert_eq!(
+        layout.field("reserved8").unwrap().kind,
+        FieldKind::Reserved(0)
+    );
+    let version = layout.field("version").unwrap();
+    assert_eq!(
+        (version.kind, version.access),
+        (FieldKind::Fixed(2), Access::ReadOnly)
+    );
+    assert!(!version.is_settable());
+
+    let imm = Branch::LAYOUT.field("imm").unwrap();
+    assert!(imm.is_split());
+    assert_eq!(imm.shift, 1);
+    assert_eq!(imm.bits[0], BitRange { start: 31, size: 1 });
+    assert_eq!(
+        imm.segments()
+            .map(|(r, offset)| (r.start, offset))
+            .collect::<Vec<_>>(),
+        [(8, 1), (25, 5), (7, 11), (31, 12)]
+    );
+}
+
+#[test]
+fn c_header() {
+    let header = CHeader::new("link.h")
+        .spec(&LinkCtrl::LAYOUT)
+        .spec(&Branch::LAYOUT)
+        .bitfields()
+        .to_string();
+    assert_eq!(header, include_str!("../exports/link.h"));
+}
+
+#[test]
+fn system_verilog_package() {
+    let package = SystemVerilog::new("link_pkg")
+        .spec(&LinkCtrl::LAYOUT)
+        .spec(&Branch::LAYOUT)
+        .to_string();
+    assert_eq!(package, include_str!("../exports/link_pkg.sv"));
+}
+
+#[test]
+fn vhdl_package() {
+    let package = Vhdl::new("link_pkg")
+        .spec(&LinkCtrl::LAYOUT)
+        .spec(&Branch::LAYOUT)
+        .to_string();
+    assert_eq!(package, include_str!("../exports/link_pkg.vhd"));
+}
+
+spec! {
+    struct Frame {
+        kind: u4,
+        match kind {
+            1 => Data { len: u12 },
+            2 => Ack { seq: u8, speed: u2 as Speed },
+        }
+    }
+}
+
+#[test]
+fn layout_describes_variants() {
+    let variants = Frame::LAYOUT.variants.unwrap();
+    assert_eq!(variants.tag, "kind");
+    assert_eq!(variants.variants[1].name, "Ack");
+    assert_eq!(variants.variants[1].tag_value, 2);
+    assert_eq!(variants.variants[1].layout.name, "FrameAck");
+    assert_eq!(variants.variants[1].layout.field("seq").unwrap().start, 4);
+    assert!(LinkCtrl::LAYOUT.variants.is_none());
+}
+
+#[test]
+fn wireshark_dissector() {
+    let dissector = Dissector::new("link", "Link protocol")
+        .spec(&LinkCtrl::LAYOUT)
+        .spec(&Branch::LAYOUT)
+        .spec(&Frame::LAYOUT)
+        .udp_port(9000)
+        .to_string();
+    assert_eq!(dissector, include_str!("../exports/link.lua"));
+}
+
+#[test]
+fn json_manifest() {
+    let manifest = Json::new()
+        .spec(&LinkCtrl::LAYOUT)
+        .spec(&Frame::LAYOUT)
+        .to_string();
+    assert_eq!(manifest, include_str!("../exports/link.json"));
+
+    let json: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+    let ctrl = &json["specs"][0];
+    assert_eq!(
+        (&ctrl["name"], &ctrl["bits"], &ctrl["reset"]),
+        (&"LinkCtrl".into(), &16.into(), &0x2040.into())
+    );
+    let version = &ctrl["fields"][4];
+    assert_eq!(version["kind"], "fixed");
+    assert_eq!(version["access"], "read-only");
+    assert_eq!(version["reset"], 2);
+    assert_eq!(ctrl["fields"][1]["variants"][2]["name"], "Fast");
+    let ack = &json["specs"][1]["variants"]["views"][1];
+    assert_eq!(ack["tag_value"], 2);
+    assert_eq!(ack["layout"]["fields"][0]["kind"], "fixed");
+}
@@ -0,0 +1,128 @@
+use dials::{spec, FieldsError, RangeError, ValidationError};
+
+spec! {
+    struct Rbga {
+        red: u8,
+        blue: u8,
+        green: u8,
+        alpha: u8,
+    }
+}
+
+spec! {
+    struct Status {
+        ready: bool,
+        mode: u3,
+        count: u12,
+    }
+}
+
+spec! {
+    enum Speed {
+        Off = 0,
+        Slow = 1,
+        Fast = 3,
+    }
+
+    struct Link {
+        enable: bool,
+        speed: u2 as Speed,
+    }
+}
+
+#[test]
+fn unpacks_into_plain_fields() {
+    let mut x = Rbga(0);
+    x.set_red(0x12).set_alpha(0xff);
+    let fields = RbgaFields::from(x);
+    assert_eq!(
+        fields,
+        RbgaFields {
+            red: 0x12,
+            blue: 0,
+            green: 0,
+            alpha: 0xff,
+        }
+    );
+
+    let RbgaFields { red, alpha, .. } = fields;
+    assert_eq!((red, alpha), (0x12_u8, 0xff_u8));
+}
+
+#[test]
+fn packs_struct_literals() {
+    let s = Status::try_from(StatusFields {
+        ready: true,
+        mode: 5,
+        count: 0xabc,
+    })
+    .unwrap();
+    assert_eq!(s.0, 1 | 5 << 1 | 0xabc << 4);
+    assert_eq!(
+        StatusFields::from(s),
+        StatusFields {
+            ready: true,
+            mode: 5,
+            count: 0xabc,
+        }
+    );
+
+    let x = Rbga::try_from(RbgaFields {
+        red: 1,
+        blue: 2,
+        green: 3,
+        alpha: 4,
+    })
+    .unwrap();
+    assert_eq!(x.0, 0x0403_0201);
+}
+
+#[test]
+fn rejects_out_of_range_fields() {
+    let err = Status::try_from(StatusFields {
+        mode: 8,
+        ..Default::default()
+    })
+    .err()
+    .unwrap();
+    assert_eq!(
+        err,
+        FieldsError::OutOfRange(RangeError {
+            field: "mode",
+            value: "8".to_string(),
+            bits: 3
+        })
+    );
+    assert_eq!(
+        err.to_string(),
+        "value 8 does not fit in 3-bit field `mode`"
+    );
+}
+
+#[test]
+fn rejects_invalid_enum_fields() {
+    let link = Link::try_from(LinkFields {
+        enable: true,
+        speed: 3,
+    })
+    .unwrap();
+    assert_eq!(link.speed(), Some(Speed::Fast));
+
+    let err = Link::try_from(LinkFields {
+        enable: true,
+        speed: 2,
+    })
+    .err()
+    .unwrap();
+    assert_eq!(
+        err,
+        FieldsError::Invalid(ValidationError::InvalidDiscriminant {
+            field: "speed",
+            value: 2
+        })
+    );
+    assert_eq!(
+        err.to_string(),
+        "0x2 is not a valid value of enum field `speed`"
+    );
+}
@@ -0,0 +1,28 @@
+use dials::{include_spec, FieldEnum};
+
+include_spec!("tests/layouts/link.toml");
+include_spec!("tests/layouts/status.json");
+
+#[test]
+fn toml_layout() {
+    assert_eq!(VERSION, 2);
+    assert_eq!(Speed::from_bits(2), Some(Speed::Slow));
+    assert_eq!(Speed::Fast.to_bits(), 3);
+
+    let mut ctrl = Ctrl::new();
+    assert_eq!(ctrl.0, 0xc50);
+    assert_eq!((ctrl.version(), ctrl.retries(), ctrl.mode()), (2, 1, 2));
+    ctrl.set_speed(Speed::Fast).set_enable();
+    assert_eq!(ctrl.0, 0xc57);
+    assert_eq!(ctrl.speed(), Some(Speed::Fast));
+    assert!(Ctrl::matches(0xc00 | 0x10));
+}
+
+#[test]
+fn json_layout() {
+    let mut status = LinkStatus(0);
+    status.set_up().set_errors(3);
+    status.set_temperature_bits(130);
+    assert_eq!(status.0, 0x8207);
+    assert_eq!(status.temperature(), 25.0);
+}
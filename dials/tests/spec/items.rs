@@ -0,0 +1,92 @@
+use dials::{spec, FieldEnum};
+
+spec! {
+    /// The version of the register layout.
+    const VERSION: u8 = 2;
+
+    const KIND_STATUS: u8 = 1;
+
+    const GAIN: f32 = 1.5;
+
+    const NAME: &str = "link";
+
+    /// How fast the link runs.
+    enum Speed {
+        Off,
+        Slow = 2,
+        Fast,
+    }
+
+    struct Ctrl {
+        enable: bool,
+        speed: u2 as Speed,
+        version: u3 == VERSION,
+    }
+
+    struct Status {
+        speed: u2 as Speed,
+        errors: u4 @ 4..8,
+    }
+
+    enum Magic {
+        Empty,
+        Boot = 0xb007_0000_0000_0001,
+    }
+
+    struct Header {
+        magic: u64 as Magic,
+    }
+
+    struct Message {
+        kind: u2,
+        match kind {
+            0 => Command { ctrl: u6 },
+            KIND_STATUS => Report { status: u8 },
+        }
+    }
+}
+
+#[test]
+fn structs_share_enums() {
+    assert_eq!(Speed::Off.to_bits(), 0);
+    assert_eq!(Speed::from_bits(3), Some(Speed::Fast));
+    assert_eq!(Speed::from_bits(1), None);
+
+    let mut ctrl = Ctrl::new();
+    ctrl.set_speed(Speed::Fast).set_enable();
+    let mut status = Status(0);
+    status.set_speed(ctrl.speed().unwrap());
+    assert_eq!(status.speed(), Some(Speed::Fast));
+}
+
+#[test]
+fn enums_hold_discriminants_beyond_isize() {
+    assert_eq!(Magic::Boot.to_bits(), 0xb007_0000_0000_0001);
+    assert_eq!(Magic::Boot as u64, 0xb007_0000_0000_0001);
+
+    let mut header = Header(0);
+    header.set_magic(Magic::Boot);
+    assert_eq!(header.0, 0xb007_0000_0000_0001);
+    assert_eq!(header.magic(), Some(Magic::Boot));
+}
+
+#[test]
+fn constants_stand_in_for_integers() {
+    assert_eq!(VERSION, 2);
+    assert_eq!(Ctrl::new().version(), VERSION);
+    assert!(Ctrl::matches(0x10));
+
+    let mut report = MessageReport::new();
+    report.set_status(0xab);
+    assert_eq!(report.kind(), KIND_STATUS as u16);
+    assert!(matches!(
+        Message(report.0).decode(),
+        Some(MessageVariant::Report(_))
+    ));
+}
+
+#[test]
+fn other_constants_are_passed_through() {
+    assert_eq!(GAIN, 1.5);
+    assert_eq!(NAME, "link");
+}
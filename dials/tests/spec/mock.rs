@@ -0,0 +1,81 @@
+use dials::bus::{Bus, Register};
+use dials::mock::{Direction, MockBus};
+use dials::{register_block, spec, FieldValue};
+
+spec! {
+    struct Ctrl {
+        start: bool,
+        mode: u3,
+    }
+
+    struct Status {
+        ready: bool,
+        #[reserved]
+        rsvd: u7,
+        count: u8,
+    }
+}
+
+register_block! {
+    struct Adc {
+        0x0 => ctrl: Ctrl,
+        0x2 => status: Status,
+        0x4..0xc => samples: [u32; 2],
+    }
+}
+
+/// Driver code, written once against any bus.
+fn convert<B: Bus>(adc: &AdcRegs<B>, mode: u8) -> u32 {
+    adc.modify_ctrl(|ctrl| {
+        ctrl.set_mode(mode).set_start();
+    });
+    while !adc.status().ready() {}
+    adc.samples(usize::from(adc.status().count()) - 1)
+}
+
+#[test]
+fn scripted_hardware() {
+    let bus = MockBus::new();
+    bus.memory_mut().set(Adc::SAMPLES_OFFSET + 4, 0x3ffu32);
+    bus.on_write("ctrl", |write, memory| {
+        if Ctrl::from_raw(write.value).start() {
+            memory.modify(Adc::STATUS_OFFSET, |status: &mut Status| {
+                status.set_ready().set_count(2);
+            });
+        }
+    });
+
+    let adc = AdcRegs(&bus);
+    assert_eq!(convert(&adc, 5), 0x3ff);
+
+    let log = bus.take_transactions();
+    let lines = log.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            "read ctrl = 0x0 (start: false, mode: 0)",
+            "write ctrl = 0xb (start: true, mode: 5)",
+            "read status = 0x201 (ready: true, rsvd: 0, count: 2)",
+            "read status = 0x201 (ready: true, rsvd: 0, count: 2)",
+            "read samples[1] = 0x3ff",
+        ]
+    );
+    assert_eq!(log[1].direction, Direction::Write);
+    assert_eq!(log[1].fields[1], ("mode", FieldValue::Uint(5)));
+    assert_eq!(log[4].offset, 8);
+    assert!(bus.transactions().is_empty());
+}
+
+#[test]
+fn clear_on_read() {
+    let bus = MockBus::new();
+    bus.memory_mut().set(Adc::STATUS_OFFSET, Status(0x0301));
+    bus.on_read("status", |_, memory| {
+        memory.set(Adc::STATUS_OFFSET, Status(0));
+    });
+
+    let adc = AdcRegs(&bus);
+    assert_eq!(adc.status().count(), 3);
+    assert_eq!(adc.status().count(), 0);
+    assert_eq!(bus.memory().read(Adc::STATUS_OFFSET, 2), 0);
+}
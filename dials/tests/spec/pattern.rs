@@ -0,0 +1,112 @@
+use dials::{pat, spec};
+
+spec! {
+    struct Rbga {
+        red: u8,
+        blue: u8,
+        green: u8,
+        alpha: u8,
+    }
+}
+
+spec! {
+    struct Insn {
+        opcode: u7 == 0b0010011,
+        rd: u5,
+        funct3: u3,
+        rs1: u5,
+        imm: i12,
+    }
+}
+
+spec! {
+    struct Jump {
+        link: bool,
+        offset: i9 = [8..5, 4..1] << 1,
+    }
+}
+
+#[test]
+fn pair() {
+    let (mask, value) = pat!(Rbga {
+        red: 0xff,
+        alpha: 0,
+        ..
+    });
+    assert_eq!(mask, 0xff00_00ff);
+    assert_eq!(value, 0x0000_00ff);
+
+    assert_eq!(pat!(Rbga { .. }), (0, 0));
+}
+
+#[test]
+fn fixed_fields_are_included() {
+    let (mask, value) = pat!(Insn {
+        rd: 0,
+        funct3: 0b111,
+        ..
+    });
+    assert_eq!(mask, 0x0000_7fff);
+    assert_eq!(value, 0x0000_7013);
+
+    assert_eq!(pat!(Insn { .. }), (Insn::MATCH_MASK, Insn::MATCH_VALUE));
+}
+
+#[test]
+fn signed_and_bool_fields() {
+    assert_eq!(pat!(Insn { imm: -1 }), (0xfff0_007f, 0xfff0_0013));
+    assert_eq!(
+        pat!(Jump {
+            link: true,
+            offset: -2
+        }),
+        (0x1ff, 0x1ff)
+    );
+    assert_eq!(
+        pat!(Jump {
+            link: false,
+            offset: 4
+        }),
+        (0x1ff, 0x004)
+    );
+}
+
+#[test]
+fn matches_raw_values() {
+    const RET: (u32, u32) = pat!(Insn {
+        rd: 0,
+        funct3: 0,
+        imm: 0,
+        ..
+    });
+
+    let word = 0x0000_0013;
+    assert!(word & RET.0 == RET.1);
+    assert!(pat!(word, Insn { rd: 0, .. }));
+    assert!(!pat!(word | 0x80, Insn { rd: 0, .. }));
+
+    let kind = match 0x8000_00ffu32 {
+        raw if pat!(
+            raw,
+            Rbga {
+                red: 0xff,
+                alpha: 0
+            }
+        ) =>
+        {
+            "opaque red"
+        }
+        raw if pat!(
+            raw,
+            Rbga {
+                red: 0xff,
+                alpha: 0x80
+            }
+        ) =>
+        {
+            "translucent red"
+        }
+        _ => "other",
+    };
+    assert_eq!(kind, "translucent red");
+}
@@ -0,0 +1,70 @@
+use dials::{register_block, spec};
+
+spec! {
+    struct Ctrl {
+        enable: bool,
+        baud: u4,
+    }
+
+    struct Status {
+        busy: bool,
+        errors: u7 @ 8..15,
+    }
+}
+
+register_block! {
+    /// A UART with a small receive FIFO.
+    pub struct Uart {
+        0x00 => ctrl: Ctrl,
+        0x04 => status: Status,
+        0x08..0x18 => fifo: [u32; 4],
+        0x20 => scratch: u64,
+    }
+}
+
+#[test]
+fn registers_sit_at_their_offsets() {
+    assert_eq!(core::mem::offset_of!(Uart, ctrl), 0x00);
+    assert_eq!(core::mem::offset_of!(Uart, status), 0x04);
+    assert_eq!(core::mem::offset_of!(Uart, fifo), 0x08);
+    assert_eq!(core::mem::offset_of!(Uart, scratch), 0x20);
+    assert_eq!(core::mem::size_of::<Uart>(), 0x28);
+    assert_eq!(Uart::STATUS_OFFSET, 4);
+    assert_eq!(Uart::FIFO_OFFSET, 8);
+}
+
+#[test]
+fn mmio_accessor() {
+    let mut memory = [0u64; 5];
+    let uart = unsafe { UartRegs::from_ptr(memory.as_mut_ptr().cast()) };
+
+    uart.set_ctrl(Ctrl(0x1f));
+    uart.set_status(Status(0x0200));
+    assert!(uart.ctrl().enable());
+    assert_eq!(uart.ctrl().baud(), 0xf);
+    assert_eq!(uart.status().errors(), 0x2);
+
+    uart.modify_ctrl(|ctrl| {
+        ctrl.unset_enable().set_baud(3);
+    });
+    assert_eq!(uart.ctrl().0, 0x06);
+    assert_eq!(uart.status().0, 0x0200);
+
+    uart.set_fifo(2, 0xdead_beef);
+    uart.set_scratch(u64::MAX);
+    assert_eq!(uart.fifo(2), 0xdead_beef);
+    assert_eq!(uart.fifo(1), 0);
+    assert_eq!(
+        unsafe { *uart.0.base().cast::<u32>().add(0x10 / 4) },
+        0xdead_beef
+    );
+    assert_eq!(memory[4], u64::MAX);
+}
+
+#[test]
+#[should_panic]
+fn mmio_array_bounds() {
+    let mut memory = [0u64; 5];
+    let uart = unsafe { UartRegs::from_ptr(memory.as_mut_ptr().cast()) };
+    uart.fifo(4);
+}
@@ -0,0 +1,58 @@
+use dials::layout::Access;
+use dials::spec;
+
+spec! {
+    /// A status register as read after reset.
+    #[reset(0x8001)]
+    struct Status {
+        #[access(read_only)]
+        ready: bool,
+        #[reserved]
+        rsvd: u7,
+        #[access(write_once)]
+        lock: u8,
+    }
+}
+
+spec! {
+    #[reset(0xffff)]
+    struct Config {
+        mode: u4,
+        #[reserved]
+        rsvd: u4,
+        version: u4 == 2,
+        #[access(write_only)]
+        key: u4,
+    }
+}
+
+#[test]
+fn new_starts_from_the_reset_value() {
+    let status = Status::new();
+    assert_eq!(status.0, 0x8001);
+    assert!(status.ready());
+    assert_eq!(status.lock(), 0x80);
+    assert_eq!(Status::default().0, status.0);
+}
+
+#[test]
+fn required_fields_override_the_reset_value() {
+    let mut config = Config::new();
+    assert_eq!(config.0, 0xf20f);
+    assert_eq!((config.mode(), config.key()), (0xf, 0xf));
+    config.set_mode(1);
+    assert_eq!(config.0, 0xf201);
+    assert_eq!(Config::LAYOUT.reset, 0xf20f);
+}
+
+#[test]
+fn access_is_recorded_in_the_layout() {
+    let access = |name| Status::LAYOUT.field(name).unwrap().access;
+    assert_eq!(access("ready"), Access::ReadOnly);
+    assert_eq!(access("lock"), Access::WriteOnce);
+
+    let access = |name| Config::LAYOUT.field(name).unwrap().access;
+    assert_eq!(access("mode"), Access::ReadWrite);
+    assert_eq!(access("version"), Access::ReadOnly);
+    assert_eq!(access("key"), Access::WriteOnly);
+}
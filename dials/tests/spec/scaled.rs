@@ -0,0 +1,76 @@
+use dials::{spec, RangeError};
+
+spec! {
+    struct Sensor {
+        temp: q4.4,
+        voltage: u10 * 0.0125 + 0.5,
+        gain: ufix<8, 3>,
+        bias: fix<6, 2>,
+        current: i8 * 0.5 - 1,
+    }
+}
+
+#[test]
+fn fixed_point_getters() {
+    let mut s = Sensor(0);
+    s.set_temp_bits(-24);
+    assert_eq!(s.temp(), -1.5);
+    s.set_gain_bits(0b1010_1100);
+    assert_eq!(s.gain(), 21.5);
+    s.set_bias_bits(-1);
+    assert_eq!(s.bias(), -0.25);
+}
+
+#[test]
+fn scaled_getters() {
+    let mut s = Sensor(0);
+    assert_eq!(s.voltage(), 0.5);
+    s.set_voltage_bits(200);
+    assert_eq!(s.voltage(), 3.0);
+    assert_eq!(s.current(), -1.0);
+    s.set_current_bits(-6);
+    assert_eq!(s.current(), -4.0);
+
+    assert_eq!(Sensor::VOLTAGE_SCALE, 0.0125);
+    assert_eq!(Sensor::VOLTAGE_OFFSET, 0.5);
+    assert_eq!(Sensor::TEMP_SCALE, 0.0625);
+}
+
+#[test]
+fn setters_round() {
+    let mut s = Sensor(0);
+    assert!(s.set_voltage(1.7).is_ok());
+    assert_eq!(s.voltage_bits(), 96);
+    assert!(s.set_voltage(1.706).is_ok());
+    assert_eq!(s.voltage_bits(), 96);
+    assert!(s.set_voltage(1.707).is_ok());
+    assert_eq!(s.voltage_bits(), 97);
+
+    assert!(s.set_temp(-3.03).is_ok());
+    assert_eq!(s.temp(), -3.0);
+    assert_eq!(s.voltage_bits(), 97);
+}
+
+#[test]
+fn setters_range_check() {
+    let mut s = Sensor(0);
+    s.set_voltage_bits(5);
+    assert_eq!(
+        s.set_voltage(0.4).err(),
+        Some(RangeError {
+            field: "voltage",
+            value: "0.4".to_string(),
+            bits: 10,
+        })
+    );
+    assert!(s.set_voltage(0.5 + 1023.0 * 0.0125).is_ok());
+    assert!(s.set_voltage(0.5 + 1024.0 * 0.0125).is_err());
+    assert_eq!(s.voltage_bits(), 1023);
+
+    assert!(s.set_temp(7.9375).is_ok());
+    assert!(s.set_temp(-8.0).is_ok());
+    assert!(s.set_temp(8.0).is_err());
+    assert!(s.set_temp(-8.05).is_err());
+    assert!(s.set_temp(f64::NAN).is_err());
+    assert_eq!(s.temp(), -8.0);
+}
@@ -0,0 +1,229 @@
+use dials::{spec, FieldEnum};
+use serde_test::{assert_ser_tokens, Configure, Token};
+
+spec! {
+    struct Rbga {
+        red: u8,
+        blue: u8,
+        green: u8,
+        alpha: u8,
+    }
+}
+
+spec! {
+    #[serde(map)]
+    struct Status {
+        ready: bool,
+        mode: u3,
+    }
+}
+
+spec! {
+    #[serde(raw)]
+    struct Word {
+        low: u4,
+        high: u3,
+    }
+}
+
+spec! {
+    #[serde(raw)]
+    struct Padded {
+        low: u4,
+        #[reserved]
+        rsvd: u4,
+    }
+}
+
+spec! {
+    struct Instr {
+        opcode: u7,
+        match opcode {
+            0b0110011 => R { rd: u5, funct3: u3, rs1: u5, rs2: u5, funct7: u7 },
+            0b0010011 => I { rd: u5, funct3: u3, rs1: u5, imm: u12 },
+        }
+    }
+}
+
+spec! {
+    #[serde(raw)]
+    struct RawInstr {
+        opcode: u7,
+        match opcode {
+            0b0110011 => R { rd: u5, funct3: u3, rs1: u5, rs2: u5 },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Off = 0,
+    Slow = 1,
+    Fast = 3,
+}
+
+impl FieldEnum for Mode {
+    fn from_bits(bits: u128) -> Option<Self> {
+        match bits {
+            0 => Some(Self::Off),
+            1 => Some(Self::Slow),
+            3 => Some(Self::Fast),
+            _ => None,
+        }
+    }
+
+    fn to_bits(&self) -> u128 {
+        *self as u128
+    }
+}
+
+spec! {
+    #[serde(map)]
+    struct Ctrl {
+        enable: bool,
+        mode: u2 as Mode,
+        level: u4,
+    }
+}
+
+#[test]
+fn auto_uses_fields_for_human_readable_formats() {
+    let mut x = Rbga(0);
+    x.set_red(0x12).set_alpha(3);
+    let json = serde_json::to_string(&x).unwrap();
+    assert_eq!(json, r#"{"red":18,"blue":0,"green":0,"alpha":3}"#);
+    assert_eq!(serde_json::from_str::<Rbga>(&json).unwrap().0, x.0);
+}
+
+#[test]
+fn auto_uses_raw_for_compact_formats() {
+    assert_ser_tokens(&Rbga(0x0300_0012).compact(), &[Token::U32(0x0300_0012)]);
+    assert_ser_tokens(
+        &Rbga(0x12).readable(),
+        &[
+            Token::Struct {
+                name: "Rbga",
+                len: 4,
+            },
+            Token::Str("red"),
+            Token::U32(0x12),
+            Token::Str("blue"),
+            Token::U32(0),
+            Token::Str("green"),
+            Token::U32(0),
+            Token::Str("alpha"),
+            Token::U32(0),
+            Token::StructEnd,
+        ],
+    );
+}
+
+#[test]
+fn map_and_raw_are_chosen_per_spec() {
+    let mut s = Status(0);
+    s.set_ready().set_mode(5);
+    assert_eq!(
+        serde_json::to_string(&s).unwrap(),
+        r#"{"ready":true,"mode":5}"#
+    );
+    assert_ser_tokens(
+        &s.compact(),
+        &[
+            Token::Struct {
+                name: "Status",
+                len: 2,
+            },
+            Token::Str("ready"),
+            Token::Bool(true),
+            Token::Str("mode"),
+            Token::U8(5),
+            Token::StructEnd,
+        ],
+    );
+
+    assert_eq!(serde_json::to_string(&Word(0x5a)).unwrap(), "90");
+    assert_eq!(serde_json::from_str::<Word>("90").unwrap().0, 0x5a);
+}
+
+#[test]
+fn deserialize_validates_fields() {
+    let err = |json| {
+        serde_json::from_str::<Status>(json)
+            .err()
+            .unwrap()
+            .to_string()
+    };
+    assert!(err(r#"{"ready":true,"mode":8}"#).contains("does not fit in 3-bit field `mode`"));
+    assert!(err(r#"{"ready":true,"mode":1,"mode":2}"#).contains("duplicate field `mode`"));
+    assert!(err(r#"{"ready":true,"mode":1,"speed":2}"#).contains("unknown field `speed`"));
+    assert!(err(r#"{"ready":true}"#).contains("missing field `mode`"));
+    assert_eq!(
+        serde_json::from_str::<Status>("[true,6]").unwrap().mode(),
+        6
+    );
+}
+
+#[test]
+fn deserialize_fields_rejects_invalid_discriminants() {
+    let ctrl = serde_json::from_str::<Ctrl>(r#"{"enable":true,"mode":3,"level":1}"#).unwrap();
+    assert_eq!(ctrl.mode(), Some(Mode::Fast));
+
+    let invalid = "0x2 is not a valid value of enum field `mode`";
+    let err = serde_json::from_str::<Ctrl>(r#"{"enable":true,"mode":2,"level":1}"#)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains(invalid));
+    let err = serde_json::from_str::<Ctrl>("[true,2,1]").err().unwrap();
+    assert!(err.to_string().contains(invalid));
+}
+
+#[test]
+fn deserialize_raw_rejects_unused_bits() {
+    let err = serde_json::from_str::<Word>("128").err().unwrap();
+    assert!(err
+        .to_string()
+        .contains("bits 0x80 that are not part of any field"));
+}
+
+#[test]
+fn deserialize_raw_validates_reserved_bits() {
+    assert_eq!(serde_json::from_str::<Padded>("15").unwrap().low(), 15);
+    let err = serde_json::from_str::<Padded>("16").err().unwrap();
+    assert!(err
+        .to_string()
+        .contains("field `rsvd` must be 0x0, found 0x1"));
+}
+
+#[test]
+fn tagged_specs_round_trip_their_variant() {
+    // add x3, x1, x2
+    let json = serde_json::to_string(&Instr(0x0020_81b3)).unwrap();
+    assert_eq!(
+        json,
+        r#"{"opcode":51,"rd":3,"funct3":0,"rs1":1,"rs2":2,"funct7":0}"#
+    );
+    assert_eq!(serde_json::from_str::<Instr>(&json).unwrap().0, 0x0020_81b3);
+
+    // The tag may come after the fields it selects.
+    let addi = r#"{"rd":5,"funct3":0,"rs1":6,"imm":4095,"opcode":19}"#;
+    assert_eq!(serde_json::from_str::<Instr>(addi).unwrap().0, 0xfff3_0293);
+    let err = serde_json::from_str::<Instr>(r#"{"opcode":19,"rd":5,"rs2":1}"#)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("unknown field `rs2`"));
+
+    assert_eq!(
+        serde_json::to_string(&RawInstr(0x0020_81b3)).unwrap(),
+        "2130355"
+    );
+    assert_eq!(
+        serde_json::from_str::<RawInstr>("2130355").unwrap().0,
+        0x0020_81b3
+    );
+    let err = serde_json::from_str::<RawInstr>("2147483648")
+        .err()
+        .unwrap();
+    assert!(err
+        .to_string()
+        .contains("sets bits 0x80000000 that are not part of any field of `RawInstr`"));
+}
@@ -0,0 +1,79 @@
+use dials::spec;
+
+spec! {
+    struct BType {
+        opcode: u7,
+        imm: i13 = [31, 7, 30..25, 11..8] << 1,
+        funct3: u3 @ 12..15,
+        rs1: u5,
+        rs2: u5,
+        #[alias]
+        raw_imm: u12 = [31, 7, 30..25, 11..8],
+    }
+}
+
+spec! {
+    struct Offsets {
+        small: i4,
+        wide: i12,
+        flag: bool,
+    }
+}
+
+#[test]
+fn split_fields_reassemble_their_bits() {
+    // beq x1, x2, -8
+    let beq = BType(0xfe20_8ce3);
+    assert_eq!(beq.opcode(), 0b1100011);
+    assert_eq!((beq.rs1(), beq.rs2(), beq.funct3()), (1, 2, 0));
+    assert_eq!(beq.imm(), -8);
+    assert_eq!(beq.raw_imm(), 0xffc);
+    assert_eq!(BType::IMM, 0xfe00_0f80);
+    assert_eq!(BType::IMM_SIZE, 13);
+    assert_eq!(BType::RS1_START, 15);
+}
+
+#[test]
+fn split_fields_scatter_their_bits() {
+    let mut b = BType(0);
+    b.set_imm(0x7fe);
+    assert_eq!(b.0, 0x7e00_0f00);
+    assert_eq!(b.imm(), 0x7fe);
+
+    b.set_opcode(0b1100011).set_rs1(1).set_rs2(2).set_imm(-8);
+    assert_eq!(b.0, 0xfe20_8ce3);
+
+    // The implied low bit is dropped.
+    b.set_imm(5);
+    assert_eq!(b.imm(), 4);
+    assert_eq!((b.rs1(), b.rs2()), (1, 2));
+}
+
+#[test]
+fn signed_fields_sign_extend() {
+    let mut x = Offsets(0);
+    x.set_small(-3).set_wide(-2048).set_flag();
+    assert_eq!(x.small(), -3);
+    assert_eq!(x.wide(), -2048);
+    assert!(x.flag());
+    assert_eq!(x.0 & 0xf, 0b1101);
+
+    x.set_small(9);
+    assert_eq!(x.small(), -7);
+
+    let parsed: Offsets = "small=-8,wide=2047".parse().unwrap();
+    assert_eq!((parsed.small(), parsed.wide()), (-8, 2047));
+    assert_eq!(parsed.to_string(), "small=-8,wide=2047,flag=false");
+    assert!("small=8".parse::<Offsets>().is_err());
+    assert!("small=-9".parse::<Offsets>().is_err());
+
+    let fields = OffsetsFields::from(parsed);
+    assert_eq!((fields.small, fields.wide), (-8_i8, 2047_i16));
+    let err = Offsets::try_from(OffsetsFields { small: 8, ..fields })
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "value 8 does not fit in 4-bit field `small`"
+    );
+}
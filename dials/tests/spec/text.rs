@@ -0,0 +1,158 @@
+use dials::{spec, FieldEnum, ParseError, ValidationError};
+
+spec! {
+    struct Rbga {
+        red: u8,
+        blue: u8,
+        green: u8,
+        alpha: u8,
+    }
+}
+
+spec! {
+    struct Status {
+        ready: bool,
+        mode: u3,
+        error: bool,
+    }
+}
+
+spec! {
+    struct Flags {
+        a: bool,
+        b: bool,
+        c: bool,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Off = 0,
+    Slow = 1,
+    Fast = 3,
+}
+
+impl FieldEnum for Mode {
+    fn from_bits(bits: u128) -> Option<Self> {
+        match bits {
+            0 => Some(Self::Off),
+            1 => Some(Self::Slow),
+            3 => Some(Self::Fast),
+            _ => None,
+        }
+    }
+
+    fn to_bits(&self) -> u128 {
+        *self as u128
+    }
+}
+
+spec! {
+    struct Ctrl {
+        enable: bool,
+        mode: u2 as Mode,
+        level: u4,
+    }
+}
+
+#[test]
+fn fields_round_trip() {
+    let x: Rbga = "red=0x12,alpha=3".parse().unwrap();
+    assert_eq!(x.red(), 0x12);
+    assert_eq!(x.blue(), 0);
+    assert_eq!(x.alpha(), 3);
+    assert_eq!(x.to_string(), "red=18,blue=0,green=0,alpha=3");
+    assert_eq!(x.to_string().parse::<Rbga>().unwrap().0, x.0);
+
+    let s: Status = " ready = true , mode = 0b101 ".parse().unwrap();
+    assert!(s.ready());
+    assert_eq!(s.mode(), 5);
+    assert!(!s.error());
+    assert_eq!(s.to_string(), "ready=true,mode=5,error=false");
+}
+
+#[test]
+fn fields_report_errors() {
+    assert_eq!(
+        "red=1,purple=2".parse::<Rbga>().err(),
+        Some(ParseError::UnknownField("purple".to_string()))
+    );
+    assert_eq!(
+        "red=1,red=2".parse::<Rbga>().err(),
+        Some(ParseError::DuplicateField("red"))
+    );
+    assert_eq!(
+        "mode=8".parse::<Status>().err(),
+        Some(ParseError::OutOfRange {
+            field: "mode",
+            value: "8".to_string(),
+            bits: 3
+        })
+    );
+    assert_eq!(
+        "ready=yes".parse::<Status>().err(),
+        Some(ParseError::InvalidValue {
+            field: "ready",
+            value: "yes".to_string()
+        })
+    );
+    assert_eq!(
+        "red".parse::<Rbga>().err(),
+        Some(ParseError::Malformed("red".to_string()))
+    );
+    assert_eq!(
+        "red=0x100".parse::<Rbga>().err().unwrap().to_string(),
+        "value `0x100` does not fit in 8-bit field `red`"
+    );
+}
+
+#[test]
+fn fields_reject_invalid_discriminants() {
+    let ctrl: Ctrl = "enable=true,mode=3,level=1".parse().unwrap();
+    assert_eq!(ctrl.mode(), Some(Mode::Fast));
+    assert_eq!(
+        "mode=2".parse::<Ctrl>().err(),
+        Some(ParseError::Invalid(ValidationError::InvalidDiscriminant {
+            field: "mode",
+            value: 2
+        }))
+    );
+    assert_eq!(
+        "mode=2".parse::<Ctrl>().err().unwrap().to_string(),
+        "0x2 is not a valid value of enum field `mode`"
+    );
+}
+
+#[test]
+fn flag_sets_round_trip() {
+    let x: Flags = "A|C".parse().unwrap();
+    assert!(x.a() && !x.b() && x.c());
+    assert_eq!(x.to_string(), "A|C");
+    assert_eq!(Flags(0).to_string(), "");
+    assert_eq!("".parse::<Flags>().unwrap().0, 0);
+    assert_eq!(" b | c ".parse::<Flags>().unwrap().to_string(), "B|C");
+
+    let unnamed = Flags(0b1000_0010);
+    assert_eq!(unnamed.to_string(), "B|0x80");
+    assert_eq!(unnamed.to_string().parse::<Flags>().unwrap().0, unnamed.0);
+}
+
+#[test]
+fn flag_sets_report_errors() {
+    assert_eq!(
+        "A|D".parse::<Flags>().err(),
+        Some(ParseError::UnknownField("D".to_string()))
+    );
+    assert_eq!(
+        "A|a".parse::<Flags>().err(),
+        Some(ParseError::DuplicateField("A"))
+    );
+    assert_eq!(
+        "0x100".parse::<Flags>().err(),
+        Some(ParseError::OutOfRange {
+            field: "Flags",
+            value: "0x100".to_string(),
+            bits: 8
+        })
+    );
+}
@@ -0,0 +1,127 @@
+use dials::{spec, FieldEnum, ValidationError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Off = 0,
+    Slow = 1,
+    Fast = 3,
+}
+
+impl FieldEnum for Mode {
+    fn from_bits(bits: u128) -> Option<Self> {
+        match bits {
+            0 => Some(Self::Off),
+            1 => Some(Self::Slow),
+            3 => Some(Self::Fast),
+            _ => None,
+        }
+    }
+
+    fn to_bits(&self) -> u128 {
+        *self as u128
+    }
+}
+
+spec! {
+    struct Ctrl {
+        enable: bool,
+        mode: u2 as Mode,
+        #[reserved]
+        rsvd0: u3,
+        level: u4,
+        #[reserved(one)]
+        rsvd1: u2,
+        version: u2 == 0b01,
+    }
+}
+
+#[test]
+fn new_holds_reserved_and_fixed_bits() {
+    let ctrl = Ctrl::new();
+    assert_eq!(ctrl.0, 0x1c00);
+    assert_eq!(ctrl.rsvd1(), 0b11);
+    assert!(ctrl.validate().is_ok());
+}
+
+#[test]
+fn enum_fields() {
+    let mut ctrl = Ctrl::new();
+    assert_eq!(ctrl.mode(), Some(Mode::Off));
+    ctrl.set_mode(Mode::Fast).set_level(9);
+    assert_eq!(ctrl.mode(), Some(Mode::Fast));
+    assert_eq!(ctrl.mode_bits(), 3);
+    assert_eq!(ctrl.level(), 9);
+
+    ctrl.set_mode_bits(2);
+    assert_eq!(ctrl.mode(), None);
+    assert_eq!(
+        ctrl.validate(),
+        Err(ValidationError::InvalidDiscriminant {
+            field: "mode",
+            value: 2,
+        })
+    );
+}
+
+#[test]
+fn try_from_raw() {
+    let valid = 0x1e83;
+    assert_eq!(Ctrl::try_from(valid).map(|ctrl| ctrl.level()), Ok(0b1010));
+
+    assert_eq!(
+        Ctrl::try_from(valid | 0b010 << 3).err(),
+        Some(ValidationError::Mismatch {
+            field: "rsvd0",
+            value: 0b010,
+            expected: 0,
+        })
+    );
+    assert_eq!(
+        Ctrl::try_from(valid & !(1 << 10)).err(),
+        Some(ValidationError::Mismatch {
+            field: "rsvd1",
+            value: 0b10,
+            expected: 0b11,
+        })
+    );
+    assert_eq!(
+        Ctrl::try_from(valid ^ (0b11 << 12)).err(),
+        Some(ValidationError::Mismatch {
+            field: "version",
+            value: 0b10,
+            expected: 0b01,
+        })
+    );
+
+    // The tuple constructor doesn't check anything.
+    assert!(Ctrl(0xffff).validate().is_err());
+}
+
+#[test]
+fn text_keeps_reserved_bits() {
+    let ctrl = "enable=1,mode=1,level=2".parse::<Ctrl>().ok().unwrap();
+    assert_eq!(ctrl.to_string(), "enable=true,mode=1,level=2");
+    assert_eq!(ctrl.0, 0x1c83);
+    assert!(ctrl.validate().is_ok());
+}
+
+#[test]
+fn error_messages() {
+    assert_eq!(
+        ValidationError::Mismatch {
+            field: "rsvd1",
+            value: 2,
+            expected: 3,
+        }
+        .to_string(),
+        "field `rsvd1` must be 0x3, found 0x2"
+    );
+    assert_eq!(
+        ValidationError::InvalidDiscriminant {
+            field: "mode",
+            value: 2,
+        }
+        .to_string(),
+        "0x2 is not a valid value of enum field `mode`"
+    );
+}
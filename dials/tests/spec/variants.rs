@@ -0,0 +1,130 @@
+use dials::bus::Register;
+use dials::{spec, FieldValue, ParseError, ValidationError};
+
+spec! {
+    struct Instr {
+        opcode: u7,
+        match opcode {
+            0b0110011 => R { rd: u5, funct3: u3, rs1: u5, rs2: u5, funct7: u7 },
+            0b0010011 => I { rd: u5, funct3: u3, rs1: u5, imm: u12 },
+            0b0100011 => S { imm_lo: u5 @ 7..12, funct3: u3, rs1: u5, rs2: u5, imm_hi: u7 },
+        }
+    }
+}
+
+spec! {
+    enum Width {
+        Byte,
+        Half,
+        Word,
+    }
+
+    struct Access {
+        store: bool,
+        match store {
+            0 => Load { width: u2 as Width },
+            1 => Store { width: u2 as Width, data: u4 },
+        }
+    }
+}
+
+#[test]
+fn decode_dispatches_on_the_tag() {
+    // add x3, x1, x2
+    let add = Instr(0x0020_81b3);
+    match add.decode() {
+        Some(InstrVariant::R(r)) => {
+            assert_eq!((r.rd(), r.rs1(), r.rs2()), (3, 1, 2));
+            assert_eq!((r.funct3(), r.funct7()), (0, 0));
+        }
+        _ => panic!("expected an R-type instruction"),
+    }
+
+    // addi x5, x6, -1
+    let addi = Instr(0xfff3_0293);
+    match addi.decode() {
+        Some(InstrVariant::I(i)) => {
+            assert_eq!((i.rd(), i.rs1(), i.imm()), (5, 6, 0xfff));
+        }
+        _ => panic!("expected an I-type instruction"),
+    }
+
+    assert!(Instr(0b1111111).decode().is_none());
+}
+
+#[test]
+fn variant_constructors_set_the_tag() {
+    let mut r = InstrR::new();
+    assert_eq!(r.opcode(), 0b0110011);
+    r.set_rd(3).set_rs1(1).set_rs2(2);
+    assert_eq!(Instr::from(r).0, 0x0020_81b3);
+
+    let mut s = InstrS::new();
+    s.set_imm_lo(0b10101).set_imm_hi(1);
+    assert_eq!(InstrS::IMM_LO_START, 7);
+    let word = Instr::from(InstrVariant::S(s));
+    assert!(
+        matches!(word.decode(), Some(InstrVariant::S(s)) if s.imm_lo() == 0b10101 && s.imm_hi() == 1)
+    );
+}
+
+#[test]
+fn text_round_trips_through_the_variant() {
+    let add = Instr(0x0020_81b3);
+    assert_eq!(
+        add.to_string(),
+        "opcode=51,rd=3,funct3=0,rs1=1,rs2=2,funct7=0"
+    );
+    assert_eq!(add.to_string().parse::<Instr>().unwrap().0, add.0);
+
+    let addi: Instr = "rd=5, imm=0xfff, opcode=0b0010011, rs1=6".parse().unwrap();
+    assert_eq!(addi.0, 0xfff3_0293);
+    assert!(matches!(
+        "opcode=51,imm=1".parse::<Instr>().err(),
+        Some(ParseError::UnknownField(field)) if field == "imm"
+    ));
+
+    // Unknown tags only have the common fields.
+    assert_eq!(Instr(0b1111111).to_string(), "opcode=127");
+    assert_eq!("opcode=127".parse::<Instr>().unwrap().0, 0b1111111);
+}
+
+#[test]
+fn fields_and_diff_follow_the_variant() {
+    let fields = Instr::fields(0x0020_81b3);
+    assert_eq!(fields.len(), 6);
+    assert!(fields.contains(&("rs2", FieldValue::Uint(2))));
+
+    // add x3, x1, x4
+    let add = Instr(0x0020_81b3);
+    assert_eq!(
+        format!("{:?}", add.diff(&Instr(0x0040_81b3))),
+        "Instr { rs2: 2 -> 4 }"
+    );
+    // Only the fields that the I variant shares with R are compared.
+    assert_eq!(
+        format!("{:?}", add.diff(&Instr(0xfff3_0293))),
+        "Instr { opcode: 51 -> 19, rd: 3 -> 5, rs1: 1 -> 6 }"
+    );
+}
+
+#[test]
+fn validate_checks_the_variant() {
+    assert_eq!(Access(0b100101).to_string(), "store=true,width=2,data=4");
+    assert_eq!(
+        "width=2,store=1,data=4".parse::<Access>().unwrap().0,
+        0b100101
+    );
+
+    assert_eq!(
+        Access(0b111).validate(),
+        Err(ValidationError::InvalidDiscriminant {
+            field: "width",
+            value: 3
+        })
+    );
+    assert!(matches!(
+        "store=true,width=3".parse::<Access>().err(),
+        Some(ParseError::Invalid(_))
+    ));
+}